@@ -0,0 +1,219 @@
+use crate::systemd::Service;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Desktop {
+    Gnome,
+    Kde,
+    Xfce,
+    Other,
+    Headless,
+}
+
+impl Desktop {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Desktop::Gnome => "GNOME",
+            Desktop::Kde => "KDE Plasma",
+            Desktop::Xfce => "Xfce",
+            Desktop::Other => "desktop",
+            Desktop::Headless => "headless system",
+        }
+    }
+}
+
+/// From `XDG_CURRENT_DESKTOP` (e.g. "ubuntu:GNOME", "KDE"); unset means
+/// no graphical session, so treat the machine as headless.
+pub fn detect() -> Desktop {
+    parse_desktop(&std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default())
+}
+
+fn parse_desktop(value: &str) -> Desktop {
+    let names: Vec<String> = value.split(':').map(|n| n.to_lowercase()).collect();
+    if names.iter().any(|n| n == "gnome") {
+        Desktop::Gnome
+    } else if names.iter().any(|n| n == "kde") {
+        Desktop::Kde
+    } else if names.iter().any(|n| n == "xfce") {
+        Desktop::Xfce
+    } else if value.trim().is_empty() {
+        Desktop::Headless
+    } else {
+        Desktop::Other
+    }
+}
+
+/// A service the minimal baseline leaves off, unless the machine is one of
+/// `keep_on` (where the desktop itself relies on it).
+struct Rule {
+    prefix: &'static str,
+    reason: &'static str,
+    keep_on: &'static [Desktop],
+}
+
+const DESKTOPS: &[Desktop] = &[Desktop::Gnome, Desktop::Kde, Desktop::Xfce, Desktop::Other];
+
+const BASELINE: &[Rule] = &[
+    Rule {
+        prefix: "ModemManager",
+        reason: "Only drives mobile-broadband (WWAN/LTE) modems",
+        keep_on: &[],
+    },
+    Rule {
+        prefix: "NetworkManager-wait-online",
+        reason: "Holds up boot until the network is up; desktops connect after login anyway",
+        keep_on: &[],
+    },
+    Rule {
+        prefix: "systemd-networkd-wait-online",
+        reason: "Holds up boot until every link is configured",
+        keep_on: &[],
+    },
+    Rule {
+        prefix: "sshd",
+        reason: "Remote login server; only needed if you SSH into this machine",
+        keep_on: &[Desktop::Headless],
+    },
+    Rule {
+        prefix: "ssh.service",
+        reason: "Remote login server; only needed if you SSH into this machine",
+        keep_on: &[Desktop::Headless],
+    },
+    Rule {
+        prefix: "cups",
+        reason: "Printing; safe to turn off without a printer (it's socket-activated if you keep the socket)",
+        keep_on: &[],
+    },
+    Rule {
+        prefix: "avahi-daemon",
+        reason: "mDNS discovery of printers, AirPlay and .local names on the LAN",
+        keep_on: &[],
+    },
+    Rule {
+        prefix: "bluetooth",
+        reason: "Only needed with Bluetooth keyboards, headsets or other devices",
+        keep_on: &[],
+    },
+    Rule {
+        prefix: "lvm2-monitor",
+        reason: "Monitors LVM snapshots and mirrors; unused without LVM volumes",
+        keep_on: &[],
+    },
+    Rule {
+        prefix: "multipathd",
+        reason: "SAN multipath storage; not found on desktops and laptops",
+        keep_on: &[],
+    },
+    Rule {
+        prefix: "iscsid",
+        reason: "Network block storage (iSCSI) client",
+        keep_on: &[],
+    },
+    Rule {
+        prefix: "rpcbind",
+        reason: "Needed for NFSv3 only; NFSv4 mounts work without it",
+        keep_on: &[],
+    },
+    Rule {
+        prefix: "switcheroo-control",
+        reason: "Only useful on laptops with two GPUs",
+        keep_on: &[],
+    },
+    Rule {
+        prefix: "geoclue",
+        reason: "Location lookups for maps, weather and night light",
+        keep_on: &[Desktop::Gnome],
+    },
+    Rule {
+        prefix: "colord",
+        reason: "Color profiles for calibrated displays and printers",
+        keep_on: &[Desktop::Gnome],
+    },
+    Rule {
+        prefix: "packagekit",
+        reason: "Backend for GUI software stores; updates still work from the terminal",
+        keep_on: &[Desktop::Gnome, Desktop::Kde],
+    },
+    Rule {
+        prefix: "power-profiles-daemon",
+        reason: "Power-mode switching from the desktop's battery menu",
+        keep_on: DESKTOPS,
+    },
+    Rule {
+        prefix: "upower",
+        reason: "Battery status for desktop panels",
+        keep_on: DESKTOPS,
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub service: String,
+    pub reason: &'static str,
+}
+
+/// Enabled (as staged or on disk) services the minimal baseline for
+/// `desktop` would turn off.
+pub fn candidates(desktop: Desktop, services: &[Service]) -> Vec<Candidate> {
+    services
+        .iter()
+        .filter(|svc| svc.enabled && !svc.generated && svc.name.ends_with(".service"))
+        .filter_map(|svc| {
+            let rule = BASELINE
+                .iter()
+                .find(|rule| svc.name.starts_with(rule.prefix))?;
+            (!rule.keep_on.contains(&desktop)).then(|| Candidate {
+                service: svc.name.clone(),
+                reason: rule.reason,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled(name: &str) -> Service {
+        Service {
+            name: name.into(),
+            enabled: true,
+            active: true,
+            enabled_globally: false,
+            generated: false,
+            masked: false,
+            preset: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_desktop() {
+        assert_eq!(parse_desktop("ubuntu:GNOME"), Desktop::Gnome);
+        assert_eq!(parse_desktop("KDE"), Desktop::Kde);
+        assert_eq!(parse_desktop("Hyprland"), Desktop::Other);
+        assert_eq!(parse_desktop(""), Desktop::Headless);
+    }
+
+    #[test]
+    fn test_candidates_respect_desktop() {
+        let services = [
+            enabled("ModemManager.service"),
+            enabled("geoclue.service"),
+            enabled("sshd.service"),
+            enabled("NetworkManager.service"),
+        ];
+        let names = |desktop| {
+            candidates(desktop, &services)
+                .into_iter()
+                .map(|c| c.service)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(Desktop::Gnome),
+            ["ModemManager.service", "sshd.service"]
+        );
+        assert_eq!(
+            names(Desktop::Headless),
+            ["ModemManager.service", "geoclue.service"]
+        );
+    }
+}
@@ -1,16 +1,43 @@
 use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-use crate::categories::{categorize, CATEGORY_ORDER};
+use crate::advisor::{self, Candidate, Desktop};
+use crate::boot::{self, NextBoot};
+use crate::categories::{categorize, is_display_manager, CATEGORY_ORDER};
+use crate::compare::{diff_states, RemoteState, ServiceDiff};
+use crate::config::Config;
+use crate::cron::{self, CronJob};
+use crate::deps::DepTree;
+use crate::descriptions::{current_distro, online};
+use crate::drift::{self, DriftReport};
+use crate::export::{self, Format};
+use crate::hooks::{self, Hook};
+use crate::jobs::{self, Job, JobEvent, QueuedJob};
+use crate::journal::{self, LogView};
+use crate::live::UnitEvent;
+use crate::profiles::{self, ProfileEntry};
+use crate::sched_ext;
 use crate::systemd::{
-    get_service_info, list_services, ChangeAction, ChangeResult, PendingChange, Service,
-    ServiceInfo, ServiceScope,
+    clone_name, clone_unit, daemon_reload, display_manager, enable_now, failed_units,
+    get_service_info, graphical_session_active, graphical_session_services, list_generated,
+    list_services, list_timers, list_triggers, machine, memory_usage, next_timer_runs,
+    paired_trigger, root_services, stop_settings, timer_times, unit_file_text,
+    units_needing_reload, write_drop_in, ApplyEvent, ChangeAction, ChangeResult, PendingChange,
+    Service, ServiceInfo, ServiceScope, SystemctlError, TimerTimes,
 };
+use crate::templates::{self, TEMPLATES};
+use crate::tutorial::{Progress, Step};
+use crate::unit_cache;
 use anyhow::Result;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Tab {
     System,
     User,
+    Global,
+    Timers, // system .timer units
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -20,6 +47,73 @@ pub enum Mode {
     Confirm,
     Applying,
     Info,
+    Compare,
+    Schedule,
+    Drift,
+    StrictConfirm,
+    TemplatePick,
+    Wizard,
+    Clone,
+    Jobs,
+    Pair,
+    Scheduler,
+    Hook,
+    Results,
+    StopTimeout,
+    Logs,
+    QuitConfirm,
+    Follow,
+    NextBoot,
+    Advisor,
+    Profiles,
+    ProfileName,
+    Blame,
+    Deps,
+    UnitFile,
+    Visual,
+    CategoryBulk,
+}
+
+/// In-progress answers for the unit creation wizard.
+#[derive(Debug)]
+pub struct Wizard {
+    pub template: usize,       // index into templates::TEMPLATES
+    pub cron: Option<CronJob>, // converting this instead of a template
+    pub answers: Vec<String>,
+    pub defaults: Vec<String>, // one per prompt
+}
+
+impl Wizard {
+    pub fn title(&self) -> &'static str {
+        match self.cron {
+            Some(_) => "Cron job to timer",
+            None => TEMPLATES[self.template].name,
+        }
+    }
+
+    pub fn prompts(&self) -> &'static [templates::Prompt] {
+        match self.cron {
+            Some(_) => cron::PROMPTS,
+            None => TEMPLATES[self.template].prompts,
+        }
+    }
+}
+
+/// Lines kept in follow mode before the oldest are dropped.
+const FOLLOW_LIMIT: usize = 5000;
+
+/// Failed services whose log lines a refresh reads (one journalctl each).
+const MAX_FAILURE_LOGS: usize = 20;
+
+/// Live `journalctl -f` view. The main loop owns the process and feeds
+/// lines in; a priority change restarts it.
+#[derive(Debug)]
+pub struct LogFollow {
+    pub unit: String,
+    pub lines: Vec<String>,
+    pub priority: Option<u8>,
+    pub paused: bool,
+    pub unseen: usize, // arrived while paused
 }
 
 #[derive(Debug)]
@@ -29,54 +123,286 @@ pub struct CategoryGroup {
     pub collapsed: bool,
 }
 
+/// How the apply in flight is going, as its events arrive.
+#[derive(Debug, Default)]
+pub struct ApplyProgress {
+    pub total: usize,
+    pub done: usize,
+    pub current: Option<String>, // the unit being changed right now
+    pub results: Vec<ChangeResult>,
+    pub cancel: Arc<AtomicBool>, // shared with the apply task
+}
+
+/// What was staged for some units, kept across a refresh so those units
+/// can be staged again.
+#[derive(Debug, Default, Clone)]
+struct Staging {
+    enabled: Vec<(String, bool)>,
+    runtime: Vec<String>,
+    paired: Vec<String>,
+    masks: Vec<(String, bool)>,
+    run_changes: Vec<(String, RunChange)>,
+}
+
+/// Everything staged on the tab at one point, for undo and redo. A
+/// service's staged enablement is its on-disk one flipped when toggled.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    toggled: HashSet<String>,
+    runtime: HashSet<String>,
+    paired: HashSet<String>,
+    masks: std::collections::HashMap<String, bool>,
+    run_changes: std::collections::HashMap<String, RunChange>,
+}
+
+/// A staged change to whether a unit is running, separate from boot state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunChange {
+    Start,
+    Stop,
+    Restart,
+    /// With a staged enable/disable: change boot state only, not now.
+    Keep,
+}
+
+/// Counts behind one chip of the category summary line.
+#[derive(Debug)]
+pub struct CategoryHealth {
+    pub name: &'static str,
+    pub failed: usize,
+    pub dirty: usize, // staged changes
+    pub active: usize,
+    pub total: usize,
+}
+
+/// A configured group of services toggled as one.
+#[derive(Debug)]
+pub struct Role {
+    pub name: String,
+    pub services: Vec<usize>, // installed members, indices into App::services
+}
+
 #[derive(Debug)]
 pub struct App {
     pub services: Vec<Service>,
-    pub toggled: HashSet<String>, // service names with pending changes
+    by_name: std::collections::HashMap<String, usize>, // index into services
+    search_names: Vec<String>,                         // lowercased names, for the filter
+    pub toggled: HashSet<String>,                      // service names with pending changes
+    pub runtime: HashSet<String>,                      // toggled names to apply with --runtime
     pub original_state: std::collections::HashMap<String, bool>, // name -> was_enabled
     pub tab: Tab,
     pub mode: Mode,
     pub filter: String,
     pub categories: Vec<CategoryGroup>,
+    pub roles: Vec<Role>,
     pub cursor: usize, // index into visible_items
     pub visible_items: Vec<VisibleItem>,
     pub results: Vec<ChangeResult>,
+    pub notice: Option<String>,
+    pub boot_offer: bool, // last apply changed system services; B records boot time
+    pub prompt_input: String, // text typed into schedule / strict-confirm prompts
+    pub strict_queue: Vec<String>, // names still to be typed before applying
+    pub strict_then_schedule: bool,
+    pub template_cursor: usize,
+    pub cron_jobs: Vec<CronJob>, // listed after the templates
+    pub wizard: Option<Wizard>,
+    pub clone_source: Option<(String, PathBuf)>, // unit name, fragment path
+    pub needs_reload: Vec<String>,               // units changed on disk since daemon-reload
+    pub root_only: Option<HashSet<String>>,      // "runs as root" filter, when on
+    pub jobs: Vec<Job>,                          // systemd jobs in flight during an apply
+    pub queued_jobs: Vec<QueuedJob>,             // `list-jobs` as of the last check
+    pub failed: HashSet<String>,                 // units in ActiveState=failed
+    pub failure_logs: std::collections::HashMap<String, Vec<String>>, // failed service -> last warnings
+    pub expanded_failures: HashSet<String>, // failed services showing all their lines (`x`)
+    pub session_bound: HashSet<String>,     // user services tied to graphical-session.target
+    pub graphical_session: bool,            // graphical-session.target is active here
+    pub next_runs: std::collections::HashMap<String, u64>, // service -> its timer's next run
+    pub timer_times: std::collections::HashMap<String, TimerTimes>, // Timers tab schedule
+    pub triggers: std::collections::HashMap<String, bool>, // socket/timer -> enabled
+    pub paired: HashSet<String>,            // toggled services whose socket/timer goes with them
+    pub masks: std::collections::HashMap<String, bool>, // staged mask (true) or unmask (false)
+    pub run_changes: std::collections::HashMap<String, RunChange>, // staged with `s`
+    pub retry: HashSet<String>,             // staged again after failing to apply
+    failed_staging: Option<(Tab, Staging)>, // the last apply's failures, to stage again
+    maintenance_change: Option<(bool, Vec<String>)>, // staged by `M`: entering, units
+    undo: Vec<Snapshot>,                    // staging before each change to it, newest last
+    redo: Vec<Snapshot>,                    // what undo took back
+    pub visual_anchor: usize,               // row `V` started the selection on
+    pub bulk_category: Option<&'static str>, // header Space asks about
+    pub progress: ApplyProgress,            // of the apply in flight
+    pub pair_offer: Option<(String, String)>, // service just toggled, its same-named trigger
+    pub sched_ext: Option<String>,          // sched-ext scheduler the kernel is running
+    pub schedulers: Vec<String>,            // what scx_loader offers, while picking
+    pub sched_cursor: usize,
+    pub hook: Option<Hook>, // restart hook shown for review before installing
+    pub display_manager: Option<String>, // target of the display-manager.service alias
+    pub memory_freed: u64,  // bytes the staged stops would free, for review
+    pub slow_stops: std::collections::HashMap<String, u32>, // slow/timed-out stops this session
+    pub stop_timeout_unit: Option<String>, // unit getting a TimeoutStopSec drop-in
+    pub logs: Option<LogView>, // journal snapshot being viewed
+    pub follow: Option<LogFollow>,
+    pub next_boot: NextBoot,     // preview shown by `n`
+    pub advisor: Vec<Candidate>, // minimal-baseline suggestions, while open
+    pub advisor_desktop: Desktop,
+    pub advisor_cursor: usize,
+    pub profiles: Vec<ProfileEntry>, // saved profiles for this scope, while open
+    pub profile_cursor: usize,
+    pub boot_total: Option<f64>, // firmware-to-userspace seconds, for the header
+    pub blame: Vec<(String, f64)>, // this tab's units by startup time, while open
+    pub blame_cursor: usize,
+    pub deps: Option<DepTree>, // `d` dependency tree, while open
+    pub unit_file: Option<(String, String)>, // `c`: unit and its `systemctl cat`
+    pub tutorial: Option<Step>, // `--tutorial`: the hint on screen
     pub info: Option<ServiceInfo>,
+    pub remote: Option<RemoteState>,
+    pub drift: DriftReport,
+    drift_checked: Vec<Tab>,
+    pub modal_scroll: usize,
+    pub list_offset: usize, // first list row on screen, from the last frame
+    pub show_legend: bool,
+    pub units_stale: bool, // the bus saw unit files change; re-list when idle
+    pub config: Config,
     pub should_quit: bool,
+    pub quit_after_apply: bool, // `a` at the quit prompt: quit once it went through
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum VisibleItem {
-    Category(usize), // index into categories
-    Service(usize),  // index into services
+    Category(usize),       // index into categories
+    Service(usize),        // index into services
+    Role(usize),           // index into roles
+    Journal(usize, usize), // a failed service's log line (index into its failure_logs)
+}
+
+/// First of `len` rows to show in a window of `rows` so `cursor` is on
+/// screen, scrolling from `offset` only as far as needed. Also pulls back
+/// when a taller window would leave blank rows past the end.
+pub fn scroll_window(cursor: usize, offset: usize, rows: usize, len: usize) -> usize {
+    let offset = offset.min(len.saturating_sub(rows));
+    if rows == 0 || cursor < offset {
+        cursor
+    } else if cursor >= offset + rows {
+        cursor + 1 - rows
+    } else {
+        offset
+    }
 }
 
 impl App {
-    pub fn new() -> Result<Self> {
+    pub fn new(config: Config) -> Result<Self> {
         let mut app = Self {
             services: Vec::new(),
+            by_name: std::collections::HashMap::new(),
+            search_names: Vec::new(),
             toggled: HashSet::new(),
+            runtime: HashSet::new(),
             original_state: std::collections::HashMap::new(),
             tab: Tab::System,
             mode: Mode::Normal,
             filter: String::new(),
             categories: Vec::new(),
+            roles: Vec::new(),
             cursor: 0,
             visible_items: Vec::new(),
             results: Vec::new(),
+            notice: None,
+            boot_offer: false,
+            prompt_input: String::new(),
+            strict_queue: Vec::new(),
+            strict_then_schedule: false,
+            template_cursor: 0,
+            cron_jobs: Vec::new(),
+            wizard: None,
+            clone_source: None,
+            needs_reload: Vec::new(),
+            root_only: None,
+            jobs: Vec::new(),
+            queued_jobs: Vec::new(),
+            failed: HashSet::new(),
+            failure_logs: std::collections::HashMap::new(),
+            expanded_failures: HashSet::new(),
+            session_bound: HashSet::new(),
+            graphical_session: false,
+            next_runs: std::collections::HashMap::new(),
+            timer_times: std::collections::HashMap::new(),
+            triggers: std::collections::HashMap::new(),
+            paired: HashSet::new(),
+            masks: std::collections::HashMap::new(),
+            run_changes: std::collections::HashMap::new(),
+            retry: HashSet::new(),
+            failed_staging: None,
+            maintenance_change: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            visual_anchor: 0,
+            bulk_category: None,
+            progress: ApplyProgress::default(),
+            pair_offer: None,
+            sched_ext: None,
+            schedulers: Vec::new(),
+            sched_cursor: 0,
+            hook: None,
+            display_manager: None,
+            memory_freed: 0,
+            slow_stops: std::collections::HashMap::new(),
+            stop_timeout_unit: None,
+            logs: None,
+            follow: None,
+            next_boot: NextBoot::default(),
+            advisor: Vec::new(),
+            advisor_desktop: Desktop::Headless,
+            advisor_cursor: 0,
+            profiles: Vec::new(),
+            profile_cursor: 0,
+            boot_total: None,
+            blame: Vec::new(),
+            blame_cursor: 0,
+            deps: None,
+            unit_file: None,
+            tutorial: None,
             info: None,
+            remote: None,
+            drift: DriftReport::default(),
+            drift_checked: Vec::new(),
+            modal_scroll: 0,
+            list_offset: 0,
+            show_legend: false,
+            units_stale: false,
+            config,
             should_quit: false,
+            quit_after_apply: false,
         };
         app.refresh()?;
+        // A container's boot isn't the one systemd-analyze on the host sees
+        if machine().is_none() {
+            app.boot_total = boot::total_secs();
+        }
         Ok(app)
     }
 
     pub fn refresh(&mut self) -> Result<()> {
-        let scope = match self.tab {
-            Tab::System => ServiceScope::System,
-            Tab::User => ServiceScope::User,
+        let scope = self.scope();
+        self.services = self.load_services(&scope)?;
+        self.units_stale = false;
+        self.needs_reload = units_needing_reload(&scope);
+        self.queued_jobs = jobs::list_jobs(&scope);
+        self.failed = failed_units(&scope);
+        self.load_failure_logs();
+        self.check_graphical_session();
+        self.next_runs = next_timer_runs(&scope);
+        self.triggers = list_triggers(&scope);
+        // The kernel and display-manager link are the host's
+        let host = machine().is_none();
+        self.sched_ext = sched_ext::current().filter(|_| host);
+        self.display_manager = match scope {
+            ServiceScope::System if host => display_manager(),
+            _ => None,
         };
-        self.services = list_services(&scope)?;
+
+        // Drift snapshots are per scope (of this host) and hold services only
+        if self.config.track_drift && self.tab != Tab::Timers && host {
+            self.track_drift(&scope);
+        }
 
         self.original_state.clear();
         for svc in &self.services {
@@ -84,17 +410,98 @@ impl App {
         }
 
         self.toggled.clear();
+        self.runtime.clear();
+        self.paired.clear();
+        self.masks.clear();
+        self.run_changes.clear();
+        self.maintenance_change = None;
+        self.retry.clear();
+        // Snapshots are relative to the state just replaced
+        self.undo.clear();
+        self.redo.clear();
         self.rebuild_categories();
         self.rebuild_visible();
         self.cursor = 0;
         Ok(())
     }
 
+    /// This tab's rows as systemd has them now.
+    fn load_services(&mut self, scope: &ServiceScope) -> Result<Vec<Service>> {
+        let mut services = if self.tab == Tab::Timers {
+            self.timer_times = timer_times(scope);
+            list_timers(scope)?
+        } else {
+            self.timer_times.clear();
+            list_services(scope)?
+        };
+        if self.config.show_generated && self.tab != Tab::Timers {
+            services.extend(list_generated(scope));
+        }
+        Ok(services)
+    }
+
+    /// On the User tab, note which services wait for a desktop login.
+    fn check_graphical_session(&mut self) {
+        if self.tab != Tab::User {
+            self.session_bound.clear();
+            return;
+        }
+        let names: Vec<String> = self.services.iter().map(|s| s.name.clone()).collect();
+        self.session_bound = graphical_session_services(&names);
+        self.graphical_session = graphical_session_active();
+    }
+
+    /// The first load of each tab reports changes made outside
+    /// comma-services; later loads (after our own applies) just re-snapshot.
+    fn track_drift(&mut self, scope: &ServiceScope) {
+        if self.drift_checked.contains(&self.tab) {
+            let _ = drift::update(scope, &self.services);
+            return;
+        }
+        self.drift_checked.push(self.tab);
+
+        if let Ok(report) = drift::check_and_update(scope, &self.services) {
+            if let (Some(since), false) = (report.since, report.changes.is_empty()) {
+                let count = report.changes.len();
+                self.notice = Some(format!(
+                    "{count} service{} changed outside comma-services since {} — D to view",
+                    if count == 1 { "" } else { "s" },
+                    drift::format_date(since)
+                ));
+            }
+            self.drift = report;
+        }
+    }
+
+    pub fn show_drift(&mut self) {
+        if self.config.track_drift {
+            self.modal_scroll = 0;
+            self.mode = Mode::Drift;
+        }
+    }
+
     fn rebuild_categories(&mut self) {
+        // Lookups by name and filter keystrokes stay cheap with 1500+ units
+        self.by_name = self
+            .services
+            .iter()
+            .enumerate()
+            .map(|(idx, svc)| (svc.name.clone(), idx))
+            .collect();
+        self.search_names = self
+            .services
+            .iter()
+            .map(|svc| svc.name.to_lowercase())
+            .collect();
+
         let mut groups: BTreeMap<&'static str, Vec<usize>> = BTreeMap::new();
 
         for (idx, svc) in self.services.iter().enumerate() {
-            let cat = categorize(&svc.name);
+            let cat = if svc.generated {
+                "Generated"
+            } else {
+                categorize(&svc.name)
+            };
             groups.entry(cat).or_default().push(idx);
         }
 
@@ -108,27 +515,42 @@ impl App {
                 })
             })
             .collect();
+
+        // Roles only show members installed on this tab
+        self.roles = self
+            .config
+            .roles()
+            .into_iter()
+            .filter_map(|(name, members)| {
+                let services: Vec<usize> = members
+                    .iter()
+                    .filter_map(|m| self.service_index(m))
+                    .collect();
+                (!services.is_empty()).then_some(Role { name, services })
+            })
+            .collect();
     }
 
     pub fn rebuild_visible(&mut self) {
         self.visible_items.clear();
         let filter_lower = self.filter.to_lowercase();
 
+        // Virtual role entries go above the categories
+        if self.root_only.is_none() {
+            for (role_idx, role) in self.roles.iter().enumerate() {
+                if filter_lower.is_empty() || role.name.to_lowercase().contains(&filter_lower) {
+                    self.visible_items.push(VisibleItem::Role(role_idx));
+                }
+            }
+        }
+
         for (cat_idx, cat) in self.categories.iter().enumerate() {
-            let matching_services: Vec<usize> = if filter_lower.is_empty() {
-                cat.services.clone()
-            } else {
-                cat.services
-                    .iter()
-                    .filter(|&&svc_idx| {
-                        self.services[svc_idx]
-                            .name
-                            .to_lowercase()
-                            .contains(&filter_lower)
-                    })
-                    .copied()
-                    .collect()
-            };
+            let matching_services: Vec<usize> = cat
+                .services
+                .iter()
+                .filter(|&&svc_idx| self.passes_filters(svc_idx, &filter_lower))
+                .copied()
+                .collect();
 
             if matching_services.is_empty() {
                 continue;
@@ -139,8 +561,105 @@ impl App {
             if !cat.collapsed {
                 for svc_idx in matching_services {
                     self.visible_items.push(VisibleItem::Service(svc_idx));
+                    for line in self.failure_rows(svc_idx) {
+                        self.visible_items.push(VisibleItem::Journal(svc_idx, line));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether a service gets a row under the `/` and root-only filters;
+    /// `filter_lower` is the `/` text, lowercased.
+    fn passes_filters(&self, svc_idx: usize, filter_lower: &str) -> bool {
+        (filter_lower.is_empty() || self.search_names[svc_idx].contains(filter_lower))
+            && self
+                .root_only
+                .as_ref()
+                .is_none_or(|root| root.contains(&self.services[svc_idx].name))
+    }
+
+    /// The members of category `name` the filters leave in the list.
+    pub fn shown_members(&self, name: &str) -> Vec<usize> {
+        let filter_lower = self.filter.to_lowercase();
+        self.categories
+            .iter()
+            .find(|cat| cat.name == name)
+            .map(|cat| {
+                cat.services
+                    .iter()
+                    .copied()
+                    .filter(|&idx| self.passes_filters(idx, &filter_lower))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Which of a failed service's log lines go under its row: the last,
+    /// or all of them once `x` has opened it.
+    fn failure_rows(&self, svc_idx: usize) -> std::ops::Range<usize> {
+        let name = &self.services[svc_idx].name;
+        let count = match self.failure_logs.get(name) {
+            Some(lines) if self.failed.contains(name) => lines.len(),
+            _ => return 0..0,
+        };
+        if self.expanded_failures.contains(name) {
+            0..count
+        } else {
+            count.saturating_sub(1)..count
+        }
+    }
+
+    /// The last warnings of this tab's failed services, for their rows.
+    fn load_failure_logs(&mut self) {
+        let scope = self.scope();
+        self.failure_logs = self
+            .services
+            .iter()
+            .filter(|svc| self.failed.contains(&svc.name))
+            .take(MAX_FAILURE_LOGS)
+            .map(|svc| (svc.name.clone(), journal::last_errors(&scope, &svc.name)))
+            .filter(|(_, lines)| !lines.is_empty())
+            .collect();
+    }
+
+    /// `x` on a failed service: show all of its log lines, or just the last.
+    pub fn toggle_failure_lines(&mut self) {
+        let Some(&VisibleItem::Service(svc_idx)) = self.visible_items.get(self.cursor) else {
+            return;
+        };
+        let name = self.services[svc_idx].name.clone();
+        match self.failure_logs.get(&name).map(Vec::len) {
+            Some(count) if count > 1 && self.failed.contains(&name) => {
+                if !self.expanded_failures.remove(&name) {
+                    self.expanded_failures.insert(name);
                 }
+                self.rebuild_keeping_cursor();
             }
+            Some(_) => {}
+            None => self.notice = Some("x opens the log lines under a failed service".to_string()),
+        }
+    }
+
+    /// Rebuild the rows with the cursor staying on the item it was on.
+    fn rebuild_keeping_cursor(&mut self) {
+        let current = self.visible_items.get(self.cursor).cloned();
+        self.rebuild_visible();
+        if let Some(row) =
+            current.and_then(|item| self.visible_items.iter().position(|i| *i == item))
+        {
+            self.cursor = row;
+        }
+        self.settle_cursor();
+    }
+
+    /// Log rows under a failed service aren't a place for the cursor;
+    /// step back onto the service.
+    fn settle_cursor(&mut self) {
+        self.cursor = self.cursor.min(self.visible_items.len().saturating_sub(1));
+        while self.cursor > 0 && matches!(self.visible_items[self.cursor], VisibleItem::Journal(..))
+        {
+            self.cursor -= 1;
         }
     }
 
@@ -149,20 +668,454 @@ impl App {
             return;
         }
         let len = self.visible_items.len() as i32;
-        let new = (self.cursor as i32 + delta).rem_euclid(len);
+        let step = if delta < 0 { -1 } else { 1 };
+        let mut new = (self.cursor as i32 + delta).rem_euclid(len);
+        // Skip over log rows; each follows its service, so one is near
+        while matches!(self.visible_items[new as usize], VisibleItem::Journal(..)) {
+            new = (new + step).rem_euclid(len);
+        }
         self.cursor = new as usize;
     }
 
+    /// Mark a staged change as runtime-only (undone at reboot), or back.
+    pub fn toggle_runtime(&mut self) {
+        if self.tab == Tab::Global {
+            return; // --global has no runtime variant
+        }
+        if let Some(VisibleItem::Service(svc_idx)) = self.visible_items.get(self.cursor) {
+            let name = &self.services[*svc_idx].name;
+            if !self.toggled.contains(name) {
+                return;
+            }
+            if !self.runtime.remove(name) {
+                self.runtime.insert(name.clone());
+            }
+        }
+    }
+
     pub fn toggle_current(&mut self) {
+        if let Some(&VisibleItem::Role(role_idx)) = self.visible_items.get(self.cursor) {
+            self.toggle_role(role_idx);
+            return;
+        }
+        if let Some(&VisibleItem::Category(cat_idx)) = self.visible_items.get(self.cursor) {
+            self.bulk_category = Some(self.categories[cat_idx].name);
+            self.mode = Mode::CategoryBulk;
+            return;
+        }
         if let Some(VisibleItem::Service(svc_idx)) = self.visible_items.get(self.cursor) {
-            let svc = &mut self.services[*svc_idx];
-            svc.enabled = !svc.enabled;
+            let svc_idx = *svc_idx;
+            let svc = &self.services[svc_idx];
+            if svc.generated {
+                self.notice = Some(format!(
+                    "{} is made by a generator and can't be toggled; i shows its source",
+                    svc.name
+                ));
+                return;
+            }
+            if self.masks.contains_key(&svc.name) {
+                self.notice = Some(format!(
+                    "{} has a staged {}; m takes it back",
+                    svc.name,
+                    if svc.masked { "unmask" } else { "mask" }
+                ));
+                return;
+            }
+            if svc.masked {
+                self.notice = Some(format!("{} is masked; m stages an unmask", svc.name));
+                return;
+            }
+            // A per-user disable can't remove links in /etc/systemd/user
+            if svc.enabled && svc.enabled_globally && self.tab == Tab::User {
+                self.notice = Some(format!(
+                    "{} is enabled for all users; disable it on the All users tab",
+                    svc.name
+                ));
+                return;
+            }
+            let enabled = !svc.enabled;
+            self.set_enabled(svc_idx, enabled);
+            if let Some((new, current)) = self.display_manager_conflict() {
+                self.notice = Some(format!(
+                    "display-manager.service points at {current}; also disable it, or enabling {new} fails"
+                ));
+            }
+            self.offer_pair(svc_idx);
+        }
+    }
 
-            let original = self.original_state.get(&svc.name).copied().unwrap_or(false);
-            if svc.enabled == original {
-                self.toggled.remove(&svc.name);
-            } else {
-                self.toggled.insert(svc.name.clone());
+    /// A display manager staged on while another still holds the
+    /// display-manager.service alias and isn't staged off: (new, current).
+    pub fn display_manager_conflict(&self) -> Option<(String, String)> {
+        let current = self.display_manager.as_ref()?;
+        let still_on = self.service_named(current).is_none_or(|svc| svc.enabled);
+        if !still_on {
+            return None;
+        }
+        self.services
+            .iter()
+            .find(|svc| {
+                svc.enabled
+                    && &svc.name != current
+                    && self.toggled.contains(&svc.name)
+                    && is_display_manager(&svc.name)
+            })
+            .map(|svc| (svc.name.clone(), current.clone()))
+    }
+
+    /// If the service has a same-named socket or timer that agreed with it
+    /// until now, ask whether to stage that too; toggling one alone leaves
+    /// the trigger starting a disabled service, or an enabled one idle.
+    fn offer_pair(&mut self, svc_idx: usize) {
+        let svc = &self.services[svc_idx];
+        if !self.toggled.contains(&svc.name) {
+            return;
+        }
+        let Some(trigger) = paired_trigger(&svc.name, &self.triggers) else {
+            return;
+        };
+        if self.triggers.get(&trigger) != Some(&svc.enabled) {
+            self.pair_offer = Some((svc.name.clone(), trigger));
+            self.mode = Mode::Pair;
+        }
+    }
+
+    pub fn answer_pair(&mut self, both: bool) {
+        if let Some((service, trigger)) = self.pair_offer.take() {
+            if both {
+                self.notice = Some(format!("{trigger} staged along with {service}"));
+                // Sockets are rows of their own; stage that row so it shows
+                // as dirty and can be toggled back on its own
+                let rows = (self.service_index(&service), self.service_index(&trigger));
+                match rows {
+                    (Some(svc_idx), Some(trigger_idx)) => {
+                        let enabled = self.services[svc_idx].enabled;
+                        self.set_enabled(trigger_idx, enabled);
+                    }
+                    _ => {
+                        self.paired.insert(service);
+                    }
+                }
+            }
+        }
+        self.mode = Mode::Normal;
+    }
+
+    /// Stage `enabled` for one service, clearing the dirty mark if that
+    /// matches what's on disk.
+    fn set_enabled(&mut self, svc_idx: usize, enabled: bool) {
+        let svc = &mut self.services[svc_idx];
+        // Masked units sit out bulk toggles until they're unmasked
+        if svc.masked || self.masks.contains_key(&svc.name) {
+            return;
+        }
+        svc.enabled = enabled;
+        // A new boot change starts over with its default start/stop
+        self.run_changes.remove(&svc.name);
+
+        let original = self.original_state.get(&svc.name).copied().unwrap_or(false);
+        if svc.enabled == original {
+            self.toggled.remove(&svc.name);
+            self.runtime.remove(&svc.name);
+            self.paired.remove(&svc.name);
+        } else {
+            self.toggled.insert(svc.name.clone());
+        }
+    }
+
+    /// `s`: stage a start/stop separately from boot state. With an enable or
+    /// disable staged it switches between "and start/stop now" and "at boot
+    /// only"; otherwise a running unit cycles stop → restart → unchanged and
+    /// a stopped one start → unchanged.
+    pub fn cycle_run_change(&mut self) {
+        if self.tab == Tab::Global {
+            self.notice = Some("Nothing runs for all users; switch to the User tab".to_string());
+            return;
+        }
+        let Some(&VisibleItem::Service(svc_idx)) = self.visible_items.get(self.cursor) else {
+            return;
+        };
+        let svc = &self.services[svc_idx];
+        if self.masks.contains_key(&svc.name) || svc.masked {
+            self.notice = Some(format!("{} is masked and can't be started", svc.name));
+            return;
+        }
+        let next = match (
+            self.toggled.contains(&svc.name),
+            self.run_changes.get(&svc.name),
+        ) {
+            (true, None) => Some(RunChange::Keep),
+            (true, Some(_)) => None,
+            (false, None) if svc.active => Some(RunChange::Stop),
+            (false, Some(RunChange::Stop)) => Some(RunChange::Restart),
+            (false, None) => Some(RunChange::Start),
+            (false, Some(_)) => None,
+        };
+        let name = svc.name.clone();
+        match next {
+            Some(change) => self.run_changes.insert(name, change),
+            None => self.run_changes.remove(&name),
+        };
+    }
+
+    /// Stage masking the selected service, or unmasking it if it's masked.
+    /// `m` again takes the staged change back.
+    pub fn toggle_mask(&mut self) {
+        let Some(&VisibleItem::Service(svc_idx)) = self.visible_items.get(self.cursor) else {
+            return;
+        };
+        let name = self.services[svc_idx].name.clone();
+        if self.services[svc_idx].generated {
+            self.notice = Some(format!("{name} is made by a generator and can't be masked"));
+            return;
+        }
+        if self.masks.remove(&name).is_some() {
+            return;
+        }
+        if self.services[svc_idx].masked {
+            self.masks.insert(name.clone(), false);
+            self.notice = Some(format!(
+                "{name} will be unmasked and left disabled; enable it after applying"
+            ));
+            return;
+        }
+        // A mask replaces whatever enable/disable was staged
+        if let Some(&original) = self.original_state.get(&name) {
+            self.set_enabled(svc_idx, original);
+        }
+        self.masks.insert(name.clone(), true);
+        self.notice = Some(format!(
+            "Masking is stronger than disabling: nothing can start {name}, not even as a dependency"
+        ));
+    }
+
+    /// Stage the maintenance bundle as a unit: stop the bundled services
+    /// that are running (maintenance on), or start again just the ones it
+    /// stopped (off). Boot state is left alone either way.
+    pub fn toggle_maintenance(&mut self) {
+        if self.tab == Tab::Global {
+            self.notice = Some("Nothing runs for all users; switch to the User tab".to_string());
+            return;
+        }
+        let stopped = profiles::maintenance_stopped(&self.scope());
+        let entering = stopped.is_empty();
+        let members: Vec<usize> = if entering {
+            let bundle = self.config.maintenance_services();
+            self.services
+                .iter()
+                .enumerate()
+                .filter(|(_, svc)| svc.active && bundle.contains(&svc.name))
+                .map(|(idx, _)| idx)
+                .collect()
+        } else {
+            stopped
+                .iter()
+                .filter_map(|name| self.service_index(name))
+                .filter(|&idx| !self.services[idx].active)
+                .collect()
+        };
+        if members.is_empty() && entering {
+            self.notice = Some("No maintenance services are running on this tab".to_string());
+            return;
+        }
+        if members.is_empty() {
+            // Started by hand meanwhile: maintenance is over already
+            let _ = profiles::record_maintenance(&self.scope(), &[]);
+            self.notice = Some("Everything maintenance mode stopped is running again".to_string());
+            return;
+        }
+
+        let change = if entering {
+            RunChange::Stop
+        } else {
+            RunChange::Start
+        };
+        let (mut names, mut skipped) = (Vec::new(), 0);
+        for idx in members {
+            let svc = &self.services[idx];
+            // A staged boot change or mask already decides what happens now
+            if svc.masked || self.masks.contains_key(&svc.name) || self.toggled.contains(&svc.name)
+            {
+                skipped += 1;
+                continue;
+            }
+            self.run_changes.insert(svc.name.clone(), change);
+            names.push(svc.name.clone());
+        }
+        let prefix = format!("Maintenance mode {}: ", if entering { "on" } else { "off" });
+        self.bulk_notice(&prefix, names.len(), skipped);
+        self.maintenance_change = (!names.is_empty()).then_some((entering, names));
+    }
+
+    /// After an apply: remember what maintenance mode stopped, or forget
+    /// what it started again.
+    fn record_maintenance(&mut self, results: &[ChangeResult]) {
+        let Some((entering, names)) = self.maintenance_change.take() else {
+            return;
+        };
+        let done = |name: &String| results.iter().any(|r| r.success && r.service == *name);
+        let stopped: Vec<String> = if entering {
+            names.into_iter().filter(done).collect()
+        } else {
+            profiles::maintenance_stopped(&self.scope())
+                .into_iter()
+                .filter(|name| !(names.contains(name) && done(name)))
+                .collect()
+        };
+        if let Err(e) = profiles::record_maintenance(&self.scope(), &stopped) {
+            self.notice = Some(format!("{e:#}"));
+        }
+    }
+
+    /// `V`: select rows from here to wherever the cursor moves next.
+    pub fn begin_visual(&mut self) {
+        self.visual_anchor = self.cursor;
+        self.mode = Mode::Visual;
+    }
+
+    pub fn in_selection(&self, row: usize) -> bool {
+        self.mode == Mode::Visual
+            && (self.visual_anchor.min(self.cursor)..=self.visual_anchor.max(self.cursor))
+                .contains(&row)
+    }
+
+    /// Stage every selected service: `Some` enables or disables them all,
+    /// `None` flips each. Headers and roles in the range are passed over.
+    pub fn stage_selection(&mut self, enable: Option<bool>) {
+        let rows = self.visual_anchor.min(self.cursor)..=self.visual_anchor.max(self.cursor);
+        let selected: Vec<usize> = self
+            .visible_items
+            .get(rows)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|item| match item {
+                VisibleItem::Service(idx) => Some(*idx),
+                _ => None,
+            })
+            .collect();
+        let (staged, skipped) = self.stage_all(&selected, enable);
+        self.mode = Mode::Normal;
+        self.bulk_notice("", staged, skipped);
+    }
+
+    /// The header prompt's answer: stage every service of the category
+    /// on (`true`) or off.
+    pub fn stage_category(&mut self, enable: bool) {
+        self.mode = Mode::Normal;
+        // A live update may have emptied the category meanwhile
+        let Some(name) = self.bulk_category.take() else {
+            return;
+        };
+        let members = self.shown_members(name);
+        if members.is_empty() {
+            self.notice = Some(format!("{name} has no services left to stage"));
+            return;
+        }
+        let (staged, skipped) = self.stage_all(&members, Some(enable));
+        self.bulk_notice(&format!("{name}: "), staged, skipped);
+    }
+
+    fn bulk_notice(&mut self, prefix: &str, staged: usize, skipped: usize) {
+        let plural = if staged == 1 { "" } else { "s" };
+        self.notice = Some(match skipped {
+            0 => format!("{prefix}{staged} service{plural} staged"),
+            _ => format!(
+                "{prefix}{staged} service{plural} staged, {skipped} left alone (masked, generated or enabled for all users)"
+            ),
+        });
+    }
+
+    /// For the review: (category, staged on, staged off) wherever a
+    /// category has more than one boot change staged.
+    pub fn changes_by_category(&self) -> Vec<(&'static str, usize, usize)> {
+        self.categories
+            .iter()
+            .map(|cat| {
+                let staged = cat
+                    .services
+                    .iter()
+                    .map(|&idx| &self.services[idx])
+                    .filter(|svc| self.toggled.contains(&svc.name));
+                let (on, off) = staged.fold((0, 0), |(on, off), svc| match svc.enabled {
+                    true => (on + 1, off),
+                    false => (on, off + 1),
+                });
+                (cat.name, on, off)
+            })
+            .filter(|(_, on, off)| on + off > 1)
+            .collect()
+    }
+
+    /// Stage `indices` (into services) to `enable`, or each to the
+    /// opposite of what it is with `None`, under the same limits as a
+    /// single toggle. Returns how many were staged and how many those
+    /// limits left alone; services already as asked count as neither.
+    fn stage_all(&mut self, indices: &[usize], enable: Option<bool>) -> (usize, usize) {
+        let (mut staged, mut skipped) = (0, 0);
+        for &idx in indices {
+            let svc = &self.services[idx];
+            let enabled = enable.unwrap_or(!svc.enabled);
+            // Leaves a staged start/stop on those alone, too
+            if svc.enabled == enabled {
+                continue;
+            }
+            let global = !enabled && svc.enabled_globally && self.tab == Tab::User;
+            if svc.generated || svc.masked || self.masks.contains_key(&svc.name) || global {
+                skipped += 1;
+                continue;
+            }
+            self.set_enabled(idx, enabled);
+            staged += 1;
+        }
+        (staged, skipped)
+    }
+
+    /// A role is on when every installed member is enabled; toggling turns
+    /// a fully-on role off and anything else fully on.
+    fn toggle_role(&mut self, role_idx: usize) {
+        let members = self.roles[role_idx].services.clone();
+        let turning_on = !members.iter().all(|&idx| self.services[idx].enabled);
+        // Same limits as a single toggle: global links can't be undone per-user
+        let (staged, skipped) = self.stage_all(&members, Some(turning_on));
+        let prefix = format!(
+            "Role {} {}: ",
+            self.roles[role_idx].name,
+            if turning_on { "on" } else { "off" }
+        );
+        self.bulk_notice(&prefix, staged, skipped);
+    }
+
+    /// Stage every service whose state differs from its preset back to
+    /// what the preset files say, for review like any other batch.
+    pub fn apply_presets(&mut self) {
+        let indices: Vec<usize> = self
+            .services
+            .iter()
+            .enumerate()
+            .filter(|(_, svc)| svc.differs_from_preset() && !svc.generated)
+            // Same limit as toggle_current: global links aren't ours to remove
+            .filter(|(_, svc)| !(svc.enabled_globally && self.tab == Tab::User))
+            .map(|(idx, _)| idx)
+            .collect();
+
+        if indices.is_empty() {
+            self.notice = Some("Every service on this tab matches its preset".to_string());
+            return;
+        }
+
+        let (on, off): (Vec<usize>, Vec<usize>) = indices
+            .into_iter()
+            .partition(|&idx| self.services[idx].preset.unwrap_or(false));
+        let (staged_on, skipped_on) = self.stage_all(&on, Some(true));
+        let (staged_off, skipped_off) = self.stage_all(&off, Some(false));
+        self.bulk_notice(
+            "Back to preset: ",
+            staged_on + staged_off,
+            skipped_on + skipped_off,
+        );
+        if staged_on + staged_off > 0 {
+            if let Some(notice) = &mut self.notice {
+                notice.push_str(" — Enter to review");
             }
         }
     }
@@ -170,7 +1123,8 @@ impl App {
     pub fn toggle_collapse(&mut self) {
         let cat_idx = match self.visible_items.get(self.cursor) {
             Some(VisibleItem::Category(idx)) => Some(*idx),
-            Some(VisibleItem::Service(svc_idx)) => {
+            Some(VisibleItem::Role(_)) => None,
+            Some(VisibleItem::Service(svc_idx) | VisibleItem::Journal(svc_idx, _)) => {
                 // Find which category this service belongs to
                 self.categories
                     .iter()
@@ -183,68 +1137,1584 @@ impl App {
             self.categories[idx].collapsed = !self.categories[idx].collapsed;
             self.rebuild_visible();
             // Keep cursor in bounds
-            if self.cursor >= self.visible_items.len() {
-                self.cursor = self.visible_items.len().saturating_sub(1);
-            }
+            self.settle_cursor();
         }
     }
 
-    pub fn pending_changes(&self) -> Vec<PendingChange> {
-        let scope = match self.tab {
-            Tab::System => ServiceScope::System,
-            Tab::User => ServiceScope::User,
-        };
-
-        self.services
-            .iter()
-            .filter(|svc| self.toggled.contains(&svc.name))
-            .map(|svc| PendingChange {
-                service: svc.name.clone(),
-                scope: scope.clone(),
-                action: if svc.enabled {
-                    ChangeAction::Enable
-                } else {
-                    ChangeAction::Disable
-                },
-            })
-            .collect()
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            toggled: self.toggled.clone(),
+            runtime: self.runtime.clone(),
+            paired: self.paired.clone(),
+            masks: self.masks.clone(),
+            run_changes: self.run_changes.clone(),
+        }
     }
 
-    pub fn has_pending_changes(&self) -> bool {
-        !self.toggled.is_empty()
+    /// After a key press: if it changed what's staged (one toggle or a
+    /// whole bundle, role or profile), `before` becomes an undo step.
+    pub fn record_undo(&mut self, before: Snapshot) {
+        if before != self.snapshot() {
+            self.undo.push(before);
+            self.redo.clear();
+        }
     }
 
-    pub fn pending_count(&self) -> usize {
-        self.toggled.len()
+    /// `u`: put the staging back as it was before the last change to it.
+    pub fn undo(&mut self) {
+        let Some(snapshot) = self.undo.pop() else {
+            self.notice = Some("Nothing to undo".to_string());
+            return;
+        };
+        self.redo.push(self.snapshot());
+        self.restore(snapshot);
+        self.notice = Some(format!(
+            "Undone ({} more to undo, Ctrl-r redoes)",
+            self.undo.len()
+        ));
     }
 
-    pub fn apply_done(&mut self, results: Vec<ChangeResult>) -> Result<()> {
+    /// Ctrl-r: make the last undone change again.
+    pub fn redo(&mut self) {
+        let Some(snapshot) = self.redo.pop() else {
+            self.notice = Some("Nothing to redo".to_string());
+            return;
+        };
+        self.undo.push(self.snapshot());
+        self.restore(snapshot);
+        self.notice = Some(format!("Redone ({} more to redo)", self.redo.len()));
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        for svc in &mut self.services {
+            let original = self.original_state.get(&svc.name).copied().unwrap_or(false);
+            svc.enabled = original != snapshot.toggled.contains(&svc.name);
+        }
+        self.toggled = snapshot.toggled;
+        self.runtime = snapshot.runtime;
+        self.paired = snapshot.paired;
+        self.masks = snapshot.masks;
+        self.run_changes = snapshot.run_changes;
+        let staged = self.snapshot();
+        self.retry.retain(|name| {
+            staged.toggled.contains(name)
+                || staged.masks.contains_key(name)
+                || staged.run_changes.contains_key(name)
+        });
+    }
+
+    /// The row of the `n`th (from 1) category header in the list.
+    fn category_row(&self, n: usize) -> Option<usize> {
+        self.visible_items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| matches!(item, VisibleItem::Category(_)))
+            .nth(n.checked_sub(1)?)
+            .map(|(row, _)| row)
+    }
+
+    /// Number keys: put the cursor on the `n`th category header, and with
+    /// `fold` (Shift) collapse or expand it too.
+    pub fn jump_to_category(&mut self, n: usize, fold: bool) {
+        let Some(row) = self.category_row(n) else {
+            self.notice = Some(format!("No category {n} in this list"));
+            return;
+        };
+        self.cursor = row;
+        if fold {
+            self.toggle_collapse();
+        }
+    }
+
+    pub fn pending_changes(&self) -> Vec<PendingChange> {
+        let scope = self.scope();
+
+        let mut changes: Vec<PendingChange> = self
+            .services
+            .iter()
+            .filter(|svc| self.toggled.contains(&svc.name))
+            .flat_map(|svc| {
+                let trigger = self
+                    .paired
+                    .contains(&svc.name)
+                    .then(|| paired_trigger(&svc.name, &self.triggers))
+                    .flatten();
+                let keep = self.run_changes.get(&svc.name) == Some(&RunChange::Keep);
+                let action = match (svc.enabled, keep) {
+                    (true, false) => ChangeAction::Enable,
+                    (false, false) => ChangeAction::Disable,
+                    (true, true) => ChangeAction::EnableOnly,
+                    (false, true) => ChangeAction::DisableOnly,
+                };
+                let scope = &scope;
+                std::iter::once(svc.name.clone())
+                    .chain(trigger)
+                    .map(move |unit| PendingChange {
+                        service: unit,
+                        scope: scope.clone(),
+                        action: action.clone(),
+                        runtime: self.runtime.contains(&svc.name),
+                    })
+            })
+            .collect();
+        // Start/stop without a boot change
+        changes.extend(
+            self.run_changes
+                .iter()
+                .filter(|(name, _)| !self.toggled.contains(*name))
+                .filter_map(|(name, change)| {
+                    let action = match change {
+                        RunChange::Start => ChangeAction::Start,
+                        RunChange::Stop => ChangeAction::Stop,
+                        RunChange::Restart => ChangeAction::Restart,
+                        RunChange::Keep => return None,
+                    };
+                    Some(PendingChange {
+                        service: name.clone(),
+                        scope: scope.clone(),
+                        action,
+                        runtime: false,
+                    })
+                }),
+        );
+        changes.extend(self.masks.iter().map(|(name, &mask)| PendingChange {
+            service: name.clone(),
+            scope: scope.clone(),
+            action: if mask {
+                ChangeAction::Mask
+            } else {
+                ChangeAction::Unmask
+            },
+            runtime: false,
+        }));
+        // Disables first, so a unit giving up an alias (display-manager.service)
+        // frees it before its replacement is enabled
+        changes.sort_by_key(|change| {
+            matches!(
+                change.action,
+                ChangeAction::Enable | ChangeAction::EnableOnly | ChangeAction::Start
+            )
+        });
+        changes
+    }
+
+    /// With `strict_confirm`, every system service being disabled must be
+    /// typed by name. Returns false when no typing is required.
+    pub fn begin_strict_confirm(&mut self, then_schedule: bool) -> bool {
+        if !self.config.strict_confirm || self.tab != Tab::System {
+            return false;
+        }
+        self.strict_queue = self
+            .pending_changes()
+            .into_iter()
+            .filter(|c| {
+                matches!(
+                    c.action,
+                    ChangeAction::Disable | ChangeAction::DisableOnly | ChangeAction::Mask
+                )
+            })
+            .map(|c| c.service)
+            .collect();
+        if self.strict_queue.is_empty() {
+            return false;
+        }
+        self.strict_then_schedule = then_schedule;
+        self.prompt_input.clear();
+        self.mode = Mode::StrictConfirm;
+        true
+    }
+
+    /// Check the typed name against the next service. Returns true once
+    /// every name has been confirmed.
+    pub fn submit_strict_confirm(&mut self) -> bool {
+        let typed = self.prompt_input.trim();
+        if let Some(expected) = self.strict_queue.first() {
+            let short = expected.trim_end_matches(".service");
+            if typed == expected || typed == short {
+                self.strict_queue.remove(0);
+            }
+        }
+        self.prompt_input.clear();
+        self.strict_queue.is_empty()
+    }
+
+    pub fn has_pending_changes(&self) -> bool {
+        !self.pending_changes().is_empty()
+    }
+
+    /// Quit, unless that would throw away staged changes; then ask first.
+    pub fn request_quit(&mut self) {
+        if self.has_pending_changes() {
+            self.mode = Mode::QuitConfirm;
+        } else {
+            self.should_quit = true;
+        }
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending_changes().len()
+    }
+
+    /// Whether Enter reviews first, by the confirmation policy in the config.
+    pub fn needs_review(&self) -> bool {
+        self.config
+            .wants_confirm(self.tab == Tab::User, self.pending_count())
+    }
+
+    /// Open the review modal with a fresh look at the job queue, since an
+    /// apply during a big transaction gives confusing results.
+    pub fn review_changes(&mut self) {
+        let scope = self.scope();
+        self.queued_jobs = jobs::list_jobs(&scope);
+        let stopping: Vec<String> = self
+            .pending_changes()
+            .into_iter()
+            .filter(|change| change.action.stops())
+            .map(|change| change.service)
+            .collect();
+        self.memory_freed = memory_usage(&scope, &stopping);
+        self.mode = Mode::Confirm;
+    }
+
+    pub fn show_results(&mut self) {
+        if self.results.is_empty() {
+            self.notice = Some("Nothing applied yet this session".to_string());
+            return;
+        }
+        self.modal_scroll = 0;
+        self.mode = Mode::Results;
+    }
+
+    /// Preview what a reboot would start with the staged changes applied.
+    pub fn show_next_boot(&mut self) {
+        if self.tab == Tab::Global {
+            self.notice =
+                Some("All-users units start with each user's login, not at boot".to_string());
+            return;
+        }
+        // Staged masks count as already masked
+        let mut services = self.services.clone();
+        for svc in &mut services {
+            if let Some(&mask) = self.masks.get(&svc.name) {
+                svc.masked = mask;
+                svc.enabled &= !mask;
+            }
+        }
+        self.next_boot = boot::preview(
+            &services,
+            &self.original_state,
+            &self.triggers,
+            &self.paired,
+        );
+        self.modal_scroll = 0;
+        self.mode = Mode::NextBoot;
+    }
+
+    pub fn show_jobs(&mut self) {
+        self.queued_jobs = jobs::list_jobs(&self.scope());
+        self.modal_scroll = 0;
+        self.mode = Mode::Jobs;
+    }
+
+    pub fn job_event(&mut self, event: JobEvent) {
+        match event {
+            JobEvent::New(job) => self.jobs.push(job),
+            JobEvent::Removed(job) => self.jobs.retain(|j| j.id != job.id),
+        }
+    }
+
+    /// A unit signal from the bus. State changes are patched in place;
+    /// anything that may add rows marks the list stale instead.
+    pub fn unit_event(&mut self, event: UnitEvent) {
+        match event {
+            UnitEvent::State {
+                unit,
+                active,
+                failed,
+            } => {
+                let listed = self.service_index(&unit);
+                if let Some(idx) = listed {
+                    self.services[idx].active = active;
+                }
+                if failed && self.failed.insert(unit.clone()) && listed.is_some() {
+                    let lines = journal::last_errors(&self.scope(), &unit);
+                    if !lines.is_empty() {
+                        self.failure_logs.insert(unit, lines);
+                        self.rebuild_keeping_cursor();
+                    }
+                } else if !failed && self.failed.remove(&unit) {
+                    self.expanded_failures.remove(&unit);
+                    if self.failure_logs.remove(&unit).is_some() {
+                        self.rebuild_keeping_cursor();
+                    }
+                }
+            }
+            UnitEvent::Unloaded(unit) => {
+                if let Some(idx) = self.service_index(&unit) {
+                    self.services[idx].active = false;
+                }
+            }
+            UnitEvent::Loaded(unit) => {
+                let listed = if self.tab == Tab::Timers {
+                    unit.ends_with(".timer")
+                } else {
+                    unit.ends_with(".service") || unit.ends_with(".socket")
+                };
+                if listed && self.service_index(&unit).is_none() {
+                    self.units_stale = true;
+                }
+            }
+            UnitEvent::FilesChanged => {
+                unit_cache::clear();
+                self.units_stale = true;
+            }
+        }
+    }
+
+    /// Re-list units after a change outside comma-services, keeping staged
+    /// toggles and the cursor. A toggle someone else already made for us is
+    /// dropped, since there's nothing left to apply.
+    pub fn sync_units(&mut self) {
+        // An apply's own changes are picked up by its refresh
+        if !self.units_stale || self.mode == Mode::Applying {
+            return;
+        }
+        self.units_stale = false;
+        let scope = self.scope();
+        let Ok(mut services) = self.load_services(&scope) else {
+            return;
+        };
+        let selected = match self.visible_items.get(self.cursor) {
+            Some(&VisibleItem::Service(idx)) => Some(self.services[idx].name.clone()),
+            _ => None,
+        };
+        let staged: std::collections::HashMap<String, bool> = self
+            .services
+            .iter()
+            .filter(|svc| self.toggled.contains(&svc.name))
+            .map(|svc| (svc.name.clone(), svc.enabled))
+            .collect();
+
+        self.original_state.clear();
+        self.toggled.clear();
+        for svc in &mut services {
+            self.original_state.insert(svc.name.clone(), svc.enabled);
+            match staged.get(&svc.name) {
+                Some(&enabled) if enabled != svc.enabled => {
+                    svc.enabled = enabled;
+                    self.toggled.insert(svc.name.clone());
+                }
+                _ => {}
+            }
+        }
+        self.runtime.retain(|name| self.toggled.contains(name));
+        self.paired.retain(|name| self.toggled.contains(name));
+        self.run_changes
+            .retain(|name, change| *change != RunChange::Keep || self.toggled.contains(name));
+        self.retry.retain(|name| staged.contains_key(name));
+        self.masks
+            .retain(|name, &mut mask| services.iter().any(|s| s.name == *name && s.masked != mask));
+        // Snapshots are relative to the state just replaced, as in refresh
+        self.undo.clear();
+        self.redo.clear();
+        self.services = services;
+        self.triggers = list_triggers(&scope);
+        self.needs_reload = units_needing_reload(&scope);
+
+        let collapsed: Vec<&'static str> = self
+            .categories
+            .iter()
+            .filter(|cat| cat.collapsed)
+            .map(|cat| cat.name)
+            .collect();
+        self.rebuild_categories();
+        for cat in &mut self.categories {
+            cat.collapsed = collapsed.contains(&cat.name);
+        }
+        self.rebuild_visible();
+        self.cursor = selected
+            .and_then(|name| {
+                self.visible_items.iter().position(|item| {
+                    matches!(item, VisibleItem::Service(idx) if self.services[*idx].name == name)
+                })
+            })
+            .unwrap_or(self.cursor);
+        self.settle_cursor();
+    }
+
+    /// Start tracking an apply of `total` changes; setting the flag this
+    /// returns cancels it.
+    pub fn begin_apply(&mut self, total: usize) -> Arc<AtomicBool> {
+        self.progress = ApplyProgress {
+            total,
+            ..ApplyProgress::default()
+        };
+        self.progress.cancel.clone()
+    }
+
+    /// Skip the changes still queued; the one in flight finishes.
+    pub fn cancel_apply(&mut self) {
+        if self.progress.total == 0 || self.progress.cancel.swap(true, Ordering::Relaxed) {
+            return; // a scheduled batch, or already canceling
+        }
+        self.notice = Some("Canceling after the current change…".to_string());
+    }
+
+    pub fn canceling(&self) -> bool {
+        self.progress.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Take in one event from the apply; true once it has finished and
+    /// the results are in.
+    pub fn apply_event(&mut self, event: ApplyEvent) -> Result<bool> {
+        match event {
+            ApplyEvent::Working { done, service } => {
+                self.progress.done = done;
+                self.progress.current = Some(service);
+            }
+            ApplyEvent::Done(result) => self.progress.results.push(result),
+            ApplyEvent::Finished => {
+                let results = std::mem::take(&mut self.progress.results);
+                self.progress = ApplyProgress::default();
+                self.apply_done(results)?;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    pub fn apply_done(&mut self, results: Vec<ChangeResult>) -> Result<()> {
+        self.jobs.clear();
+        // System changes are what affect boot time; offer a before/after check
+        self.boot_offer =
+            self.tab == Tab::System && machine().is_none() && results.iter().any(|r| r.success);
+        if self.boot_offer && self.notice.is_none() {
+            self.notice = Some("B: compare boot time after next reboot".to_string());
+        }
+        for result in results.iter().filter(|r| r.slow_stop()) {
+            *self.slow_stops.entry(result.service.clone()).or_default() += 1;
+        }
+        // Failed changes stay staged rather than vanish with the refresh
+        let failed: HashSet<String> = results
+            .iter()
+            .filter(|r| !r.success)
+            .map(|r| r.service.clone())
+            .collect();
+        let canceled = results
+            .iter()
+            .any(|r| r.error == Some(SystemctlError::PolkitDismissed));
+        // Never tried rather than failed, so not marked for retry
+        let skipped: HashSet<String> = results
+            .iter()
+            .filter(|r| r.error == Some(SystemctlError::Canceled))
+            .map(|r| r.service.clone())
+            .collect();
+        let applied = results.iter().filter(|r| r.success).count();
+        let staging = self.staging_for(&failed);
+        let tried: HashSet<String> = failed.difference(&skipped).cloned().collect();
+        self.failed_staging = (!tried.is_empty()).then(|| (self.tab, self.staging_for(&tried)));
+        self.record_maintenance(&results);
         self.results = results;
-        self.refresh()
+        self.refresh()?;
+        self.restage(staging);
+        // A half-done change (enabled, but the start failed) may have
+        // nothing left to stage
+        self.retry = failed
+            .into_iter()
+            .filter(|name| {
+                !skipped.contains(name)
+                    && (self.toggled.contains(name)
+                        || self.masks.contains_key(name)
+                        || self.run_changes.contains_key(name))
+            })
+            .collect();
+        let count = self.retry.len();
+        let plural = if count == 1 { "" } else { "s" };
+        if !skipped.is_empty() {
+            let failed = if count > 0 {
+                format!(", {count} failed")
+            } else {
+                String::new()
+            };
+            self.notice = Some(format!(
+                "Apply canceled: {applied} applied{failed}, {} skipped and still staged (L for details)",
+                skipped.len()
+            ));
+        } else if canceled && count > 0 {
+            self.notice = Some(format!(
+                "Authentication canceled — {count} change{plural} not applied, still staged"
+            ));
+        } else if count > 0 {
+            self.notice = Some(format!(
+                "{count} failed change{plural} staged again to retry (L for details)"
+            ));
+        }
+        // Apply-and-quit only quits when nothing is left staged to lose
+        if std::mem::take(&mut self.quit_after_apply) && !self.has_pending_changes() {
+            self.should_quit = true;
+        }
+        // Real failures open the details; a dismissed dialog or a cancel
+        // was the user's own doing
+        let errored = self.results.iter().any(|r| {
+            !matches!(
+                r.error,
+                None | Some(SystemctlError::PolkitDismissed | SystemctlError::Canceled)
+            )
+        });
+        if errored && self.mode == Mode::Applying {
+            self.modal_scroll = 0;
+            self.mode = Mode::Results;
+        }
+        Ok(())
+    }
+
+    pub fn has_failed_changes(&self) -> bool {
+        self.failed_staging.is_some()
+    }
+
+    /// Stage exactly what failed in the last apply again, however it has
+    /// been unstaged or restaged since, and review it to apply once more.
+    pub fn retry_failed(&mut self) {
+        let Some((tab, staging)) = self.failed_staging.clone() else {
+            self.notice = Some("Nothing failed in the last apply".to_string());
+            return;
+        };
+        if tab != self.tab {
+            self.notice = Some("The failed changes were made on another tab".to_string());
+            return;
+        }
+        let names: HashSet<String> = staging
+            .enabled
+            .iter()
+            .map(|(name, _)| name)
+            .chain(staging.masks.iter().map(|(name, _)| name))
+            .chain(staging.run_changes.iter().map(|(name, _)| name))
+            .cloned()
+            .collect();
+        self.restage(staging);
+        self.retry = names
+            .into_iter()
+            .filter(|name| {
+                self.toggled.contains(name)
+                    || self.masks.contains_key(name)
+                    || self.run_changes.contains_key(name)
+            })
+            .collect();
+        if self.retry.is_empty() {
+            // Done since, from here or outside
+            self.failed_staging = None;
+            self.notice = Some("The failed changes have nothing left to do".to_string());
+            return;
+        }
+        self.review_changes();
+    }
+
+    fn staging_for(&self, names: &HashSet<String>) -> Staging {
+        let picked = |name: &String| names.contains(name);
+        Staging {
+            enabled: self
+                .services
+                .iter()
+                .filter(|svc| picked(&svc.name) && self.toggled.contains(&svc.name))
+                .map(|svc| (svc.name.clone(), svc.enabled))
+                .collect(),
+            runtime: self.runtime.iter().filter(|n| picked(n)).cloned().collect(),
+            paired: self.paired.iter().filter(|n| picked(n)).cloned().collect(),
+            masks: self
+                .masks
+                .iter()
+                .filter(|(n, _)| picked(n))
+                .map(|(n, &mask)| (n.clone(), mask))
+                .collect(),
+            run_changes: self
+                .run_changes
+                .iter()
+                .filter(|(n, _)| picked(n))
+                .map(|(n, &change)| (n.clone(), change))
+                .collect(),
+        }
+    }
+
+    /// Put `staging` back on the freshly loaded list, skipping whatever
+    /// the system now already agrees with.
+    fn restage(&mut self, staging: Staging) {
+        for (name, mask) in staging.masks {
+            if self
+                .service_named(&name)
+                .is_some_and(|svc| svc.masked != mask)
+            {
+                self.masks.insert(name, mask);
+            }
+        }
+        for (name, enabled) in staging.enabled {
+            if let Some(idx) = self.service_index(&name) {
+                self.set_enabled(idx, enabled);
+            }
+        }
+        for name in staging.runtime {
+            if self.toggled.contains(&name) {
+                self.runtime.insert(name);
+            }
+        }
+        for name in staging.paired {
+            if self.toggled.contains(&name) {
+                self.paired.insert(name);
+            }
+        }
+        for (name, change) in staging.run_changes {
+            if change != RunChange::Keep || self.toggled.contains(&name) {
+                self.run_changes.insert(name, change);
+            }
+        }
+    }
+
+    /// From the results view: offer a TimeoutStopSec drop-in for the unit
+    /// whose stop was slowest.
+    pub fn begin_stop_timeout(&mut self) {
+        if self.refuse_in_machine() {
+            return;
+        }
+        let slowest = self
+            .results
+            .iter()
+            .filter(|r| r.slow_stop())
+            .max_by_key(|r| self.slow_stops.get(&r.service).copied().unwrap_or(0));
+        if let Some(result) = slowest {
+            self.stop_timeout_unit = Some(result.service.clone());
+            self.prompt_input = "15s".to_string();
+            self.mode = Mode::StopTimeout;
+        }
+    }
+
+    pub fn submit_stop_timeout(&mut self) {
+        let Some(unit) = self.stop_timeout_unit.clone() else {
+            return;
+        };
+        let settings = match stop_settings(&self.prompt_input) {
+            Ok(settings) => settings,
+            Err(e) => {
+                self.notice = Some(e);
+                return;
+            }
+        };
+        self.stop_timeout_unit = None;
+        self.mode = Mode::Results;
+        self.notice = Some(match write_drop_in(&self.scope(), &unit, &settings) {
+            Ok(path) => format!("Wrote {} ({})", path.display(), settings.join(", ")),
+            Err(e) => format!("{e:#}"),
+        });
+    }
+
+    pub fn record_boot(&mut self) {
+        if !self.boot_offer {
+            return;
+        }
+        let changed: Vec<String> = self
+            .results
+            .iter()
+            .filter(|r| r.success)
+            .map(|r| r.service.clone())
+            .collect();
+        self.notice = Some(match boot::record(changed) {
+            Ok(secs) => format!(
+                "Boot time recorded ({secs:.1}s userspace); relaunch after reboot to compare"
+            ),
+            Err(e) => format!("Could not record boot time: {e}"),
+        });
+        self.boot_offer = false;
+    }
+
+    /// Dump every scope's services (as systemd has them, not as staged)
+    /// to a JSON file under the state directory.
+    pub fn export_state(&mut self) {
+        let written = export::collect().and_then(|rows| export::write(Format::Json, &rows));
+        self.notice = Some(match written {
+            Ok(path) => format!("Exported service state to {}", path.display()),
+            Err(e) => format!("Export failed: {e}"),
+        });
+    }
+
+    /// Carry what the last frame showed into the next key press: the list
+    /// stays where it was scrolled, and a modal scrolled further than fits
+    /// (after a resize, or j past the end) comes back to its last page.
+    pub fn fit_viewport(&mut self, list_offset: usize, modal_scroll_max: Option<usize>) {
+        self.list_offset = list_offset;
+        if let Some(max) = modal_scroll_max {
+            self.modal_scroll = self.modal_scroll.min(max);
+        }
+    }
+
+    /// A scheduled batch was handed to systemd: the changes now live in
+    /// a timer, so drop the staged toggles. On failure, keep them staged.
+    pub fn schedule_done(&mut self, result: Result<String, String>) -> Result<()> {
+        self.results.clear();
+        match result {
+            Ok(summary) => {
+                self.notice = Some(summary);
+                self.refresh()
+            }
+            Err(e) => {
+                self.notice = Some(e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Add pasted text to the filter in one go. A copied line's trailing
+    /// newline (or any other control character) isn't part of a unit name.
+    pub fn paste_filter(&mut self, text: &str) {
+        self.filter
+            .extend(text.trim().chars().filter(|c| !c.is_control()));
+        self.rebuild_visible();
+        self.cursor = 0;
+    }
+
+    /// `I`: switch between staging toggles and applying them at once.
+    pub fn toggle_instant(&mut self) {
+        self.config.instant = !self.config.instant;
+        self.notice = Some(if !self.config.instant {
+            "Staged mode: toggles wait for Enter".to_string()
+        } else if self.has_pending_changes() {
+            "Instant mode: the next toggle applies it and everything already staged".to_string()
+        } else {
+            "Instant mode: each toggle applies right away".to_string()
+        });
+    }
+
+    /// Narrow the System tab to services whose process runs as root.
+    pub fn toggle_root_filter(&mut self) {
+        if self.root_only.take().is_none() {
+            if self.tab != Tab::System {
+                self.notice = Some("The root filter applies to system services".to_string());
+                return;
+            }
+            let names: Vec<String> = self.services.iter().map(|s| s.name.clone()).collect();
+            self.root_only = Some(root_services(&self.scope(), &names));
+        }
+        self.cursor = 0;
+        self.rebuild_visible();
     }
 
     pub fn switch_tab(&mut self) -> Result<()> {
+        // The batch's results are matched against this tab's original_state
+        if self.mode == Mode::Applying {
+            self.notice = Some("Wait for the apply to finish before switching tabs".to_string());
+            return Ok(());
+        }
+        self.root_only = None;
         self.tab = match self.tab {
             Tab::System => Tab::User,
-            Tab::User => Tab::System,
+            Tab::User => Tab::Global,
+            Tab::Global => Tab::Timers,
+            Tab::Timers => Tab::System,
         };
+        // Only a container's system manager is reachable with -M
+        if machine().is_some() && matches!(self.tab, Tab::User | Tab::Global) {
+            self.tab = Tab::Timers;
+        }
         self.filter.clear();
-        self.refresh()
+        self.refresh()?;
+
+        if self.tab == Tab::User && !self.graphical_session && !self.session_bound.is_empty() {
+            let count = self.session_bound.len();
+            self.notice = Some(format!(
+                "No graphical session here (SSH?): {count} service{} marked (desktop) stay inactive until a desktop login",
+                if count == 1 { "" } else { "s" }
+            ));
+        }
+        Ok(())
+    }
+
+    /// Per-category counts for the summary line, in list order.
+    pub fn category_health(&self) -> Vec<CategoryHealth> {
+        self.categories
+            .iter()
+            .map(|cat| {
+                let count = |pred: &dyn Fn(&Service) -> bool| {
+                    cat.services
+                        .iter()
+                        .filter(|&&idx| pred(&self.services[idx]))
+                        .count()
+                };
+                CategoryHealth {
+                    name: cat.name,
+                    failed: count(&|svc| self.failed.contains(&svc.name)),
+                    dirty: count(&|svc| self.is_service_dirty(svc)),
+                    active: count(&|svc| svc.active),
+                    total: cat.services.len(),
+                }
+            })
+            .collect()
+    }
+
+    pub fn service_index(&self, name: &str) -> Option<usize> {
+        self.by_name.get(name).copied()
+    }
+
+    pub fn service_named(&self, name: &str) -> Option<&Service> {
+        self.service_index(name).map(|idx| &self.services[idx])
     }
 
     pub fn is_service_dirty(&self, svc: &Service) -> bool {
         self.toggled.contains(&svc.name)
+            || self.masks.contains_key(&svc.name)
+            || self.run_changes.contains_key(&svc.name)
+    }
+
+    /// Masked, counting a staged mask or unmask.
+    pub fn is_masked(&self, svc: &Service) -> bool {
+        self.masks.get(&svc.name).copied().unwrap_or(svc.masked)
     }
 
     pub fn show_info(&mut self) {
         if let Some(VisibleItem::Service(svc_idx)) = self.visible_items.get(self.cursor) {
             let svc = &self.services[*svc_idx];
-            let scope = match self.tab {
-                Tab::System => ServiceScope::System,
-                Tab::User => ServiceScope::User,
-            };
-            self.info = Some(get_service_info(&scope, &svc.name));
+            let scope = self.scope();
+            let mut info = get_service_info(&scope, &svc.name);
+            info.next_run = self.next_runs.get(&svc.name).copied();
+            if info.extra_info.is_empty() && self.config.descriptions_url.is_some() {
+                if let Some(extra) = online::cached_description(&svc.name) {
+                    info.extra_info = extra;
+                }
+            }
+            self.info = Some(info);
+            self.modal_scroll = 0;
             self.mode = Mode::Info;
         }
     }
+
+    pub fn show_compare(&mut self) {
+        if self.remote.is_some() {
+            self.modal_scroll = 0;
+            self.mode = Mode::Compare;
+        }
+    }
+
+    /// Differences between this machine's on-disk state and the remote host.
+    /// Remote data covers system services only (`systemctl -H` has no --user).
+    pub fn comparison(&self) -> Vec<ServiceDiff> {
+        match (&self.remote, self.tab) {
+            (Some(remote), Tab::System) => diff_states(&self.original_state, &remote.enabled),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn open_templates(&mut self) {
+        if self.refuse_with_pending() || self.refuse_in_machine() {
+            return;
+        }
+        self.template_cursor = 0;
+        self.cron_jobs = cron::jobs(&cron::crontab());
+        self.mode = Mode::TemplatePick;
+    }
+
+    pub fn move_template_cursor(&mut self, delta: i32) {
+        let len = (TEMPLATES.len() + self.cron_jobs.len()) as i32;
+        self.template_cursor = (self.template_cursor as i32 + delta).rem_euclid(len) as usize;
+    }
+
+    pub fn choose_template(&mut self) {
+        let wizard = match self.template_cursor.checked_sub(TEMPLATES.len()) {
+            Some(idx) => {
+                let job = self.cron_jobs[idx].clone();
+                Wizard {
+                    template: 0,
+                    defaults: job.defaults(),
+                    cron: Some(job),
+                    answers: Vec::new(),
+                }
+            }
+            None => Wizard {
+                template: self.template_cursor,
+                cron: None,
+                answers: Vec::new(),
+                defaults: TEMPLATES[self.template_cursor]
+                    .prompts
+                    .iter()
+                    .map(|p| p.default.to_string())
+                    .collect(),
+            },
+        };
+        self.prompt_input = wizard.defaults[0].clone();
+        self.wizard = Some(wizard);
+        self.mode = Mode::Wizard;
+    }
+
+    /// Record the current answer; after the last prompt, write the unit
+    /// files, reload the user manager, and show the User tab.
+    pub fn submit_wizard_answer(&mut self) {
+        let Some(wizard) = self.wizard.as_mut() else {
+            return;
+        };
+        let step = wizard.answers.len();
+        let answer = match self.prompt_input.trim() {
+            "" => wizard.defaults[step].clone(),
+            typed => typed.to_string(),
+        };
+        wizard.answers.push(answer);
+
+        if let Some(next) = wizard.defaults.get(step + 1) {
+            self.prompt_input = next.clone();
+            return;
+        }
+
+        let Some(wizard) = self.wizard.take() else {
+            return;
+        };
+        self.mode = Mode::Normal;
+        self.notice = Some(match wizard.cron {
+            Some(job) => match self.convert_cron_job(&job, &wizard.answers) {
+                Ok(done) => done,
+                Err(e) => format!("Conversion failed: {e:#}"),
+            },
+            None => match self.install_template(&TEMPLATES[wizard.template], &wizard.answers) {
+                Ok(created) => format!("Created {}; toggle to enable", created.join(", ")),
+                Err(e) => format!("Template failed: {e:#}"),
+            },
+        });
+    }
+
+    /// Write the service and timer, start the timer, and (if asked) retire
+    /// the cron line so the job doesn't run twice.
+    fn convert_cron_job(&mut self, job: &CronJob, answers: &[String]) -> Result<String> {
+        let [name, calendar, comment] = answers else {
+            anyhow::bail!("Missing answers");
+        };
+        let files = cron::render(job, name, calendar)?;
+        templates::install(&files)?;
+        daemon_reload(&ServiceScope::User)?;
+        let timer = format!("{name}.timer");
+        enable_now(&ServiceScope::User, &timer)?;
+        self.tab = Tab::User;
+        self.filter.clear();
+        self.refresh()?;
+        if !comment.eq_ignore_ascii_case("y") {
+            return Ok(format!("{timer} enabled; the cron line still runs too"));
+        }
+        Ok(match cron::retire(job, name) {
+            Ok(()) => format!("{timer} enabled and the cron line commented out"),
+            Err(e) => format!("{timer} enabled, but the cron line is still active: {e:#}"),
+        })
+    }
+
+    fn install_template(
+        &mut self,
+        template: &templates::Template,
+        answers: &[String],
+    ) -> Result<Vec<String>> {
+        let files = templates::render(template, answers)?;
+        let created = templates::install(&files)?;
+        daemon_reload(&ServiceScope::User)?;
+        self.tab = Tab::User;
+        self.filter.clear();
+        self.refresh()?;
+        Ok(created)
+    }
+
+    pub fn cancel_wizard(&mut self) {
+        self.wizard = None;
+        self.mode = Mode::Normal;
+    }
+
+    /// Pick a sched-ext scheduler for scx_loader (CachyOS) to switch to.
+    pub fn open_schedulers(&mut self) {
+        if self.refuse_in_machine() {
+            return;
+        }
+        let installed = self.tab == Tab::System
+            && self
+                .services
+                .iter()
+                .any(|svc| svc.name == sched_ext::LOADER_SERVICE);
+        if !installed {
+            self.notice = Some("scx_loader isn't installed on this system".to_string());
+            return;
+        }
+        match sched_ext::supported() {
+            Ok(schedulers) if !schedulers.is_empty() => {
+                self.sched_cursor = self
+                    .sched_ext
+                    .as_ref()
+                    .and_then(|current| schedulers.iter().position(|s| s == current))
+                    .unwrap_or(0);
+                self.schedulers = schedulers;
+                self.mode = Mode::Scheduler;
+            }
+            Ok(_) => self.notice = Some("scx_loader reports no schedulers".to_string()),
+            Err(e) => {
+                self.notice = Some(format!("{e:#} — is scx_loader.service running?"));
+            }
+        }
+    }
+
+    /// List enabled system services a minimal setup for this desktop would
+    /// turn off, each with why it's probably safe to.
+    pub fn open_advisor(&mut self) {
+        if self.tab != Tab::System {
+            self.notice =
+                Some("The advisor looks at system services; switch to System".to_string());
+            return;
+        }
+        // A container has no desktop of its own
+        self.advisor_desktop = match machine() {
+            Some(_) => Desktop::Headless,
+            None => advisor::detect(),
+        };
+        self.advisor = advisor::candidates(self.advisor_desktop, &self.services);
+        if self.advisor.is_empty() {
+            self.notice = Some(format!(
+                "Nothing to trim: enabled services look minimal for a {}",
+                self.advisor_desktop.label()
+            ));
+            return;
+        }
+        self.advisor_cursor = 0;
+        self.mode = Mode::Advisor;
+    }
+
+    /// `b`: this tab's units by how long they took to start this boot, so
+    /// the slow ones can be staged off from there.
+    pub fn show_blame(&mut self) {
+        if self.refuse_in_machine() {
+            return;
+        }
+        let times = match boot::blame(&self.scope()) {
+            Ok(times) => times,
+            Err(e) => {
+                self.notice = Some(format!("{e:#}"));
+                return;
+            }
+        };
+        let listed: HashSet<&str> = self.services.iter().map(|svc| svc.name.as_str()).collect();
+        let blame: Vec<(String, f64)> = times
+            .into_iter()
+            .filter(|(unit, _)| listed.contains(unit.as_str()))
+            .collect();
+        if blame.is_empty() {
+            self.notice = Some("No start times recorded for this tab's units".to_string());
+            return;
+        }
+        self.blame = blame;
+        self.blame_cursor = 0;
+        self.mode = Mode::Blame;
+    }
+
+    pub fn move_blame_cursor(&mut self, delta: i32) {
+        let len = self.blame.len().max(1) as i32;
+        self.blame_cursor = (self.blame_cursor as i32 + delta).rem_euclid(len) as usize;
+    }
+
+    /// Stage (or unstage) toggling the highlighted unit.
+    pub fn toggle_blame_entry(&mut self) {
+        let Some((unit, _)) = self.blame.get(self.blame_cursor) else {
+            return;
+        };
+        if let Some(idx) = self.service_index(unit) {
+            if self.services[idx].generated {
+                return;
+            }
+            let enabled = !self.services[idx].enabled;
+            self.set_enabled(idx, enabled);
+        }
+    }
+
+    pub fn move_advisor_cursor(&mut self, delta: i32) {
+        let len = self.advisor.len().max(1) as i32;
+        self.advisor_cursor = (self.advisor_cursor as i32 + delta).rem_euclid(len) as usize;
+    }
+
+    /// `p`: the profiles saved for this tab's scope. Saving and restoring
+    /// both start from the on-disk state, so nothing may be staged.
+    pub fn open_profiles(&mut self) {
+        if self.tab == Tab::Timers {
+            self.notice = Some("Profiles cover services; switch to a service tab".to_string());
+            return;
+        }
+        if self.refuse_with_pending() {
+            return;
+        }
+        self.profiles = profiles::list(&self.scope());
+        self.profile_cursor = 0;
+        self.mode = Mode::Profiles;
+    }
+
+    pub fn move_profile_cursor(&mut self, delta: i32) {
+        let len = self.profiles.len().max(1) as i32;
+        self.profile_cursor = (self.profile_cursor as i32 + delta).rem_euclid(len) as usize;
+    }
+
+    pub fn begin_save_profile(&mut self) {
+        self.prompt_input.clear();
+        self.mode = Mode::ProfileName;
+    }
+
+    /// Save under the typed name; a bad name keeps the prompt open.
+    pub fn submit_profile_name(&mut self) {
+        let name = self.prompt_input.trim().to_string();
+        match profiles::save(&name, &self.scope(), &self.services) {
+            Ok(path) => {
+                self.notice = Some(format!("Saved profile {name} to {}", path.display()));
+                self.mode = Mode::Normal;
+            }
+            Err(e) => self.notice = Some(format!("{e:#}")),
+        }
+    }
+
+    /// Stage whatever differs from the highlighted profile and open the
+    /// review, so the restore is applied (or backed out) like any batch.
+    pub fn restore_profile(&mut self) {
+        let Some(name) = self
+            .profiles
+            .get(self.profile_cursor)
+            .map(|p| p.name.clone())
+        else {
+            return;
+        };
+        self.mode = Mode::Normal;
+        let profile = match profiles::load(&name) {
+            Ok(profile) => profile,
+            Err(e) => {
+                self.notice = Some(format!("{e:#}"));
+                return;
+            }
+        };
+        let plan = profiles::restore(&profile, &self.services);
+        for (service, enabled) in &plan.changes {
+            let Some(idx) = self.service_index(service) else {
+                continue;
+            };
+            // Same limit as toggle_current: global links aren't ours to remove
+            if !(self.services[idx].enabled_globally && self.tab == Tab::User) {
+                self.set_enabled(idx, *enabled);
+            }
+        }
+
+        let missing = match plan.missing.len() {
+            0 => String::new(),
+            1 => " (1 saved service is no longer installed)".to_string(),
+            n => format!(" ({n} saved services are no longer installed)"),
+        };
+        if !self.has_pending_changes() {
+            self.notice = Some(format!("Already matches profile {name}{missing}"));
+            return;
+        }
+        self.notice = Some(format!("Restoring profile {name}{missing}"));
+        self.review_changes();
+    }
+
+    pub fn delete_profile(&mut self) {
+        let Some(name) = self
+            .profiles
+            .get(self.profile_cursor)
+            .map(|p| p.name.clone())
+        else {
+            return;
+        };
+        self.notice = Some(match profiles::delete(&name) {
+            Ok(()) => format!("Deleted profile {name}"),
+            Err(e) => format!("{e:#}"),
+        });
+        self.profiles = profiles::list(&self.scope());
+        self.profile_cursor = self
+            .profile_cursor
+            .min(self.profiles.len().saturating_sub(1));
+    }
+
+    /// Stage (or unstage) disabling the highlighted suggestion.
+    pub fn toggle_advisor_candidate(&mut self) {
+        let Some(candidate) = self.advisor.get(self.advisor_cursor) else {
+            return;
+        };
+        if let Some(idx) = self.service_index(&candidate.service) {
+            let enabled = !self.services[idx].enabled;
+            self.set_enabled(idx, enabled);
+        }
+    }
+
+    pub fn move_sched_cursor(&mut self, delta: i32) {
+        let len = self.schedulers.len().max(1) as i32;
+        self.sched_cursor = (self.sched_cursor as i32 + delta).rem_euclid(len) as usize;
+    }
+
+    pub fn choose_scheduler(&mut self) {
+        self.mode = Mode::Normal;
+        let Some(name) = self.schedulers.get(self.sched_cursor).cloned() else {
+            return;
+        };
+        self.notice = Some(match sched_ext::switch(&name) {
+            Ok(()) => {
+                self.sched_ext = sched_ext::current();
+                format!(
+                    "Switched to {name}; set default_sched in /etc/scx_loader.toml to keep it after reboot"
+                )
+            }
+            Err(e) => format!("{e:#}"),
+        });
+    }
+
+    /// Preview a pacman (or apt) hook that restarts the selected system
+    /// service whenever its package is upgraded.
+    pub fn suggest_hook(&mut self) {
+        if self.refuse_in_machine() {
+            return;
+        }
+        let Some(VisibleItem::Service(svc_idx)) = self.visible_items.get(self.cursor) else {
+            return;
+        };
+        if self.tab != Tab::System {
+            self.notice = Some("Restart hooks are for packaged system services".to_string());
+            return;
+        }
+        let name = self.services[*svc_idx].name.clone();
+        let info = get_service_info(&self.scope(), &name);
+        match hooks::suggest(current_distro(), &name, &info.fragment_path) {
+            Ok(hook) => {
+                self.hook = Some(hook);
+                self.mode = Mode::Hook;
+            }
+            Err(e) => self.notice = Some(format!("{e:#}")),
+        }
+    }
+
+    pub fn install_hook(&mut self) {
+        self.mode = Mode::Normal;
+        if let Some(hook) = self.hook.take() {
+            self.notice = Some(match hooks::install(&hook) {
+                Ok(()) => format!("Wrote {}", hook.path.display()),
+                Err(e) => format!("{e:#}"),
+            });
+        }
+    }
+
+    /// Plain text of the open view for `$PAGER`: the log lines, the unit
+    /// file behind the info modal, or the drift/results report.
+    pub fn pager_text(&self) -> Option<String> {
+        let mut text = String::new();
+        match self.mode {
+            Mode::Logs => {
+                let logs = self.logs.as_ref().filter(|logs| !logs.lines.is_empty())?;
+                for line in &logs.lines {
+                    text.push_str(line);
+                    text.push('\n');
+                }
+            }
+            Mode::Info => {
+                let Some(VisibleItem::Service(svc_idx)) = self.visible_items.get(self.cursor)
+                else {
+                    return None;
+                };
+                text = unit_file_text(&self.scope(), &self.services[*svc_idx].name)?;
+            }
+            Mode::UnitFile => text = self.unit_file.as_ref()?.1.clone(),
+            Mode::Drift => {
+                let since = self.drift.since?;
+                text.push_str(&format!(
+                    "Changed outside comma-services since {}:\n\n",
+                    drift::format_date(since)
+                ));
+                for change in &self.drift.changes {
+                    let verb = match (change.local, change.remote) {
+                        (_, Some(true)) => "enabled",
+                        (Some(true), Some(false)) => "disabled",
+                        (Some(_), None) => "removed",
+                        _ => "changed",
+                    };
+                    text.push_str(&format!("{verb:<9} {}\n", change.name));
+                }
+            }
+            Mode::Results => {
+                for result in &self.results {
+                    let timings: Vec<String> = result
+                        .timings
+                        .iter()
+                        .map(|(step, elapsed)| format!("{step} {:.1} s", elapsed.as_secs_f64()))
+                        .collect();
+                    text.push_str(&format!(
+                        "{} {}  {}\n",
+                        if result.success { "✓" } else { "✗" },
+                        result.service,
+                        timings.join(" · ")
+                    ));
+                    if !result.success {
+                        for line in result.message.lines() {
+                            text.push_str(&format!("    {line}\n"));
+                        }
+                    }
+                    if let Some(note) = result.retry_note() {
+                        text.push_str(&format!("    {note}\n"));
+                    }
+                    if let Some(fix) = result.error.as_ref().and_then(|e| e.fix()) {
+                        text.push_str(&format!("    → {fix}\n"));
+                    }
+                }
+            }
+            _ => return None,
+        }
+        Some(text)
+    }
+
+    /// Recent journal lines for the selected service. The log modal counts
+    /// `modal_scroll` back from the newest line, so 0 opens at the end.
+    pub fn show_logs(&mut self) {
+        let Some(VisibleItem::Service(svc_idx)) = self.visible_items.get(self.cursor) else {
+            return;
+        };
+        let logs = journal::snapshot(&self.scope(), &self.services[*svc_idx].name);
+        self.modal_scroll = 0;
+        self.logs = Some(logs);
+        self.mode = Mode::Logs;
+    }
+
+    /// Move the tutorial on if the user just did what its hint asked.
+    /// Returns whether the hint changed.
+    pub fn advance_tutorial(&mut self) -> bool {
+        let Some(step) = self.tutorial else {
+            return false;
+        };
+        let next = step.advance(&Progress {
+            moved: self.cursor > 0,
+            staged: self.has_pending_changes(),
+            info_open: self.mode == Mode::Info,
+            applied: !self.results.is_empty(),
+        });
+        self.tutorial = Some(next);
+        next != step
+    }
+
+    /// `c`: the selected unit's file and drop-ins, as `systemctl cat`
+    /// prints them.
+    pub fn show_unit_file(&mut self) {
+        let Some(VisibleItem::Service(svc_idx)) = self.visible_items.get(self.cursor) else {
+            return;
+        };
+        let name = &self.services[*svc_idx].name;
+        match unit_file_text(&self.scope(), name) {
+            Some(text) => {
+                self.unit_file = Some((name.clone(), text));
+                self.modal_scroll = 0;
+                self.mode = Mode::UnitFile;
+            }
+            None => self.notice = Some(format!("systemctl cat found no unit file for {name}")),
+        }
+    }
+
+    /// `d`: what the selected unit pulls in, as a foldable tree.
+    pub fn show_deps(&mut self) {
+        let Some(VisibleItem::Service(svc_idx)) = self.visible_items.get(self.cursor) else {
+            return;
+        };
+        match DepTree::load(&self.scope(), &self.services[*svc_idx].name) {
+            Ok(tree) => {
+                self.deps = Some(tree);
+                self.mode = Mode::Deps;
+            }
+            Err(e) => self.notice = Some(format!("{e:#}")),
+        }
+    }
+
+    /// Start following the open log snapshot's unit, or the selected
+    /// service's. The caller starts journalctl when this returns true.
+    pub fn begin_follow(&mut self) -> bool {
+        let unit = match (&self.logs, self.visible_items.get(self.cursor)) {
+            (Some(logs), _) if self.mode == Mode::Logs => logs.unit.clone(),
+            (_, Some(VisibleItem::Service(svc_idx))) => self.services[*svc_idx].name.clone(),
+            _ => return false,
+        };
+        self.logs = None;
+        self.follow = Some(LogFollow {
+            unit,
+            lines: Vec::new(),
+            priority: None,
+            paused: false,
+            unseen: 0,
+        });
+        self.modal_scroll = 0;
+        self.mode = Mode::Follow;
+        true
+    }
+
+    pub fn follow_line(&mut self, line: String) {
+        let Some(follow) = &mut self.follow else {
+            return;
+        };
+        follow.lines.push(line);
+        if follow.lines.len() > FOLLOW_LIMIT {
+            follow.lines.drain(..follow.lines.len() - FOLLOW_LIMIT);
+        }
+        // Keep a paused view on the same lines
+        if follow.paused {
+            follow.unseen += 1;
+            self.modal_scroll += 1;
+        }
+    }
+
+    pub fn set_follow_paused(&mut self, paused: bool) {
+        if let Some(follow) = &mut self.follow {
+            follow.paused = paused;
+            if !paused {
+                follow.unseen = 0;
+                self.modal_scroll = 0;
+            }
+        }
+    }
+
+    /// Next priority filter; the caller restarts journalctl with it.
+    pub fn cycle_follow_priority(&mut self) {
+        if let Some(follow) = &mut self.follow {
+            let pos = journal::PRIORITIES
+                .iter()
+                .position(|&p| p == follow.priority)
+                .unwrap_or(0);
+            follow.priority = journal::PRIORITIES[(pos + 1) % journal::PRIORITIES.len()];
+            follow.lines.clear();
+            follow.unseen = 0;
+            self.modal_scroll = 0;
+        }
+    }
+
+    pub fn fix_journal(&mut self) {
+        let Some(problem) = self.logs.as_ref().and_then(|logs| logs.problem) else {
+            return;
+        };
+        self.mode = Mode::Normal;
+        self.logs = None;
+        let user = std::env::var("USER").unwrap_or_default();
+        self.notice = Some(match journal::fix(problem, &user) {
+            Ok(message) => message,
+            Err(e) => format!("{e:#}"),
+        });
+    }
+
+    /// Features that write host files or read host state don't reach
+    /// into a container.
+    fn refuse_in_machine(&mut self) -> bool {
+        if let Some(name) = machine() {
+            self.notice = Some(format!("Not available while managing {name}"));
+        }
+        machine().is_some()
+    }
+
+    /// Creating units reloads the list, which would drop staged toggles.
+    fn refuse_with_pending(&mut self) -> bool {
+        if self.has_pending_changes() {
+            self.notice = Some("Apply or undo pending changes first".to_string());
+        }
+        self.has_pending_changes()
+    }
+
+    pub fn scope(&self) -> ServiceScope {
+        match self.tab {
+            Tab::System => ServiceScope::System,
+            Tab::User => ServiceScope::User,
+            Tab::Global => ServiceScope::Global,
+            Tab::Timers => ServiceScope::System,
+        }
+    }
+
+    /// Prompt for a new name for a copy of the selected unit.
+    pub fn begin_clone(&mut self) {
+        if self.refuse_with_pending() || self.refuse_in_machine() {
+            return;
+        }
+        let Some(VisibleItem::Service(svc_idx)) = self.visible_items.get(self.cursor) else {
+            return;
+        };
+        let name = self.services[*svc_idx].name.clone();
+        let info = get_service_info(&self.scope(), &name);
+        if info.fragment_path.is_empty() {
+            self.notice = Some(format!("{name} has no unit file to copy"));
+            return;
+        }
+
+        self.prompt_input = match name.rsplit_once('.') {
+            Some((stem, suffix)) => format!("{stem}-2.{suffix}"),
+            None => format!("{name}-2"),
+        };
+        self.clone_source = Some((name, PathBuf::from(info.fragment_path)));
+        self.mode = Mode::Clone;
+    }
+
+    /// Copy the unit; returns the new name to open in the editor.
+    pub fn submit_clone(&mut self) -> Option<String> {
+        let (original, source) = self.clone_source.take()?;
+        self.mode = Mode::Normal;
+
+        let result = clone_name(&original, &self.prompt_input)
+            .map_err(anyhow::Error::msg)
+            .and_then(|name| clone_unit(&self.scope(), &source, &name).map(|_| name));
+        match result {
+            Ok(name) => Some(name),
+            Err(e) => {
+                self.notice = Some(format!("Clone failed: {e:#}"));
+                None
+            }
+        }
+    }
+
+    /// `e`: the unit to open in `systemctl edit` for an override drop-in.
+    /// The list is reloaded afterwards, so nothing may be staged.
+    pub fn begin_edit(&mut self) -> Option<String> {
+        if self.refuse_with_pending() || self.refuse_in_machine() {
+            return None;
+        }
+        let Some(VisibleItem::Service(svc_idx)) = self.visible_items.get(self.cursor) else {
+            return None;
+        };
+        let svc = &self.services[*svc_idx];
+        if svc.masked {
+            self.notice = Some(format!("{} is masked; unmask it before editing", svc.name));
+            return None;
+        }
+        Some(svc.name.clone())
+    }
+
+    /// Back from the editor: reload the list so a copy (`full`) or
+    /// whatever the drop-in changed shows up.
+    pub fn edit_done(
+        &mut self,
+        name: &str,
+        full: bool,
+        status: std::io::Result<std::process::ExitStatus>,
+    ) {
+        unit_cache::clear(); // the editor may have added a drop-in
+        self.notice = Some(match status {
+            Ok(status) if status.success() && full => format!("Created {name}; toggle to enable"),
+            Ok(status) if status.success() => format!("Edited {name}'s override; manager reloaded"),
+            Ok(status) if full => format!("Editor exited with {status}; {name} kept as copied"),
+            Ok(status) => format!("Editor exited with {status}; {name} left as it was"),
+            Err(e) => format!("Could not start editor: {e}"),
+        });
+        let _ = self.refresh();
+    }
+
+    /// Run the daemon-reload offered by the header banner.
+    pub fn reload_daemon(&mut self) {
+        if self.needs_reload.is_empty() {
+            return;
+        }
+        let scope = self.scope();
+        self.notice = Some(match daemon_reload(&scope) {
+            Ok(()) => "Reloaded systemd manager configuration".to_string(),
+            Err(e) => format!("{e:#}"),
+        });
+        self.needs_reload = units_needing_reload(&scope);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scroll_window() {
+        // Moving within the window keeps it; past either edge drags it
+        assert_eq!(scroll_window(12, 10, 5, 40), 10);
+        assert_eq!(scroll_window(15, 10, 5, 40), 11);
+        assert_eq!(scroll_window(9, 10, 5, 40), 9);
+        // Shrinking to 3 rows with the cursor at the bottom of 5
+        assert_eq!(scroll_window(14, 10, 3, 40), 12);
+        // Growing past the end pulls the window back
+        assert_eq!(scroll_window(38, 30, 20, 40), 20);
+        assert_eq!(scroll_window(3, 0, 0, 40), 3);
+    }
 }
@@ -0,0 +1,354 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::state_dir;
+use crate::systemd::{paired_trigger, Service, ServiceScope};
+use crate::trace;
+
+const RECORD_FILE: &str = "boot-record.json";
+const BOOT_ID: &str = "/proc/sys/kernel/random/boot_id";
+
+/// Boot timing captured right after an apply, compared on the next boot.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BootRecord {
+    pub boot_id: String,
+    pub userspace_secs: f64,
+    pub changes: Vec<String>,
+}
+
+fn record_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join(RECORD_FILE))
+}
+
+fn current_boot_id() -> String {
+    std::fs::read_to_string(BOOT_ID)
+        .map(|id| id.trim().to_string())
+        .unwrap_or_default()
+}
+
+fn analyze(args: &[&str]) -> Result<String> {
+    let mut cmd = Command::new("systemd-analyze");
+    cmd.args(args);
+    let output = trace::output(&mut cmd).context("Failed to run systemd-analyze")?;
+    if !output.status.success() {
+        bail!(
+            "systemd-analyze: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Userspace startup time of the current boot from `systemd-analyze time`.
+fn userspace_secs() -> Result<f64> {
+    parse_userspace(&analyze(&["time"])?).context("No userspace time in systemd-analyze output")
+}
+
+/// The whole boot, firmware to userspace, for the header. None while the
+/// boot is still running (systemd-analyze refuses until it's finished).
+pub fn total_secs() -> Option<f64> {
+    parse_total(&analyze(&["time"]).ok()?)
+}
+
+/// How long each unit took to start this boot, slowest first, from
+/// `systemd-analyze blame`. User units come from the user manager, which
+/// started at login rather than at boot.
+pub fn blame(scope: &ServiceScope) -> Result<Vec<(String, f64)>> {
+    let output = match scope {
+        ServiceScope::System => analyze(&["blame", "--no-pager"])?,
+        ServiceScope::User => analyze(&["--user", "blame", "--no-pager"])?,
+        ServiceScope::Global => bail!("All-users units start with each login, not at boot"),
+    };
+    Ok(parse_blame(&output))
+}
+
+/// Save this boot's timing and the services just changed.
+pub fn record(changes: Vec<String>) -> Result<f64> {
+    let userspace_secs = userspace_secs()?;
+    let record = BootRecord {
+        boot_id: current_boot_id(),
+        userspace_secs,
+        changes,
+    };
+
+    let path = record_path().context("No state directory (HOME unset)")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(&record)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(userspace_secs)
+}
+
+/// On the first launch after a reboot, compare against the saved record and
+/// consume it. Returns None if there's nothing to report yet.
+pub fn compare_with_record() -> Option<String> {
+    let path = record_path()?;
+    let record: BootRecord = serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+    if record.boot_id == current_boot_id() {
+        return None; // haven't rebooted yet
+    }
+
+    let now = userspace_secs().ok()?;
+    let _ = std::fs::remove_file(&path);
+    Some(comparison_message(&record, now))
+}
+
+fn comparison_message(record: &BootRecord, now: f64) -> String {
+    let delta = record.userspace_secs - now;
+    let verdict = if delta.abs() < 0.05 {
+        "no change".to_string()
+    } else if delta > 0.0 {
+        format!("saved {delta:.1}s")
+    } else {
+        format!("{:.1}s slower", -delta)
+    };
+    format!(
+        "Boot after changing {} service{}: userspace {now:.1}s (was {:.1}s, {verdict})",
+        record.changes.len(),
+        if record.changes.len() == 1 { "" } else { "s" },
+        record.userspace_secs
+    )
+}
+
+/// Extract the "(userspace)" component of `systemd-analyze time` output.
+fn parse_userspace(output: &str) -> Option<f64> {
+    let line = output
+        .lines()
+        .find_map(|l| l.strip_prefix("Startup finished in "))?;
+    let components = line.split(" = ").next()?;
+    components
+        .split(" + ")
+        .find_map(|part| part.strip_suffix(" (userspace)"))
+        .and_then(parse_duration)
+}
+
+/// The "= 22.567s" total ending `systemd-analyze time`'s first line.
+fn parse_total(output: &str) -> Option<f64> {
+    let line = output
+        .lines()
+        .find_map(|l| l.strip_prefix("Startup finished in "))?;
+    parse_duration(line.rsplit_once(" = ")?.1.trim())
+}
+
+/// `systemd-analyze blame` lines are "<time span> <unit>", e.g.
+/// "1min 2.301s dev-sda.device" or "845ms cups.service".
+fn parse_blame(output: &str) -> Vec<(String, f64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (span, unit) = line.trim().rsplit_once(' ')?;
+            Some((unit.to_string(), parse_duration(span)?))
+        })
+        .collect()
+}
+
+/// Render seconds the way systemd-analyze does: "1min 2.5s", "5.12s", "845ms".
+pub fn format_secs(secs: f64) -> String {
+    if secs >= 60.0 {
+        format!("{}min {:.1}s", (secs / 60.0) as u64, secs % 60.0)
+    } else if secs >= 1.0 {
+        format!("{secs:.2}s")
+    } else {
+        format!("{:.0}ms", secs * 1000.0)
+    }
+}
+
+/// Parse systemd's human time spans: "1min 2.345s", "845ms", "3.2s".
+fn parse_duration(text: &str) -> Option<f64> {
+    let mut total = 0.0;
+    let mut seen = false;
+    for token in text.split_whitespace() {
+        let split = token
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(token.len());
+        let (number, unit) = token.split_at(split);
+        let value: f64 = number.parse().ok()?;
+        let scale = match unit {
+            "h" => 3600.0,
+            "min" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            "us" | "µs" => 0.000_001,
+            _ => return None,
+        };
+        total += value * scale;
+        seen = true;
+    }
+    seen.then_some(total)
+}
+
+/// What a reboot would bring up with the staged enablement applied.
+#[derive(Debug, Default, PartialEq)]
+pub struct NextBoot {
+    pub at_boot: Vec<String>,
+    /// Disabled services an enabled socket or timer still starts: (service, trigger).
+    pub on_demand: Vec<(String, String)>,
+    /// Starts at boot only because of staged changes.
+    pub gained: Vec<String>,
+    /// Started at boot before the staged changes.
+    pub lost: Vec<String>,
+}
+
+/// Work out the next boot from staged `services` (`original` is what's on
+/// disk). A trigger's state is its own row when it has one, else `triggers`,
+/// or the service's own when `paired` stages it along.
+pub fn preview(
+    services: &[Service],
+    original: &HashMap<String, bool>,
+    triggers: &HashMap<String, bool>,
+    paired: &HashSet<String>,
+) -> NextBoot {
+    let rows: HashMap<&str, bool> = services
+        .iter()
+        .map(|svc| (svc.name.as_str(), svc.enabled))
+        .collect();
+    let mut next = NextBoot::default();
+
+    for svc in services.iter().filter(|svc| !svc.generated) {
+        let was = original.get(&svc.name).copied().unwrap_or(svc.enabled);
+        if svc.masked {
+            // Not even a socket or timer can start it
+            if was {
+                next.lost.push(svc.name.clone());
+            }
+            continue;
+        }
+        if svc.enabled {
+            next.at_boot.push(svc.name.clone());
+            if !was {
+                next.gained.push(svc.name.clone());
+            }
+            continue;
+        }
+        if was {
+            next.lost.push(svc.name.clone());
+        }
+        let Some(trigger) = paired_trigger(&svc.name, triggers) else {
+            continue;
+        };
+        let trigger_on = match rows.get(trigger.as_str()) {
+            Some(&enabled) => enabled,
+            None if paired.contains(&svc.name) => svc.enabled,
+            None => triggers.get(&trigger).copied().unwrap_or(false),
+        };
+        if trigger_on {
+            next.on_demand.push((svc.name.clone(), trigger));
+        }
+    }
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_units() {
+        let ms = parse_duration("845ms").unwrap();
+        assert!((ms - 0.845).abs() < 1e-9);
+        assert_eq!(parse_duration("1min 2.5s"), Some(62.5));
+        assert_eq!(parse_duration("soon"), None);
+    }
+
+    #[test]
+    fn test_parse_userspace() {
+        let output = "Startup finished in 5.1s (firmware) + 2.3s (loader) + 1.2s (kernel) + 3.4s (initrd) + 10.567s (userspace) = 22.567s\ngraphical.target reached after 10.5s in userspace.\n";
+        assert_eq!(parse_userspace(output), Some(10.567));
+        let no_initrd = "Startup finished in 1.5s (kernel) + 1min 3s (userspace) = 1min 4.5s\n";
+        assert_eq!(parse_userspace(no_initrd), Some(63.0));
+    }
+
+    #[test]
+    fn test_parse_total() {
+        let output =
+            "Startup finished in 2.3s (loader) + 1.2s (kernel) + 10.5s (userspace) = 14.012s\n";
+        assert_eq!(parse_total(output), Some(14.012));
+        assert_eq!(parse_total("Bootup is not yet finished.\n"), None);
+    }
+
+    #[test]
+    fn test_parse_blame() {
+        let output =
+            "1min 2.5s plymouth-quit-wait.service\n     845ms cups.service\n 12us tmp.mount\n";
+        let blame = parse_blame(output);
+        assert_eq!(blame[0], ("plymouth-quit-wait.service".to_string(), 62.5));
+        assert_eq!(blame[1].0, "cups.service");
+        assert!((blame[1].1 - 0.845).abs() < 1e-9);
+        assert_eq!(blame.len(), 3);
+        assert_eq!(format_secs(62.5), "1min 2.5s");
+        assert_eq!(format_secs(0.845), "845ms");
+    }
+
+    fn service(name: &str, enabled: bool) -> Service {
+        Service {
+            name: name.into(),
+            enabled,
+            active: false,
+            enabled_globally: false,
+            generated: false,
+            masked: false,
+            preset: None,
+        }
+    }
+
+    #[test]
+    fn test_preview_counts_staged_and_triggered() {
+        // sshd staged on, cups staged off but its socket stays, avahi off with its socket
+        let services = [
+            service("sshd.service", true),
+            service("cups.service", false),
+            service("avahi-daemon.service", false),
+        ];
+        let original: HashMap<String, bool> = [
+            ("sshd.service".to_string(), false),
+            ("cups.service".to_string(), true),
+            ("avahi-daemon.service".to_string(), true),
+        ]
+        .into();
+        let triggers: HashMap<String, bool> = [
+            ("cups.socket".to_string(), true),
+            ("avahi-daemon.socket".to_string(), true),
+        ]
+        .into();
+        let paired: HashSet<String> = ["avahi-daemon.service".to_string()].into();
+
+        let next = preview(&services, &original, &triggers, &paired);
+        assert_eq!(next.at_boot, ["sshd.service"]);
+        assert_eq!(next.gained, ["sshd.service"]);
+        assert_eq!(next.lost, ["cups.service", "avahi-daemon.service"]);
+        assert_eq!(
+            next.on_demand,
+            [("cups.service".to_string(), "cups.socket".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_preview_masked_ignores_trigger() {
+        let mut masked = service("cups.service", false);
+        masked.masked = true;
+        let original: HashMap<String, bool> = [("cups.service".to_string(), true)].into();
+        let triggers: HashMap<String, bool> = [("cups.socket".to_string(), true)].into();
+
+        let next = preview(&[masked], &original, &triggers, &HashSet::new());
+        assert_eq!(next.lost, ["cups.service"]);
+        assert!(next.on_demand.is_empty());
+    }
+
+    #[test]
+    fn test_comparison_message() {
+        let record = BootRecord {
+            boot_id: "a".into(),
+            userspace_secs: 15.1,
+            changes: vec!["NetworkManager-wait-online.service".into()],
+        };
+        assert_eq!(
+            comparison_message(&record, 12.3),
+            "Boot after changing 1 service: userspace 12.3s (was 15.1s, saved 2.8s)"
+        );
+    }
+}
@@ -1,17 +1,29 @@
 pub const CATEGORY_ORDER: &[&str] = &[
     "Audio",
+    "Backup & Sync",
     "Bluetooth",
     "Containers",
+    "Desktop Session",
     "Display",
+    "Flatpak & Portals",
     "Network",
     "Printing",
     "Security",
+    "Servers",
+    "Snap",
     "Systemd Core",
+    "Virtualization",
+    "VPN",
     "Other",
+    "Generated",
 ];
 
 pub fn categorize(service_name: &str) -> &'static str {
-    let name = service_name.trim_end_matches(".service");
+    // Sockets and timers share their service's stem, and so its category
+    let name = service_name
+        .trim_end_matches(".service")
+        .trim_end_matches(".socket")
+        .trim_end_matches(".timer");
 
     if matches_any(
         name,
@@ -36,6 +48,44 @@ pub fn categorize(service_name: &str) -> &'static str {
         return "Bluetooth";
     }
 
+    if matches_any(
+        name,
+        &["borgmatic", "restic", "syncthing", "rclone", "timeshift"],
+    ) {
+        return "Backup & Sync";
+    }
+
+    if snap_name(service_name).is_some() || name == "snapd" || name.starts_with("snapd.") {
+        return "Snap";
+    }
+
+    if matches_any(
+        name,
+        &[
+            "flatpak-",
+            "xdg-desktop-portal",
+            "xdg-document-portal",
+            "xdg-permission-store",
+        ],
+    ) {
+        return "Flatpak & Portals";
+    }
+
+    if matches_any(
+        name,
+        &[
+            "xdg-",
+            "gvfs-",
+            "gnome-keyring",
+            "polkit-",
+            "lxpolkit",
+            "hyprpolkitagent",
+            "at-spi",
+        ],
+    ) {
+        return "Desktop Session";
+    }
+
     if matches_any(name, &["gdm", "sddm", "lightdm", "greetd", "ly"]) {
         return "Display";
     }
@@ -48,10 +98,57 @@ pub fn categorize(service_name: &str) -> &'static str {
         return "Security";
     }
 
+    if matches_any(
+        name,
+        &[
+            "postgresql",
+            "mariadb",
+            "mysql",
+            "redis",
+            "valkey",
+            "nginx",
+            "caddy",
+            "httpd",
+            "apache2",
+        ],
+    ) {
+        return "Servers";
+    }
+
     if matches_any(name, &["cups", "avahi"]) {
         return "Printing";
     }
 
+    if matches_any(
+        name,
+        &[
+            "libvirt",
+            "virtqemud",
+            "virtlogd",
+            "virtlockd",
+            "virtnetworkd",
+            "virtstoraged",
+            "vbox",
+            "qemu-guest-agent",
+            "spice-vdagentd",
+        ],
+    ) {
+        return "Virtualization";
+    }
+
+    if matches_any(
+        name,
+        &[
+            "openvpn",
+            "wg-quick",
+            "tailscaled",
+            "zerotier-one",
+            "headscale",
+        ],
+    ) {
+        return "VPN";
+    }
+
     if name.starts_with("systemd-") {
         return "Systemd Core";
     }
@@ -59,6 +156,28 @@ pub fn categorize(service_name: &str) -> &'static str {
     "Other"
 }
 
+/// Daemons that typically accept connections from other machines, where
+/// running without an AppArmor/SELinux profile matters most.
+pub fn is_network_facing(service_name: &str) -> bool {
+    let name = service_name.trim_end_matches(".service");
+    matches!(categorize(service_name), "Servers" | "VPN" | "Printing")
+        || matches_any(
+            name,
+            &["sshd", "smb", "nmb", "nfs-server", "rpcbind", "vsftpd"],
+        )
+}
+
+/// Login managers, which all claim the display-manager.service alias.
+pub fn is_display_manager(service_name: &str) -> bool {
+    categorize(service_name) == "Display"
+}
+
+/// The snap that owns a `snap.<snap>.<app>.service` unit.
+pub fn snap_name(service_name: &str) -> Option<&str> {
+    let rest = service_name.strip_prefix("snap.")?;
+    rest.split('.').next().filter(|name| !name.is_empty())
+}
+
 fn matches_any(name: &str, patterns: &[&str]) -> bool {
     patterns.iter().any(|p| name.starts_with(p))
 }
@@ -71,6 +190,8 @@ mod tests {
     fn test_categorize_network() {
         assert_eq!(categorize("NetworkManager.service"), "Network");
         assert_eq!(categorize("wpa_supplicant.service"), "Network");
+        assert_eq!(categorize("NetworkManager-dispatcher.timer"), "Network");
+        assert_eq!(categorize("systemd-networkd.socket"), "Network");
     }
 
     #[test]
@@ -91,8 +212,78 @@ mod tests {
         assert_eq!(categorize("systemd-resolved.service"), "Network");
     }
 
+    #[test]
+    fn test_categorize_vpn() {
+        assert_eq!(categorize("openvpn-client@.service"), "VPN");
+        assert_eq!(categorize("wg-quick@wg0.service"), "VPN");
+        assert_eq!(categorize("tailscaled.service"), "VPN");
+        assert_eq!(categorize("zerotier-one.service"), "VPN");
+    }
+
+    #[test]
+    fn test_categorize_backup_sync() {
+        assert_eq!(categorize("borgmatic.service"), "Backup & Sync");
+        assert_eq!(categorize("syncthing@.service"), "Backup & Sync");
+        assert_eq!(categorize("rclone-mount@gdrive.service"), "Backup & Sync");
+    }
+
+    #[test]
+    fn test_categorize_servers() {
+        assert_eq!(categorize("postgresql.service"), "Servers");
+        assert_eq!(categorize("redis.service"), "Servers");
+        assert_eq!(categorize("httpd.service"), "Servers");
+        assert_eq!(categorize("apache2.service"), "Servers");
+    }
+
+    #[test]
+    fn test_categorize_virtualization() {
+        assert_eq!(categorize("libvirtd.service"), "Virtualization");
+        assert_eq!(categorize("virtqemud.service"), "Virtualization");
+        assert_eq!(categorize("vboxdrv.service"), "Virtualization");
+        assert_eq!(categorize("spice-vdagentd.service"), "Virtualization");
+    }
+
+    #[test]
+    fn test_categorize_desktop_session() {
+        assert_eq!(
+            categorize("xdg-desktop-portal-gtk.service"),
+            "Flatpak & Portals"
+        );
+        assert_eq!(categorize("gvfs-daemon.service"), "Desktop Session");
+        assert_eq!(
+            categorize("gnome-keyring-daemon.service"),
+            "Desktop Session"
+        );
+        assert_eq!(categorize("at-spi-dbus-bus.service"), "Desktop Session");
+    }
+
+    #[test]
+    fn test_is_network_facing() {
+        assert!(is_network_facing("sshd.service"));
+        assert!(is_network_facing("nginx.service"));
+        assert!(is_network_facing("cups.service"));
+        assert!(!is_network_facing("pipewire.service"));
+    }
+
+    #[test]
+    fn test_is_display_manager() {
+        assert!(is_display_manager("sddm.service"));
+        assert!(is_display_manager("gdm.service"));
+        assert!(!is_display_manager("display-manager.service"));
+    }
+
     #[test]
     fn test_categorize_unknown() {
         assert_eq!(categorize("my-custom-thing.service"), "Other");
     }
+
+    #[test]
+    fn test_categorize_snap_and_flatpak() {
+        assert_eq!(categorize("snap.lxd.daemon.service"), "Snap");
+        assert_eq!(categorize("snapd.service"), "Snap");
+        assert_eq!(categorize("flatpak-portal.service"), "Flatpak & Portals");
+        assert_eq!(categorize("xdg-user-dirs.service"), "Desktop Session");
+        assert_eq!(snap_name("snap.lxd.daemon.service"), Some("lxd"));
+        assert_eq!(snap_name("snapd.service"), None);
+    }
 }
@@ -0,0 +1,192 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::systemd::Service;
+
+/// System services of the other side, read at startup: fetched from a
+/// host (`--compare HOST`) or loaded from an export (`--compare FILE`).
+#[derive(Debug)]
+pub struct RemoteState {
+    pub host: String,                   // or the export's file name
+    pub enabled: HashMap<String, bool>, // name -> enabled
+}
+
+impl RemoteState {
+    pub fn new(host: String, services: &[Service]) -> Self {
+        let enabled = services
+            .iter()
+            .map(|svc| (svc.name.clone(), svc.enabled))
+            .collect();
+        Self { host, enabled }
+    }
+
+    pub fn from_export(path: &Path) -> Result<Self> {
+        let host = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        Ok(Self {
+            host,
+            enabled: load_export(path)?,
+        })
+    }
+}
+
+/// The fields of an `--export json` row a comparison needs.
+#[derive(Debug, Deserialize)]
+struct ExportedRow {
+    name: String,
+    scope: String,
+    enabled: bool,
+}
+
+/// The system services of an `--export json` file, like `systemctl -H`
+/// gives for a host.
+pub fn load_export(path: &Path) -> Result<HashMap<String, bool>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_export(&text).with_context(|| {
+        format!(
+            "{} isn't a JSON export (comma-services --export json)",
+            path.display()
+        )
+    })
+}
+
+fn parse_export(text: &str) -> Result<HashMap<String, bool>> {
+    let rows: Vec<ExportedRow> = serde_json::from_str(text)?;
+    Ok(rows
+        .into_iter()
+        .filter(|row| row.scope == "system")
+        .map(|row| (row.name, row.enabled))
+        .collect())
+}
+
+/// `comma-services diff A B`: the differences as plain lines, one
+/// service each, for the terminal or a script.
+pub fn render_diff(left: &str, right: &str, diffs: &[ServiceDiff]) -> String {
+    if diffs.is_empty() {
+        return format!("{left} and {right} enable the same system services\n");
+    }
+    let state = |side: Option<bool>| match side {
+        Some(true) => "enabled",
+        Some(false) => "disabled",
+        None => "not installed",
+    };
+    let width = diffs.iter().map(|diff| diff.name.len()).max().unwrap_or(0);
+    let mut out = format!("{:width$}  {left} / {right}\n", "service");
+    for diff in diffs {
+        out.push_str(&format!(
+            "{:width$}  {} / {}\n",
+            diff.name,
+            state(diff.local),
+            state(diff.remote)
+        ));
+    }
+    out
+}
+
+/// A service whose enablement differs between the two machines.
+/// `None` means the unit isn't installed (or isn't toggleable) on that side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceDiff {
+    pub name: String,
+    pub local: Option<bool>,
+    pub remote: Option<bool>,
+}
+
+pub fn diff_states(
+    local: &HashMap<String, bool>,
+    remote: &HashMap<String, bool>,
+) -> Vec<ServiceDiff> {
+    let names: BTreeSet<&String> = local.keys().chain(remote.keys()).collect();
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let l = local.get(name).copied();
+            let r = remote.get(name).copied();
+            // Installed-but-disabled on one side and missing on the other
+            // behaves the same at boot, so it's not worth reporting.
+            let differs = match (l, r) {
+                (Some(a), Some(b)) => a != b,
+                (Some(on), None) | (None, Some(on)) => on,
+                (None, None) => false,
+            };
+            differs.then(|| ServiceDiff {
+                name: name.clone(),
+                local: l,
+                remote: r,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn states(pairs: &[(&str, bool)]) -> HashMap<String, bool> {
+        pairs.iter().map(|(n, e)| (n.to_string(), *e)).collect()
+    }
+
+    #[test]
+    fn test_diff_states_reports_enablement_mismatch() {
+        let local = states(&[("sshd.service", true), ("cups.service", false)]);
+        let remote = states(&[("sshd.service", false), ("cups.service", false)]);
+        assert_eq!(
+            diff_states(&local, &remote),
+            vec![ServiceDiff {
+                name: "sshd.service".into(),
+                local: Some(true),
+                remote: Some(false),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_states_missing_units() {
+        let local = states(&[("docker.service", true), ("gpm.service", false)]);
+        let remote = states(&[("tailscaled.service", true)]);
+        let names: Vec<String> = diff_states(&local, &remote)
+            .into_iter()
+            .map(|d| d.name)
+            .collect();
+        // gpm is disabled locally and absent remotely: same boot behaviour
+        assert_eq!(names, ["docker.service", "tailscaled.service"]);
+    }
+
+    #[test]
+    fn test_parse_export_keeps_system_services() {
+        let text = r#"[
+  {"name": "sshd.service", "scope": "system", "category": "Remote Access",
+   "enabled": true, "active": true, "masked": false},
+  {"name": "pipewire.service", "scope": "user", "category": "Bluetooth & Audio",
+   "enabled": true, "active": true, "masked": false}
+]"#;
+        assert_eq!(
+            parse_export(text).unwrap(),
+            states(&[("sshd.service", true)])
+        );
+        assert!(parse_export("- name: \"sshd.service\"\n").is_err());
+    }
+
+    #[test]
+    fn test_render_diff() {
+        let diffs = diff_states(
+            &states(&[("sshd.service", true)]),
+            &states(&[("sshd.service", false), ("tailscaled.service", true)]),
+        );
+        assert_eq!(
+            render_diff("a.json", "b.json", &diffs),
+            "service             a.json / b.json
+sshd.service        enabled / disabled
+tailscaled.service  not installed / enabled
+"
+        );
+        assert!(render_diff("a", "b", &[]).contains("the same"));
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::systemd::TimeoutPolicy;
+
+const APP_DIR: &str = "comma-services";
+const CONFIG_FILE: &str = "config.toml";
+
+/// Services the maintenance bundle stops when `maintenance` isn't configured.
+const DEFAULT_MAINTENANCE: &[&str] = &[
+    "docker.service",
+    "containerd.service",
+    "podman.service",
+    "postgresql.service",
+    "mariadb.service",
+    "mysqld.service",
+    "redis.service",
+];
+
+/// User settings from `~/.config/comma-services/config.toml`.
+/// Every field is optional; a missing file means all defaults.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Opt-in: JSON endpoint with extra descriptions for units we don't curate.
+    pub descriptions_url: Option<String>,
+    /// Services stopped together by the maintenance bundle (`M`).
+    pub maintenance: Option<Vec<String>>,
+    /// Snapshot unit-file states each launch and report outside changes (`D`).
+    pub track_drift: bool,
+    /// Require typing the name of each system service being disabled.
+    pub strict_confirm: bool,
+    /// Apply User-tab changes on Enter without the review (other tabs keep it).
+    pub skip_user_confirm: bool,
+    /// Review only when more than this many changes are staged.
+    pub confirm_above: Option<usize>,
+    /// Apply each toggle as it's made instead of staging it (`I` switches).
+    pub instant: bool,
+    /// Also list generator-produced units, read-only, under "Generated".
+    pub show_generated: bool,
+    /// Named service groups toggled as one, e.g. `print-server = ["cups", "avahi-daemon"]`.
+    pub roles: BTreeMap<String, Vec<String>>,
+    /// Open logs and reports in `$PAGER` instead of a modal (`|` does it per view).
+    pub pager: bool,
+    /// Seconds each systemctl step of an apply may take (default 10; `--timeout`).
+    pub timeout: Option<u64>,
+    /// How many more times a step that timed out is tried.
+    pub timeout_retries: u32,
+    /// Per-service timeouts for slow starters and stoppers, e.g. `postgresql = 60`.
+    pub timeouts: BTreeMap<String, u64>,
+}
+
+impl Config {
+    pub fn load() -> Result<Self> {
+        let Some(path) = config_dir().map(|dir| dir.join(CONFIG_FILE)) else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("Invalid config in {}", path.display()))
+    }
+
+    fn parse(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    pub fn maintenance_services(&self) -> Vec<String> {
+        match &self.maintenance {
+            Some(names) => names.iter().map(|n| unit_name(n)).collect(),
+            None => DEFAULT_MAINTENANCE.iter().map(|n| n.to_string()).collect(),
+        }
+    }
+
+    pub fn timeout_policy(&self) -> TimeoutPolicy {
+        let seconds = |secs: u64| std::time::Duration::from_secs(secs.max(1));
+        let default = TimeoutPolicy::default();
+        TimeoutPolicy {
+            timeout: self.timeout.map(seconds).unwrap_or(default.timeout),
+            retries: self.timeout_retries,
+            per_unit: self
+                .timeouts
+                .iter()
+                .map(|(name, secs)| (unit_name(name), seconds(*secs)))
+                .collect(),
+        }
+    }
+
+    /// Whether Enter should open the review for `count` staged changes,
+    /// `user_scope` ones being where a mistake costs least.
+    pub fn wants_confirm(&self, user_scope: bool, count: usize) -> bool {
+        if user_scope && self.skip_user_confirm {
+            return false;
+        }
+        self.confirm_above.is_none_or(|limit| count > limit)
+    }
+
+    /// Roles in name order, with shorthand expanded to unit names.
+    pub fn roles(&self) -> Vec<(String, Vec<String>)> {
+        self.roles
+            .iter()
+            .map(|(role, names)| (role.clone(), names.iter().map(|n| unit_name(n)).collect()))
+            .collect()
+    }
+}
+
+/// Accept "docker" as shorthand for "docker.service" in config lists.
+fn unit_name(name: &str) -> String {
+    if name.contains('.') {
+        name.to_string()
+    } else {
+        format!("{name}.service")
+    }
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+pub fn cache_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
+
+/// `~/.config/systemd/user`, where per-user unit files live.
+pub fn user_unit_dir() -> Option<PathBuf> {
+    xdg_base("XDG_CONFIG_HOME", ".config").map(|base| base.join("systemd/user"))
+}
+
+fn xdg_dir(var: &str, home_fallback: &str) -> Option<PathBuf> {
+    xdg_base(var, home_fallback).map(|base| base.join(APP_DIR))
+}
+
+fn xdg_base(var: &str, home_fallback: &str) -> Option<PathBuf> {
+    match std::env::var_os(var) {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => Some(PathBuf::from(std::env::var_os("HOME")?).join(home_fallback)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_empty_config_is_default() {
+        let config = Config::parse("").unwrap();
+        assert!(config.descriptions_url.is_none());
+        assert!(!config.track_drift);
+    }
+
+    #[test]
+    fn test_parse_descriptions_url() {
+        let config = Config::parse("descriptions_url = \"https://example.org/d.json\"").unwrap();
+        assert_eq!(
+            config.descriptions_url.as_deref(),
+            Some("https://example.org/d.json")
+        );
+    }
+
+    #[test]
+    fn test_maintenance_services_default_and_shorthand() {
+        let default = Config::parse("").unwrap();
+        assert!(default
+            .maintenance_services()
+            .contains(&"docker.service".to_string()));
+
+        let custom = Config::parse("maintenance = [\"nginx\", \"foo.socket\"]").unwrap();
+        assert_eq!(
+            custom.maintenance_services(),
+            ["nginx.service", "foo.socket"]
+        );
+    }
+
+    #[test]
+    fn test_parse_roles() {
+        let config = Config::parse(
+            "[roles]\nprint-server = [\"cups\", \"avahi-daemon\"]\ndev-box = [\"docker\", \"containerd.socket\"]\n",
+        )
+        .unwrap();
+        let roles = config.roles();
+        assert_eq!(roles[0].0, "dev-box");
+        assert_eq!(roles[0].1, ["docker.service", "containerd.socket"]);
+        assert_eq!(roles[1].1, ["cups.service", "avahi-daemon.service"]);
+    }
+
+    #[test]
+    fn test_wants_confirm() {
+        let default = Config::parse("").unwrap();
+        assert!(default.wants_confirm(true, 1));
+        let user = Config::parse("skip_user_confirm = true").unwrap();
+        assert!(!user.wants_confirm(true, 20));
+        assert!(user.wants_confirm(false, 1));
+        let above = Config::parse("confirm_above = 3").unwrap();
+        assert!(!above.wants_confirm(false, 3));
+        assert!(above.wants_confirm(false, 4));
+    }
+
+    #[test]
+    fn test_timeout_policy() {
+        let config =
+            Config::parse("timeout = 20\ntimeout_retries = 1\n[timeouts]\npostgresql = 90\n")
+                .unwrap();
+        let policy = config.timeout_policy();
+        assert_eq!(policy.timeout.as_secs(), 20);
+        assert_eq!(policy.retries, 1);
+        assert_eq!(policy.per_unit["postgresql.service"].as_secs(), 90);
+        assert_eq!(
+            Config::parse("")
+                .unwrap()
+                .timeout_policy()
+                .timeout
+                .as_secs(),
+            10
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_types() {
+        assert!(Config::parse("descriptions_url = 3").is_err());
+    }
+}
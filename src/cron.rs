@@ -0,0 +1,359 @@
+use std::io::Write;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use crate::config::state_dir;
+use crate::systemd::valid_unit_name;
+use crate::templates::Prompt;
+use crate::trace;
+
+/// Questions the conversion wizard asks; the defaults come from the job.
+pub const PROMPTS: &[Prompt] = &[
+    Prompt {
+        key: "name",
+        question: "Unit name",
+        default: "",
+    },
+    Prompt {
+        key: "schedule",
+        question: "When (OnCalendar)",
+        default: "",
+    },
+    Prompt {
+        key: "comment",
+        question: "Comment out the cron line (y/n)",
+        default: "y",
+    },
+];
+
+/// A line of the user's crontab that a timer can stand in for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronJob {
+    pub line: usize, // index into the crontab's lines
+    pub entry: String,
+    pub command: String,
+    pub calendar: String, // the schedule as an OnCalendar= expression
+}
+
+impl CronJob {
+    /// `cron-` plus the command's program name, e.g. `cron-backup`.
+    pub fn unit_name(&self) -> String {
+        let program = self
+            .command
+            .split_whitespace()
+            .next()
+            .and_then(|word| word.rsplit('/').next())
+            .unwrap_or_default();
+        let stem: String = program
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+            .collect();
+        if stem.is_empty() {
+            "cron-job".to_string()
+        } else {
+            format!("cron-{stem}")
+        }
+    }
+
+    pub fn defaults(&self) -> Vec<String> {
+        vec![self.unit_name(), self.calendar.clone(), "y".to_string()]
+    }
+}
+
+/// `crontab -l`; no crontab (or no cron at all) reads as empty.
+pub fn crontab() -> String {
+    let mut cmd = Command::new("crontab");
+    cmd.arg("-l");
+    match trace::output(&mut cmd) {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        }
+        _ => String::new(),
+    }
+}
+
+/// The jobs in `crontab` with an exact timer equivalent. `@reboot`, `%`
+/// (stdin to the command) and day-of-month together with day-of-week
+/// (cron runs on either, a timer only on both) have none.
+pub fn jobs(crontab: &str) -> Vec<CronJob> {
+    crontab
+        .lines()
+        .enumerate()
+        .filter_map(|(line, entry)| {
+            let (calendar, command) = parse_entry(entry.trim())?;
+            Some(CronJob {
+                line,
+                entry: entry.to_string(),
+                command,
+                calendar,
+            })
+        })
+        .collect()
+}
+
+fn parse_entry(entry: &str) -> Option<(String, String)> {
+    if entry.is_empty() || entry.starts_with('#') {
+        return None;
+    }
+    let (schedule, command) = if let Some(special) = entry.strip_prefix('@') {
+        let (name, command) = special.split_once(char::is_whitespace)?;
+        let calendar = match name {
+            "hourly" | "daily" | "weekly" | "monthly" | "yearly" => name,
+            "midnight" => "daily",
+            "annually" => "yearly",
+            _ => return None,
+        };
+        (calendar.to_string(), command)
+    } else {
+        let mut rest = entry;
+        let mut fields = Vec::new();
+        for _ in 0..5 {
+            let (field, tail) = rest.split_once(char::is_whitespace)?;
+            fields.push(field);
+            rest = tail.trim_start();
+        }
+        (on_calendar(&fields)?, rest)
+    };
+    // An unescaped % ends the command; the rest is its stdin
+    let command = command.trim();
+    if command.is_empty() || command.replace("\\%", "").contains('%') {
+        return None;
+    }
+    Some((schedule, command.replace("\\%", "%")))
+}
+
+/// `minute hour day-of-month month day-of-week` as OnCalendar=, e.g.
+/// `30 2 * * 1-5` -> `Mon..Fri *-*-* 02:30:00`. Environment lines
+/// (`MAILTO=...`) don't parse and are skipped.
+fn on_calendar(fields: &[&str]) -> Option<String> {
+    let [minute, hour, day, month, weekday] = fields else {
+        return None;
+    };
+    if *day != "*" && *weekday != "*" {
+        return None;
+    }
+    let minute = field(minute, 0, &[])?;
+    let hour = field(hour, 0, &[])?;
+    let day = field(day, 1, &[])?;
+    let month = field(month, 1, MONTHS)?;
+    let date = format!("*-{month}-{day} {hour}:{minute}:00");
+    match *weekday {
+        "*" => Some(date),
+        days => Some(format!("{} {date}", weekdays(days)?)),
+    }
+}
+
+const MONTHS: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: &[&str] = &["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// One numeric field: lists, `a-b` ranges and `*/n` or `a/n` steps.
+/// `names` are accepted in place of numbers from `first` on.
+fn field(value: &str, first: u32, names: &[&str]) -> Option<String> {
+    if value == "*" {
+        return Some("*".to_string());
+    }
+    let number = |text: &str| -> Option<String> {
+        let n = match names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(text))
+        {
+            Some(idx) => idx as u32 + first,
+            None => text.parse().ok()?,
+        };
+        Some(format!("{n:02}"))
+    };
+    let parts: Option<Vec<String>> = value
+        .split(',')
+        .map(|part| match part.split_once('/') {
+            Some(("*", step)) => Some(format!("{first:02}/{}", step.parse::<u32>().ok()?)),
+            Some((start, step)) if !start.contains('-') => {
+                Some(format!("{}/{}", number(start)?, step.parse::<u32>().ok()?))
+            }
+            Some(_) => None,
+            None => match part.split_once('-') {
+                Some((from, to)) => Some(format!("{}..{}", number(from)?, number(to)?)),
+                None => number(part),
+            },
+        })
+        .collect();
+    Some(parts?.join(","))
+}
+
+/// Cron's day of week (0 or 7 is Sunday, names allowed) as systemd's.
+fn weekdays(value: &str) -> Option<String> {
+    let day = |text: &str| -> Option<usize> {
+        match WEEKDAYS.iter().position(|d| d.eq_ignore_ascii_case(text)) {
+            Some(idx) => Some(idx),
+            None => text.parse().ok().filter(|n| *n < WEEKDAYS.len()),
+        }
+    };
+    let parts: Option<Vec<String>> = value
+        .split(',')
+        .map(|part| match part.split_once('-') {
+            // systemd's week starts on Monday, so a range from Sunday splits
+            Some((from, to)) => match (day(from)?, day(to)?) {
+                (0, 0) => Some("Sun".to_string()),
+                (0, to) => Some(format!("Sun,Mon..{}", WEEKDAYS[to])),
+                (from, to) if from < to => Some(format!("{}..{}", WEEKDAYS[from], WEEKDAYS[to])),
+                _ => None,
+            },
+            None if part.contains('/') => None,
+            None => Some(WEEKDAYS[day(part)?].to_string()),
+        })
+        .collect();
+    Some(parts?.join(","))
+}
+
+/// The service and timer that replace `job`, named `name`.
+pub fn render(job: &CronJob, name: &str, calendar: &str) -> Result<Vec<(String, String)>> {
+    if !valid_unit_name(&format!("{name}.timer")) || name.contains('@') {
+        bail!("Not a unit name: {name:?}");
+    }
+    if calendar.trim().is_empty() || calendar.contains('\n') {
+        bail!("Not a calendar expression: {calendar:?}");
+    }
+    // systemd expands % specifiers and $VARIABLES itself
+    let systemd_escape = |text: &str| text.replace('%', "%%").replace('$', "$$");
+    let command = systemd_escape(&job.command)
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    let service = format!(
+        "[Unit]
+Description=Converted from crontab: {}
+
+[Service]
+Type=oneshot
+ExecStart=/bin/sh -c \"{command}\"
+",
+        systemd_escape(job.entry.trim())
+    );
+    let timer = format!(
+        "[Unit]
+Description=Runs {name}.service (was a cron job)
+
+[Timer]
+OnCalendar={}
+
+[Install]
+WantedBy=timers.target
+",
+        calendar.trim()
+    );
+    Ok(vec![
+        (format!("{name}.service"), service),
+        (format!("{name}.timer"), timer),
+    ])
+}
+
+/// `crontab` with `job`'s line commented out, pointing at the timer.
+fn comment_out(crontab: &str, job: &CronJob, name: &str) -> Result<String> {
+    let mut lines: Vec<String> = crontab.lines().map(String::from).collect();
+    match lines.get_mut(job.line) {
+        Some(line) if *line == job.entry => {
+            *line = format!("# now {name}.timer (comma-services): {line}");
+        }
+        _ => bail!("The crontab changed since it was read; left it alone"),
+    }
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Comment out `job` in the installed crontab. The new one is staged in
+/// the state directory, which only we can write to, never a shared /tmp.
+pub fn retire(job: &CronJob, name: &str) -> Result<()> {
+    let text = comment_out(&crontab(), job, name)?;
+    let dir = state_dir().context("No state directory (HOME unset)")?;
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("crontab-{}", std::process::id()));
+    // A leftover from a crashed run; create_new won't follow what's there
+    let _ = std::fs::remove_file(&path);
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    let mut cmd = Command::new("crontab");
+    cmd.arg(&path);
+    let output = trace::output(&mut cmd);
+    let _ = std::fs::remove_file(&path);
+    let output = output.context("Failed to run crontab")?;
+    if !output.status.success() {
+        bail!(
+            "crontab failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_on_calendar() {
+        let calendar = |fields: &str| on_calendar(&fields.split(' ').collect::<Vec<_>>());
+        assert_eq!(
+            calendar("30 2 * * 1-5").as_deref(),
+            Some("Mon..Fri *-*-* 02:30:00")
+        );
+        assert_eq!(
+            calendar("*/15 * * * *").as_deref(),
+            Some("*-*-* *:00/15:00")
+        );
+        assert_eq!(
+            calendar("0 9,17 1 jan *").as_deref(),
+            Some("*-01-01 09,17:00:00")
+        );
+        assert_eq!(
+            calendar("0 0 * * 0-2").as_deref(),
+            Some("Sun,Mon..Tue *-*-* 00:00:00")
+        );
+        assert_eq!(calendar("0 0 1 * 1"), None, "cron ORs the two days");
+        assert_eq!(calendar("0-30/10 * * * *"), None);
+    }
+
+    #[test]
+    fn test_jobs_skip_what_has_no_timer() {
+        let crontab = "MAILTO=me@example.com
+# nightly backup
+0 3 * * * /usr/local/bin/backup --quiet
+@reboot ~/bin/start-agent
+@daily date +\\%F >> ~/log
+* * * * * mail -s hi me%body
+";
+        let jobs = jobs(crontab);
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].line, 2);
+        assert_eq!(jobs[0].calendar, "*-*-* 03:00:00");
+        assert_eq!(jobs[0].unit_name(), "cron-backup");
+        assert_eq!(jobs[1].calendar, "daily");
+        assert_eq!(jobs[1].command, "date +%F >> ~/log");
+    }
+
+    #[test]
+    fn test_render_escapes_command() {
+        let job = &jobs("@daily echo \"$HOME\" \\% done\n")[0];
+        let files = render(job, "cron-echo", "daily").unwrap();
+        assert_eq!(files[0].0, "cron-echo.service");
+        assert!(files[0]
+            .1
+            .contains("ExecStart=/bin/sh -c \"echo \\\"$$HOME\\\" %% done\""));
+        assert!(files[1].1.contains("OnCalendar=daily"));
+        assert!(render(job, "../x", "daily").is_err());
+    }
+
+    #[test]
+    fn test_comment_out_checks_line() {
+        let crontab = "MAILTO=me\n0 3 * * * backup\n";
+        let job = &jobs(crontab)[0];
+        assert_eq!(
+            comment_out(crontab, job, "cron-backup").unwrap(),
+            "MAILTO=me\n# now cron-backup.timer (comma-services): 0 3 * * * backup\n"
+        );
+        assert!(comment_out("0 4 * * * backup\n", job, "cron-backup").is_err());
+    }
+}
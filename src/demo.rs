@@ -0,0 +1,266 @@
+use std::io;
+use std::os::unix::process::ExitStatusExt;
+use std::process::{ExitStatus, Output};
+
+use crate::systemd::batch_prelude;
+
+/// A made-up machine for `--tutorial`: enough units to move around, read
+/// about and toggle, answering the systemctl calls the app makes so
+/// nothing on the real system is read or changed.
+#[derive(Debug)]
+pub struct World {
+    units: Vec<Unit>,
+}
+
+#[derive(Debug)]
+struct Unit {
+    user: bool,
+    name: &'static str,
+    description: &'static str,
+    enabled: bool,
+    active: bool,
+    masked: bool,
+}
+
+/// (name, Description=, enabled at boot); enabled ones are running.
+const SYSTEM_UNITS: &[(&str, &str, bool)] = &[
+    ("bluetooth.service", "Bluetooth service", true),
+    ("cups.service", "CUPS Scheduler", true),
+    ("cups.socket", "CUPS Scheduler", true),
+    ("avahi-daemon.service", "Avahi mDNS/DNS-SD Stack", true),
+    ("ModemManager.service", "Modem Manager", true),
+    ("docker.service", "Docker Engine", false),
+    ("libvirtd.service", "libvirt daemon", false),
+    ("fstrim.service", "Discard unused blocks", false),
+    ("power-profiles-daemon.service", "Power Profiles", true),
+];
+const USER_UNITS: &[(&str, &str, bool)] = &[
+    ("pipewire.service", "PipeWire Multimedia Service", true),
+    ("syncthing.service", "Syncthing", false),
+];
+
+impl World {
+    pub fn new() -> Self {
+        let system = SYSTEM_UNITS.iter().map(|unit| (false, unit));
+        let user = USER_UNITS.iter().map(|unit| (true, unit));
+        let units = system
+            .chain(user)
+            .map(|(user, &(name, description, enabled))| Unit {
+                user,
+                name,
+                description,
+                enabled,
+                active: enabled,
+                masked: false,
+            })
+            .collect();
+        World { units }
+    }
+
+    /// What `argv` would have printed on the demo machine.
+    pub fn answer(&mut self, argv: &[String]) -> io::Result<Output> {
+        let args: Vec<&str> = argv.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            ["systemctl", rest @ ..] => Ok(self.systemctl(rest)),
+            ["pkexec", "/bin/sh", "-c", script] => Ok(self.batch(script)),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "not part of the tutorial",
+            )),
+        }
+    }
+
+    fn systemctl(&mut self, args: &[&str]) -> Output {
+        let user = args.iter().any(|a| matches!(*a, "--user" | "--global"));
+        let option = |key: &str| {
+            args.iter()
+                .find_map(|a| a.strip_prefix(key)?.strip_prefix('='))
+                .unwrap_or("")
+        };
+        let types: Vec<&str> = option("--type").split(',').collect();
+        let names: Vec<&str> = match args.iter().position(|a| *a == "--") {
+            Some(idx) => args[idx + 1..].to_vec(),
+            None => Vec::new(),
+        };
+        let verb = args
+            .iter()
+            .find(|a| !a.starts_with('-'))
+            .copied()
+            .unwrap_or("");
+        let listed = self.units.iter().filter(|unit| {
+            unit.user == user && types.iter().any(|t| unit.name.ends_with(&format!(".{t}")))
+        });
+
+        let stdout = match verb {
+            "list-unit-files" if option("--state").is_empty() => listed
+                .map(|unit| {
+                    let state = match (unit.masked, unit.enabled) {
+                        (true, _) => "masked",
+                        (_, true) => "enabled",
+                        (_, false) => "disabled",
+                    };
+                    format!("{} {state} enabled\n", unit.name)
+                })
+                .collect(),
+            "list-units" if option("--state") == "active" => listed
+                .filter(|unit| unit.active)
+                .map(|unit| format!("{} loaded active running {}\n", unit.name, unit.description))
+                .collect(),
+            "show" => names
+                .iter()
+                .filter_map(|name| self.unit(user, name))
+                .map(Unit::show)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "cat" => names
+                .iter()
+                .filter_map(|name| self.unit(user, name))
+                .map(Unit::cat)
+                .collect(),
+            "enable" | "disable" | "start" | "stop" | "restart" | "mask" | "unmask" => {
+                for name in names {
+                    self.change(user, verb, name);
+                }
+                String::new()
+            }
+            _ => String::new(),
+        };
+        output(0, stdout)
+    }
+
+    /// The one-line apply script (`systemd::batch_script`): run its steps
+    /// and print each as done in a moment.
+    fn batch(&mut self, script: &str) -> Output {
+        let steps = script
+            .strip_prefix(batch_prelude().as_str())
+            .and_then(|s| s.strip_suffix("exit 0"))
+            .unwrap_or("");
+        let mut stdout = String::new();
+        for step in steps.split("; ").flat_map(|change| change.split(" && ")) {
+            let words: Vec<&str> = step.split_whitespace().collect();
+            // r <timeout> <retries> [--global] <verb> ... -- <unit>
+            let (Some(verb), Some(unit)) = (
+                words.iter().skip(3).find(|w| !w.starts_with('-')),
+                words.last(),
+            ) else {
+                continue;
+            };
+            let user = words.contains(&"--global");
+            self.change(user, verb, unit.trim_matches('\''));
+            stdout.push_str("0\t40\t0\t\n");
+        }
+        output(0, stdout)
+    }
+
+    fn unit(&self, user: bool, name: &str) -> Option<&Unit> {
+        self.units
+            .iter()
+            .find(|unit| unit.user == user && unit.name == name)
+    }
+
+    fn change(&mut self, user: bool, verb: &str, name: &str) {
+        let Some(unit) = self
+            .units
+            .iter_mut()
+            .find(|unit| unit.user == user && unit.name == name)
+        else {
+            return;
+        };
+        match verb {
+            "enable" => unit.enabled = true,
+            "disable" => unit.enabled = false,
+            "start" | "restart" => unit.active = true,
+            "stop" => unit.active = false,
+            "mask" => unit.masked = true,
+            "unmask" => unit.masked = false,
+            _ => {}
+        }
+    }
+}
+
+impl Unit {
+    fn path(&self) -> String {
+        let dir = if self.user {
+            "/usr/lib/systemd/user"
+        } else {
+            "/usr/lib/systemd/system"
+        };
+        format!("{dir}/{}", self.name)
+    }
+
+    fn show(&self) -> String {
+        let (state, sub) = if self.active {
+            ("active", "running")
+        } else {
+            ("inactive", "dead")
+        };
+        format!(
+            "Id={}\nDescription={}\nActiveState={state}\nSubState={sub}\nFragmentPath={}\nType=simple\nMainPID=0\nWantedBy=multi-user.target\n",
+            self.name,
+            self.description,
+            self.path()
+        )
+    }
+
+    fn cat(&self) -> String {
+        format!(
+            "# {}\n[Unit]\nDescription={}\n\n[Service]\nExecStart=/usr/bin/{}\n\n[Install]\nWantedBy=multi-user.target\n",
+            self.path(),
+            self.description,
+            self.name.split('.').next().unwrap_or_default()
+        )
+    }
+}
+
+fn output(code: i32, stdout: String) -> Output {
+    Output {
+        status: ExitStatus::from_raw(code << 8),
+        stdout: stdout.into_bytes(),
+        stderr: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(world: &mut World, argv: &[&str]) -> String {
+        let argv: Vec<String> = argv.iter().map(|a| a.to_string()).collect();
+        String::from_utf8(world.answer(&argv).unwrap().stdout).unwrap()
+    }
+
+    #[test]
+    fn test_batch_changes_the_world() {
+        let mut world = World::new();
+        let script = format!(
+            "{}r 10 0 disable -- 'cups.service' && r 10 0 stop -- 'cups.service'; exit 0",
+            batch_prelude()
+        );
+        assert_eq!(
+            run(&mut world, &["pkexec", "/bin/sh", "-c", &script]),
+            "0\t40\t0\t\n0\t40\t0\t\n"
+        );
+        let files = run(
+            &mut world,
+            &[
+                "systemctl",
+                "list-unit-files",
+                "--type=service",
+                "--no-pager",
+            ],
+        );
+        assert!(files.contains("cups.service disabled"));
+        assert!(!files.contains("cups.socket"));
+        let active = run(
+            &mut world,
+            &[
+                "systemctl",
+                "list-units",
+                "--type=service,socket",
+                "--state=active",
+            ],
+        );
+        assert!(!active.contains("cups.service"));
+        assert!(active.contains("cups.socket"));
+    }
+}
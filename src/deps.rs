@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+use crate::systemd::{list_dependencies, ServiceScope};
+
+/// A unit's state as `list-dependencies` marks it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepState {
+    Active,
+    Inactive,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepNode {
+    pub unit: String,
+    pub depth: usize, // 0 is the unit asked about
+    pub state: Option<DepState>,
+}
+
+/// The dependency tree of one unit, flattened in display order, with
+/// the rows the user folded.
+#[derive(Debug)]
+pub struct DepTree {
+    pub nodes: Vec<DepNode>,
+    pub collapsed: HashSet<usize>, // indices into nodes
+    pub cursor: usize,             // index into visible()
+}
+
+impl DepTree {
+    pub fn load(scope: &ServiceScope, unit: &str) -> Result<Self> {
+        Ok(Self::new(parse_tree(&list_dependencies(scope, unit)?)))
+    }
+
+    fn new(nodes: Vec<DepNode>) -> Self {
+        // Targets pull in dozens of units; start with them folded
+        let collapsed = (0..nodes.len())
+            .filter(|&idx| idx > 0 && nodes[idx].unit.ends_with(".target"))
+            .collect();
+        Self {
+            nodes,
+            collapsed,
+            cursor: 0,
+        }
+    }
+
+    pub fn has_children(&self, idx: usize) -> bool {
+        self.nodes
+            .get(idx + 1)
+            .is_some_and(|next| next.depth > self.nodes[idx].depth)
+    }
+
+    /// Indices of the nodes not hidden under a folded ancestor.
+    pub fn visible(&self) -> Vec<usize> {
+        let mut shown = Vec::new();
+        let mut hide_below: Option<usize> = None;
+        for (idx, node) in self.nodes.iter().enumerate() {
+            if hide_below.is_some_and(|depth| node.depth > depth) {
+                continue;
+            }
+            hide_below = self.collapsed.contains(&idx).then_some(node.depth);
+            shown.push(idx);
+        }
+        shown
+    }
+
+    pub fn move_cursor(&mut self, delta: i32) {
+        let len = self.visible().len().max(1) as i32;
+        self.cursor = (self.cursor as i32 + delta).rem_euclid(len) as usize;
+    }
+
+    /// Fold or unfold the highlighted node; `Some(open)` forces one way.
+    pub fn toggle(&mut self, open: Option<bool>) {
+        let Some(&idx) = self.visible().get(self.cursor) else {
+            return;
+        };
+        if !self.has_children(idx) {
+            return;
+        }
+        let open = open.unwrap_or(self.collapsed.contains(&idx));
+        if open {
+            self.collapsed.remove(&idx);
+        } else {
+            self.collapsed.insert(idx);
+        }
+    }
+}
+
+/// Parse the tree systemctl prints, e.g.
+/// ```text
+/// cups.service
+/// ● ├─cups.socket
+/// ○ └─sysinit.target
+/// ●   └─dev-hugepages.mount
+/// ```
+/// Each level indents the name two columns past the state marker.
+fn parse_tree(output: &str) -> Vec<DepNode> {
+    let mut nodes = Vec::new();
+    for (row, line) in output.lines().enumerate() {
+        if row == 0 {
+            let unit = line.trim();
+            if !unit.is_empty() {
+                nodes.push(DepNode {
+                    unit: unit.to_string(),
+                    depth: 0,
+                    state: None,
+                });
+            }
+            continue;
+        }
+        let mut chars = line.chars();
+        let state = match chars.next() {
+            Some('●') => Some(DepState::Active),
+            Some('○') => Some(DepState::Inactive),
+            Some('×') => Some(DepState::Failed),
+            _ => None,
+        };
+        let tree: String = chars.collect();
+        let Some(start) = tree.find(|c: char| !matches!(c, ' ' | '│' | '├' | '└' | '─'))
+        else {
+            continue;
+        };
+        let column = tree[..start].chars().count();
+        nodes.push(DepNode {
+            unit: tree[start..].trim().to_string(),
+            depth: column.saturating_sub(1) / 2,
+            state,
+        });
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = "cups.service
+● ├─cups.socket
+× ├─cups-browsed.service
+○ └─sysinit.target
+●   ├─dev-hugepages.mount
+○   └─local-fs.target
+●     └─tmp.mount
+";
+
+    #[test]
+    fn test_parse_tree() {
+        let nodes = parse_tree(OUTPUT);
+        let shape: Vec<(&str, usize)> = nodes.iter().map(|n| (n.unit.as_str(), n.depth)).collect();
+        assert_eq!(
+            shape,
+            [
+                ("cups.service", 0),
+                ("cups.socket", 1),
+                ("cups-browsed.service", 1),
+                ("sysinit.target", 1),
+                ("dev-hugepages.mount", 2),
+                ("local-fs.target", 2),
+                ("tmp.mount", 3),
+            ]
+        );
+        assert_eq!(nodes[2].state, Some(DepState::Failed));
+        assert_eq!(nodes[3].state, Some(DepState::Inactive));
+    }
+
+    #[test]
+    fn test_targets_start_folded() {
+        let mut tree = DepTree::new(parse_tree(OUTPUT));
+        assert_eq!(tree.visible(), [0, 1, 2, 3]);
+        tree.cursor = 3;
+        tree.toggle(None);
+        // Unfolding sysinit.target still leaves local-fs.target folded
+        assert_eq!(tree.visible(), [0, 1, 2, 3, 4, 5]);
+        tree.toggle(Some(false));
+        assert_eq!(tree.visible(), [0, 1, 2, 3]);
+    }
+}
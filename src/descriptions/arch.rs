@@ -0,0 +1,22 @@
+pub fn lookup(base: &str) -> Option<&'static str> {
+    match base {
+        // Servers (Arch paths)
+        "postgresql" => Some("PostgreSQL database server. Data lives in /var/lib/postgres/data — run `initdb` as the postgres user before first start. Listens on port 5432."),
+        "mariadb" | "mysqld" => Some("MariaDB database server. Run `mariadb-install-db --user=mysql --basedir=/usr --datadir=/var/lib/mysql` once before first start. Listens on port 3306."),
+        "httpd" => Some("Apache HTTP Server. Config in /etc/httpd/conf/httpd.conf. Listens on ports 80/443."),
+        "cpupower" => Some("CPU frequency scaling. Sets CPU governor (performance/powersave/schedutil) at boot. Config in /etc/default/cpupower."),
+
+        // Package management
+        "reflector" => Some("Arch Linux mirrorlist updater. Fetches latest mirror list and sorts by speed/country. Usually run via timer, not continuously."),
+        "paccache" => Some("Pacman cache cleaner. Removes old package versions from /var/cache/pacman/pkg, keeping the last three. Usually run via paccache.timer."),
+        "pkgfile-update" => Some("Refreshes the pkgfile database used by `pkgfile` and command-not-found hooks. Usually run via pkgfile-update.timer."),
+        "archlinux-keyring-wkd-sync" => Some("Refreshes Arch packager PGP keys from WKD so signature checks keep working between keyring package updates."),
+
+        // CachyOS
+        "ananicy-cpp" => Some("Auto Nice Daemon (C++ rewrite). Automatically adjusts process priorities and I/O scheduling for better desktop responsiveness."),
+        "cachyos-rate-mirrors" => Some("CachyOS mirror rating. Tests and sorts pacman mirrors by speed for faster package downloads."),
+        "scx_loader" => Some("Sched-ext loader. Loads custom Linux CPU schedulers (BORE, Rusty, etc.) for CachyOS's optimized scheduling."),
+
+        _ => None,
+    }
+}
@@ -0,0 +1,141 @@
+pub fn lookup(base: &str) -> Option<&'static str> {
+    match base {
+        // Display managers
+        "gdm" => Some("GNOME Display Manager. Provides graphical login screen and manages user sessions. Handles X11/Wayland session startup."),
+        "sddm" => Some("Simple Desktop Display Manager. Qt-based login screen, commonly used with KDE Plasma."),
+        "lightdm" => Some("Lightweight Display Manager. Cross-desktop login screen supporting multiple greeters."),
+        "ly" => Some("Lightweight TUI display manager. Provides a terminal-based login screen as an alternative to graphical display managers."),
+        "greetd" => Some("Minimal login daemon. Supports pluggable greeter frontends (tuigreet, gtkgreet, etc.)."),
+
+        // Network
+        "NetworkManager" => Some("Desktop network management daemon. Manages WiFi, Ethernet, VPN, and mobile broadband connections. Provides nm-applet tray icon."),
+        "NetworkManager-dispatcher" => Some("Runs scripts in response to network events (connect/disconnect). Scripts live in /etc/NetworkManager/dispatcher.d/."),
+        "NetworkManager-wait-online" => Some("Blocks boot until network is fully connected. Needed by services requiring network at startup. Can slow boot if network is slow."),
+        "systemd-networkd" => Some("Systemd's built-in network manager. Lighter alternative to NetworkManager, configured via .network files in /etc/systemd/network/."),
+        "systemd-resolved" => Some("Systemd DNS resolver. Provides DNS caching, DNSSEC validation, and DNS-over-TLS. Manages /etc/resolv.conf."),
+        "wpa_supplicant" => Some("WiFi authentication daemon (WPA/WPA2/WPA3). Usually managed by NetworkManager, but can run standalone for simpler setups."),
+        "iwd" => Some("Intel Wireless Daemon. Modern alternative to wpa_supplicant with simpler config. Can be used as NetworkManager's WiFi backend."),
+
+        // Audio
+        "pipewire" => Some("Modern audio/video server replacing PulseAudio and JACK. Handles screen sharing, Bluetooth audio, and low-latency audio."),
+        "wireplumber" => Some("Session manager for PipeWire. Handles audio routing policy, device management, and Bluetooth audio profiles."),
+        "pulseaudio" => Some("Legacy audio server. Being replaced by PipeWire on most modern Linux desktops."),
+
+        // Bluetooth
+        "bluetooth" => Some("BlueZ Bluetooth daemon. Manages Bluetooth device pairing, connections, and profiles (A2DP, HFP, etc.)."),
+        "blueman-mechanism" => Some("Blueman privilege helper. Allows the Blueman Bluetooth manager applet to perform system-level Bluetooth operations."),
+
+        // Printing
+        "cups" => Some("Common Unix Printing System. Manages print queues, printer discovery (via Avahi/mDNS), and IPP printing. Web UI at localhost:631."),
+        "avahi-daemon" => Some("mDNS/DNS-SD daemon for zero-configuration networking. Enables .local hostname resolution and network service discovery (printers, etc.)."),
+        "avahi-dnsconfd" => Some("Configures DNS servers discovered via Avahi. Rarely needed if using NetworkManager or systemd-resolved."),
+
+        // Security / Firewall
+        "sshd" => Some("OpenSSH server daemon. Accepts incoming SSH connections for remote shell access, file transfer (scp/sftp), and tunneling."),
+        "ufw" => Some("Uncomplicated Firewall. User-friendly frontend for iptables/nftables. Manages incoming/outgoing traffic rules."),
+        "firewalld" => Some("Dynamic firewall daemon with zones. Uses nftables backend. Supports runtime changes without restarting."),
+        "nftables" => Some("Netfilter tables. Modern kernel packet filtering framework replacing iptables. Rules in /etc/nftables.conf."),
+        "apparmor" => Some("Mandatory Access Control security framework. Confines programs to limited resources using per-program profiles."),
+        "auditd" => Some("Linux Audit daemon. Logs security-relevant events (file access, syscalls, authentication) per configured rules."),
+        "fail2ban" => Some("Intrusion prevention. Monitors log files and bans IPs showing malicious signs (brute-force SSH, etc.) via firewall rules."),
+
+        // Power / Hardware
+        "upower" => Some("Power management abstraction. Provides battery info, suspend/hibernate support. Used by desktop environments for power status."),
+        "power-profiles-daemon" => Some("Provides power profile switching (balanced, power-saver, performance). Used by GNOME/KDE power settings."),
+        "cpupower" => Some("CPU frequency scaling. Sets CPU governor (performance/powersave/schedutil) at boot."),
+        "lm_sensors" => Some("Hardware monitoring. Reads CPU/GPU temperatures, fan speeds, and voltages from sensor chips."),
+        "smartd" => Some("S.M.A.R.T. disk monitoring daemon. Watches hard drive health indicators and warns of impending failures."),
+        "fancontrol" => Some("Fan speed control daemon. Uses lm_sensors data to dynamically adjust fan speeds based on temperature."),
+
+        // Containers
+        "docker" => Some("Docker container runtime. Manages container images, networks, and volumes. API on /var/run/docker.sock."),
+        "podman" => Some("Daemonless container engine. Docker-compatible CLI but runs rootless by default. No persistent daemon needed."),
+        "containerd" => Some("Container runtime daemon. Low-level container execution used by Docker and Kubernetes."),
+
+        // Databases / Web servers
+        "postgresql" => Some("PostgreSQL database server. Listens on port 5432. The data directory must be initialized with initdb before first start."),
+        "mariadb" | "mysqld" | "mysql" => Some("MariaDB/MySQL database server. Listens on port 3306. Secure a fresh install with mariadb-secure-installation."),
+        "redis" | "redis-server" | "valkey" => Some("In-memory key-value store used for caching and queues. Listens on port 6379. Config in /etc/redis/redis.conf."),
+        "nginx" => Some("Nginx web server and reverse proxy. Config in /etc/nginx/nginx.conf. Test config with `nginx -t` before restarting."),
+        "caddy" => Some("Caddy web server. Automatic HTTPS via Let's Encrypt. Configured via /etc/caddy/Caddyfile."),
+        "httpd" | "apache2" => Some("Apache HTTP Server. Serves websites on ports 80/443. Test config with `apachectl configtest` before restarting."),
+
+        // Virtualization
+        "libvirtd" => Some("Libvirt management daemon (monolithic). Manages QEMU/KVM virtual machines for virt-manager and virsh. Newer setups use the modular virt*d daemons instead."),
+        "virtqemud" => Some("Libvirt QEMU driver daemon (modular). Manages QEMU/KVM virtual machines. Replaces libvirtd on modular libvirt setups."),
+        "virtlogd" => Some("Libvirt log daemon. Captures VM console output so logs survive libvirt daemon restarts. Needed while VMs are running."),
+        "virtlockd" => Some("Libvirt lock daemon. Prevents two VMs from writing the same disk image at once."),
+        "virtnetworkd" => Some("Libvirt virtual network daemon. Provides NAT/bridged networks (e.g., the 'default' virbr0 network) for VMs."),
+        "virtstoraged" => Some("Libvirt storage daemon. Manages storage pools and volumes for VM disk images."),
+        "vboxdrv" => Some("VirtualBox kernel modules loader. Builds/loads vboxdrv, vboxnetflt, and vboxnetadp. Required to run VirtualBox VMs on this host."),
+        "vboxservice" => Some("VirtualBox Guest Additions service. Only useful inside a VirtualBox guest — provides time sync, shared folders, and resizing."),
+        "qemu-guest-agent" => Some("QEMU guest agent. Only useful inside a VM — lets the host request clean shutdown, freeze filesystems for snapshots, and query IPs."),
+        "spice-vdagentd" => Some("SPICE guest agent daemon. Only useful inside a VM — enables clipboard sharing and automatic display resizing with virt-viewer/virt-manager."),
+
+        // Systemd core
+        "systemd-timesyncd" => Some("Simple NTP client. Synchronizes system clock with network time servers. Lighter alternative to chrony/ntpd."),
+        "systemd-oomd" => Some("Out-of-memory daemon. Monitors memory pressure and kills cgroup trees before the kernel OOM killer triggers."),
+        "systemd-homed" => Some("Portable home directory manager. Stores home dirs as LUKS-encrypted images that can move between machines."),
+        "systemd-boot-update" => Some("Automatically updates systemd-boot EFI bootloader when systemd is upgraded."),
+        "systemd-pstore" => Some("Persistent storage for kernel crash dumps. Copies pstore data (dmesg, etc.) from /sys/fs/pstore to /var/lib/systemd/pstore."),
+
+        // Misc system services
+        "accounts-daemon" => Some("D-Bus service for user account management. Used by GDM and GNOME Settings for user info, avatar, and language preferences."),
+        "rtkit-daemon" => Some("RealtimeKit. Safely grants realtime scheduling priority to user processes (PipeWire, audio apps) without running them as root."),
+        "udisks2" => Some("Disk management daemon. Provides D-Bus API for mounting/unmounting drives, used by file managers for removable media."),
+        "ModemManager" => Some("Mobile broadband modem management. Controls 3G/4G/5G modems and provides connection setup. Safe to disable without mobile broadband."),
+        "haveged" => Some("Entropy harvesting daemon. Feeds additional randomness to /dev/random. Less needed on modern kernels with good entropy sources."),
+        "gpm" => Some("General Purpose Mouse. Provides mouse support in Linux virtual consoles (TTY). Not needed in graphical environments."),
+
+        // Backup / Sync
+        "borgmatic" => Some("Borg backup runner. Creates, prunes, and checks Borg archives per /etc/borgmatic/config.yaml. Normally triggered by borgmatic.timer."),
+        "restic" | "restic-backup" => Some("Restic backup job. Snapshots configured paths to a local or remote repository. Usually paired with a timer rather than running continuously."),
+        "syncthing" => Some("Syncthing continuous file synchronization. Peer-to-peer sync between devices without a cloud service. Web UI at localhost:8384."),
+        "syncthing-resume" => Some("Restarts Syncthing after resume from suspend so it reconnects to peers promptly."),
+        "rclone" | "rclone-mount" => Some("Rclone mount. Exposes a cloud storage remote (Google Drive, S3, etc.) as a local filesystem via FUSE."),
+        "timeshift" | "timeshift-autosnap" => Some("Timeshift system snapshots. Takes rsync or Btrfs snapshots of the system so you can roll back after a bad update."),
+
+        // Desktop portals / session helpers (user units)
+        "xdg-desktop-portal" => Some("Desktop portal frontend. Brokers file pickers, screen sharing, and screenshots for Flatpak and Wayland apps. Loads a DE-specific backend."),
+        "xdg-desktop-portal-gtk" => Some("GTK backend for desktop portals. Provides file chooser and settings dialogs for non-GNOME desktops and as a fallback."),
+        "xdg-desktop-portal-gnome" => Some("GNOME backend for desktop portals. Handles screen sharing and screenshots under GNOME Shell."),
+        "xdg-desktop-portal-kde" => Some("KDE backend for desktop portals. Provides Qt file dialogs and screen sharing under Plasma."),
+        "xdg-desktop-portal-hyprland" | "xdg-desktop-portal-wlr" => Some("wlroots/Hyprland portal backend. Provides screen sharing (via PipeWire) and screenshots on wlroots-based compositors."),
+        "xdg-document-portal" => Some("Document portal. Exposes user-chosen files to sandboxed apps through a FUSE mount at /run/user/UID/doc."),
+        "xdg-permission-store" => Some("Stores permissions granted to sandboxed apps through portals (e.g., 'allow screen sharing')."),
+        "flatpak-portal" => Some("Flatpak portal. Lets sandboxed Flatpak apps spawn helper processes and request updates of themselves."),
+        "flatpak-session-helper" => Some("Flatpak session helper. Gives sandboxed apps a view of host fonts, timezone, and /etc files like resolv.conf."),
+        "flatpak-system-helper" => Some("Flatpak system helper. Performs system-wide installs and updates on behalf of unprivileged users, authorized via polkit."),
+
+        // Snap packages
+        "snapd" => Some("Snap daemon. Installs, refreshes, and confines snap packages; each snap's own services show up as snap.<name>.*.service units."),
+        "snapd.apparmor" => Some("Loads AppArmor profiles for installed snaps at boot. Disabling leaves snaps unconfined or unable to start."),
+        "snapd.seeded" => Some("Waits for snapd's first-boot seeding to finish so snaps preinstalled with the system are ready."),
+        "xdg-user-dirs-update" => Some("Creates and localizes standard user folders (Desktop, Downloads, Music...) at login."),
+        "gvfs-daemon" => Some("GNOME virtual filesystem daemon. Provides trash, network shares (smb://, sftp://), and MTP access to file managers."),
+        "gvfs-metadata" => Some("GVfs metadata store. Remembers per-file metadata like emblems and icon positions for file managers."),
+        "gvfs-udisks2-volume-monitor" => Some("GVfs volume monitor for local drives. Shows USB sticks and partitions in file manager sidebars via UDisks2."),
+        "gvfs-mtp-volume-monitor" => Some("GVfs volume monitor for MTP devices. Makes Android phones and media players show up in file managers."),
+        "gvfs-gphoto2-volume-monitor" => Some("GVfs volume monitor for cameras (PTP/gphoto2). Safe to disable if you never import photos over USB."),
+        "gvfs-afc-volume-monitor" => Some("GVfs volume monitor for Apple devices (AFC). Safe to disable if you never connect an iPhone or iPad."),
+        "gvfs-goa-volume-monitor" => Some("GVfs volume monitor for GNOME Online Accounts. Exposes cloud accounts such as Google Drive in file managers."),
+        "gnome-keyring-daemon" => Some("GNOME Keyring. Stores passwords, secrets (Secret Service API), and optionally SSH keys, unlocked at login."),
+        "polkit-gnome-authentication-agent-1" | "lxpolkit" | "hyprpolkitagent" => Some("Polkit authentication agent. Shows the password dialog when an app (or pkexec) asks for admin rights. Needed on minimal desktops."),
+        "at-spi-dbus-bus" => Some("Accessibility bus (AT-SPI). Lets screen readers and assistive tools talk to applications. Some apps log warnings without it."),
+
+        // Session
+        "seatd" => Some("Minimal seat management daemon. Provides unprivileged access to input/display devices for Wayland compositors (Sway, etc.)."),
+
+        // VPN / Networking extras
+        "openvpn-client" | "openvpn-server" => Some("OpenVPN tunnel. Template unit — instantiate with config name (e.g., openvpn-client@myconfig)."),
+        "openvpn" => Some("OpenVPN tunnel (legacy unit name). Reads its config from /etc/openvpn/. Prefer the openvpn-client@/openvpn-server@ templates."),
+        "wg-quick" => Some("WireGuard tunnel via wg-quick. Template unit — instantiate with interface name (e.g., wg-quick@wg0) to bring up /etc/wireguard/wg0.conf at boot."),
+        "tailscaled" => Some("Tailscale daemon. WireGuard-based mesh VPN that connects your devices through a coordination server. Needed for `tailscale up`."),
+        "zerotier-one" => Some("ZeroTier daemon. Peer-to-peer virtual Ethernet network joining devices across the internet. Needed for `zerotier-cli join`."),
+        "headscale" => Some("Self-hosted Tailscale coordination server. Only needed on the machine acting as your own control server, not on clients."),
+        "dnsmasq" => Some("Lightweight DNS forwarder and DHCP server. Often used for local DNS caching, network boot (PXE), or VM networking."),
+        "nextdns" => Some("NextDNS CLI client. Routes DNS queries through NextDNS for ad-blocking, tracking protection, and security filtering."),
+
+        _ => None,
+    }
+}
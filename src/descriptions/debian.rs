@@ -0,0 +1,29 @@
+pub fn lookup(base: &str) -> Option<&'static str> {
+    match base {
+        // Network
+        "networking" => Some("Debian ifupdown networking. Brings up interfaces listed in /etc/network/interfaces at boot. Not needed if NetworkManager or netplan manages everything."),
+        "systemd-networkd-wait-online" => Some("Blocks boot until systemd-networkd reports the network online. On Ubuntu servers this is what netplan uses; can add minutes to boot with unplugged ports."),
+        "ufw" => Some("Uncomplicated Firewall (Ubuntu default). Frontend for nftables/iptables. Rules are only enforced after `ufw enable`."),
+
+        // Package management
+        "unattended-upgrades" => Some("Automatic security updates. Installs upgrades from configured origins in /etc/apt/apt.conf.d/50unattended-upgrades, then optionally reboots."),
+        "apt-daily" => Some("Daily apt package list refresh (apt update). Triggered by apt-daily.timer; can hold the dpkg lock shortly after boot."),
+        "apt-daily-upgrade" => Some("Daily apt upgrade run used by unattended-upgrades. Triggered by apt-daily-upgrade.timer."),
+        "packagekit" => Some("PackageKit daemon. Backend for GNOME Software / Discover; runs apt on demand and may refresh package lists in the background."),
+        "snapd" => Some("Snap package daemon. Installs, updates, and runs snap packages. Disabling it breaks all installed snaps."),
+
+        // Servers (Debian paths)
+        "postgresql" => Some("PostgreSQL umbrella unit. Starts every cluster managed by postgresql-common (see `pg_lsclusters`). Data lives in /var/lib/postgresql."),
+        "apache2" => Some("Apache HTTP Server. Config in /etc/apache2; enable sites and modules with a2ensite/a2enmod. Listens on ports 80/443."),
+        "mariadb" | "mysql" => Some("MariaDB/MySQL database server. Initialized automatically by the Debian package. Listens on port 3306."),
+
+        // Misc
+        "console-setup" => Some("Applies the console font and keymap from /etc/default/console-setup to virtual terminals at boot."),
+        "keyboard-setup" => Some("Loads the keyboard layout from /etc/default/keyboard for the console early in boot."),
+        "apport" => Some("Ubuntu crash reporter. Collects crash dumps and offers to send reports to Launchpad. Safe to disable on non-development machines."),
+        "whoopsie" => Some("Ubuntu error-report submission daemon. Uploads crash reports collected by apport to errors.ubuntu.com."),
+        "cloud-init" => Some("Cloud instance initializer. Configures hostname, users, and SSH keys from cloud metadata on first boot. Safe to disable on bare metal."),
+
+        _ => None,
+    }
+}
@@ -0,0 +1,21 @@
+pub fn lookup(base: &str) -> Option<&'static str> {
+    match base {
+        // Package management
+        "dnf-makecache" => Some("Refreshes DNF repository metadata in the background. Triggered by dnf-makecache.timer; safe to disable if you update manually."),
+        "dnf-automatic" => Some("Automatic DNF updates. Downloads and optionally installs updates per /etc/dnf/automatic.conf. Usually run via dnf-automatic.timer."),
+        "packagekit" => Some("PackageKit daemon. Backend for GNOME Software; runs DNF on demand and may download updates in the background."),
+        "rpm-ostreed" => Some("rpm-ostree daemon (Silverblue/Kinoite). Manages image-based OS updates and layered packages."),
+
+        // Servers (Fedora paths)
+        "postgresql" => Some("PostgreSQL database server. Run `postgresql-setup --initdb` once before first start. Data lives in /var/lib/pgsql/data."),
+        "httpd" => Some("Apache HTTP Server. Config in /etc/httpd/conf/httpd.conf and conf.d/. SELinux may block non-default ports or paths."),
+
+        // Misc
+        "abrtd" => Some("Automatic Bug Reporting Tool. Collects crash data and offers to file reports. Safe to disable if you don't report bugs."),
+        "sssd" => Some("System Security Services Daemon. Provides LDAP, Kerberos, and Active Directory logins. Not needed for local-only accounts."),
+        "mcelog" => Some("Machine Check Exception logger. Records CPU and memory hardware errors reported by the kernel."),
+        "low-memory-monitor" => Some("Low memory monitor. Notifies apps over D-Bus when the system is under memory pressure so they can free caches."),
+
+        _ => None,
+    }
+}
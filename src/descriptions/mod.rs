@@ -0,0 +1,119 @@
+mod arch;
+mod common;
+mod debian;
+mod fedora;
+pub mod online;
+
+use std::sync::OnceLock;
+
+const OS_RELEASE: &str = "/etc/os-release";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Distro {
+    Arch,
+    Debian,
+    Fedora,
+    Unknown,
+}
+
+/// Human-written explanation for a service, preferring the pack for the
+/// running distro and falling back to the distro-independent pack.
+pub fn curated_description(service: &str) -> Option<&'static str> {
+    lookup(current_distro(), service)
+}
+
+fn base_name(service: &str) -> &str {
+    let name = service.trim_end_matches(".service");
+    // Strip template suffix for matching (e.g., "ly@" -> "ly")
+    name.split('@').next().unwrap_or(name)
+}
+
+fn lookup(distro: Distro, service: &str) -> Option<&'static str> {
+    let base = base_name(service);
+
+    let distro_pack = match distro {
+        Distro::Arch => arch::lookup(base),
+        Distro::Debian => debian::lookup(base),
+        Distro::Fedora => fedora::lookup(base),
+        Distro::Unknown => None,
+    };
+
+    distro_pack.or_else(|| common::lookup(base))
+}
+
+pub fn current_distro() -> Distro {
+    static DISTRO: OnceLock<Distro> = OnceLock::new();
+    *DISTRO.get_or_init(|| {
+        std::fs::read_to_string(OS_RELEASE)
+            .map(|contents| parse_os_release(&contents))
+            .unwrap_or(Distro::Unknown)
+    })
+}
+
+/// Map `ID` / `ID_LIKE` from os-release to a description pack.
+/// Derivatives (CachyOS, Manjaro, Ubuntu, Mint) declare their parent in `ID_LIKE`.
+fn parse_os_release(contents: &str) -> Distro {
+    let mut ids: Vec<String> = Vec::new();
+
+    for line in contents.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            if key == "ID" || key == "ID_LIKE" {
+                let value = value.trim().trim_matches('"').trim_matches('\'');
+                ids.extend(value.split_whitespace().map(|s| s.to_lowercase()));
+            }
+        }
+    }
+
+    for id in &ids {
+        match id.as_str() {
+            "arch" | "cachyos" | "manjaro" | "endeavouros" => return Distro::Arch,
+            "debian" | "ubuntu" => return Distro::Debian,
+            "fedora" | "rhel" | "centos" => return Distro::Fedora,
+            _ => {}
+        }
+    }
+
+    Distro::Unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_os_release_arch_derivative() {
+        let cachyos = "NAME=\"CachyOS Linux\"\nID=cachyos\nID_LIKE=arch\n";
+        assert_eq!(parse_os_release(cachyos), Distro::Arch);
+    }
+
+    #[test]
+    fn test_parse_os_release_debian_family() {
+        let ubuntu = "NAME=\"Ubuntu\"\nID=ubuntu\nID_LIKE=debian\n";
+        assert_eq!(parse_os_release(ubuntu), Distro::Debian);
+        let mint = "ID=linuxmint\nID_LIKE=\"ubuntu debian\"\n";
+        assert_eq!(parse_os_release(mint), Distro::Debian);
+    }
+
+    #[test]
+    fn test_parse_os_release_fedora_and_unknown() {
+        assert_eq!(parse_os_release("ID=fedora\n"), Distro::Fedora);
+        assert_eq!(parse_os_release("ID=nixos\n"), Distro::Unknown);
+    }
+
+    #[test]
+    fn test_lookup_prefers_distro_pack() {
+        let debian = lookup(Distro::Debian, "networking.service");
+        assert!(debian.is_some());
+        assert_eq!(lookup(Distro::Arch, "networking.service"), None);
+        assert_ne!(
+            lookup(Distro::Fedora, "postgresql.service"),
+            lookup(Distro::Unknown, "postgresql.service")
+        );
+    }
+
+    #[test]
+    fn test_lookup_falls_back_to_common() {
+        assert!(lookup(Distro::Debian, "sshd.service").is_some());
+        assert!(lookup(Distro::Unknown, "wg-quick@wg0.service").is_some());
+    }
+}
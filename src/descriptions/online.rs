@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Context, Result};
+use tokio::process::Command as AsyncCommand;
+use tokio::time::timeout;
+
+use crate::config::cache_dir;
+use crate::trace;
+
+const CACHE_FILE: &str = "descriptions.json";
+const CACHE_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+fn cache_path() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join(CACHE_FILE))
+}
+
+/// Look up a unit in the locally cached community database.
+/// Never touches the network; `refresh` keeps the cache current.
+pub fn cached_description(service: &str) -> Option<String> {
+    let contents = std::fs::read_to_string(cache_path()?).ok()?;
+    let mut db = parse_database(&contents).ok()?;
+    db.remove(super::base_name(service))
+}
+
+pub fn cache_is_stale() -> bool {
+    let modified = cache_path()
+        .and_then(|path| std::fs::metadata(path).ok())
+        .and_then(|meta| meta.modified().ok());
+
+    match modified {
+        Some(time) => SystemTime::now()
+            .duration_since(time)
+            .map(|age| age > CACHE_MAX_AGE)
+            .unwrap_or(false),
+        None => true,
+    }
+}
+
+/// Download the database with curl and replace the cache if it parses.
+/// Returns a one-line summary of what changed.
+pub async fn refresh(url: &str) -> Result<String> {
+    let mut cmd = AsyncCommand::new("curl");
+    cmd.args(["--fail", "--silent", "--show-error", "--location", url]);
+
+    let output = match timeout(FETCH_TIMEOUT, trace::output_async(&mut cmd)).await {
+        Ok(result) => result.context("Failed to run curl")?,
+        Err(_) => bail!("timed out fetching {url}"),
+    };
+    if !output.status.success() {
+        bail!(
+            "curl failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let body = String::from_utf8_lossy(&output.stdout);
+    let fresh = parse_database(&body).context("Downloaded description database is not valid")?;
+
+    let path = cache_path().context("No cache directory (HOME unset)")?;
+    let current = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|contents| parse_database(&contents).ok())
+        .unwrap_or_default();
+    let summary = update_summary(&current, &fresh);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // Write then rename so a reader never sees a half-written file
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, body.as_bytes())?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(summary)
+}
+
+fn update_summary(current: &HashMap<String, String>, fresh: &HashMap<String, String>) -> String {
+    let added = fresh.keys().filter(|k| !current.contains_key(*k)).count();
+    let updated = fresh
+        .iter()
+        .filter(|(k, v)| current.get(*k).is_some_and(|old| old != *v))
+        .count();
+
+    if added == 0 && updated == 0 {
+        format!("Descriptions up to date ({} entries)", fresh.len())
+    } else {
+        format!(
+            "Installed {} descriptions: {added} new, {updated} updated",
+            fresh.len()
+        )
+    }
+}
+
+/// The database is a flat JSON object: `{ "unit-name": "description", ... }`,
+/// keyed by unit name without `.service` or template suffix.
+fn parse_database(contents: &str) -> Result<HashMap<String, String>> {
+    Ok(serde_json::from_str(contents)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_database_flat_object() {
+        let db = parse_database(r#"{"foo": "Foo daemon.", "bar": "Bar."}"#).unwrap();
+        assert_eq!(db.get("foo").map(String::as_str), Some("Foo daemon."));
+        assert_eq!(db.len(), 2);
+    }
+
+    #[test]
+    fn test_update_summary() {
+        let old = parse_database(r#"{"a": "A.", "b": "B."}"#).unwrap();
+        let new = parse_database(r#"{"a": "A.", "b": "Better B.", "c": "C."}"#).unwrap();
+        assert_eq!(
+            update_summary(&old, &new),
+            "Installed 3 descriptions: 1 new, 1 updated"
+        );
+        assert_eq!(
+            update_summary(&new, &new),
+            "Descriptions up to date (3 entries)"
+        );
+    }
+
+    #[test]
+    fn test_parse_database_rejects_nested_values() {
+        assert!(parse_database(r#"{"foo": {"description": "x"}}"#).is_err());
+        assert!(parse_database("not json").is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::compare::{diff_states, ServiceDiff};
+use crate::config::state_dir;
+use crate::systemd::{Service, ServiceScope};
+
+/// Unit-file states as last seen by comma-services, one file per scope.
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    taken_at: u64, // unix seconds
+    enabled: HashMap<String, bool>,
+}
+
+/// Changes made outside comma-services since the previous snapshot.
+#[derive(Debug, Default)]
+pub struct DriftReport {
+    pub since: Option<u64>,
+    pub changes: Vec<ServiceDiff>, // `local` = last snapshot, `remote` = now
+}
+
+fn snapshot_path(scope: &ServiceScope) -> Option<PathBuf> {
+    let file = match scope {
+        ServiceScope::System => "snapshot-system.json",
+        ServiceScope::User => "snapshot-user.json",
+        ServiceScope::Global => "snapshot-global.json",
+    };
+    state_dir().map(|dir| dir.join(file))
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Compare `services` against the stored snapshot, then replace it.
+pub fn check_and_update(scope: &ServiceScope, services: &[Service]) -> Result<DriftReport> {
+    let path = snapshot_path(scope).context("No state directory (HOME unset)")?;
+    let current: HashMap<String, bool> = services
+        .iter()
+        .map(|svc| (svc.name.clone(), svc.enabled))
+        .collect();
+
+    let previous: Option<Snapshot> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok());
+    let report = match previous {
+        Some(snapshot) => DriftReport {
+            since: Some(snapshot.taken_at),
+            changes: diff_states(&snapshot.enabled, &current),
+        },
+        None => DriftReport::default(),
+    };
+
+    save(&path, current)?;
+    Ok(report)
+}
+
+/// Record the current state without reporting (after our own applies).
+pub fn update(scope: &ServiceScope, services: &[Service]) -> Result<()> {
+    let path = snapshot_path(scope).context("No state directory (HOME unset)")?;
+    let current = services
+        .iter()
+        .map(|svc| (svc.name.clone(), svc.enabled))
+        .collect();
+    save(&path, current)
+}
+
+fn save(path: &PathBuf, enabled: HashMap<String, bool>) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let snapshot = Snapshot {
+        taken_at: now_secs(),
+        enabled,
+    };
+    std::fs::write(path, serde_json::to_string(&snapshot)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Format unix seconds as a UTC calendar date (YYYY-MM-DD).
+pub fn format_date(secs: u64) -> String {
+    // Days-to-civil conversion from Howard Hinnant's date algorithms
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_770_508_800), "2026-02-08");
+    }
+}
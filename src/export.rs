@@ -0,0 +1,242 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+
+use crate::categories::categorize;
+use crate::config::state_dir;
+use crate::systemd::{list_services, machine, requirements, Requirements, Service, ServiceScope};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Dot,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "json" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            "dot" | "gv" => Ok(Format::Dot),
+            _ => bail!("Unknown export format {name:?} (json, yaml or dot)"),
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+            Format::Dot => "dot",
+        }
+    }
+}
+
+/// One service as exported.
+#[derive(Debug, Serialize)]
+pub struct Row {
+    pub name: String,
+    pub scope: &'static str,
+    pub category: &'static str,
+    pub enabled: bool,
+    pub active: bool,
+    pub masked: bool,
+}
+
+fn row(scope: &ServiceScope, svc: &Service) -> Row {
+    Row {
+        name: svc.name.clone(),
+        scope: scope.name(),
+        category: categorize(&svc.name),
+        enabled: svc.enabled,
+        active: svc.active,
+        masked: svc.masked,
+    }
+}
+
+/// Every service of every scope, as systemd reports them now. A
+/// container only has its system manager.
+pub fn collect() -> Result<Vec<Row>> {
+    let scopes: &[ServiceScope] = if machine().is_some() {
+        &[ServiceScope::System]
+    } else {
+        &[
+            ServiceScope::System,
+            ServiceScope::User,
+            ServiceScope::Global,
+        ]
+    };
+    let mut rows = Vec::new();
+    for scope in scopes {
+        rows.extend(list_services(scope)?.iter().map(|svc| row(scope, svc)));
+    }
+    Ok(rows)
+}
+
+pub fn render(format: Format, rows: &[Row]) -> Result<String> {
+    match format {
+        Format::Json => Ok(serde_json::to_string_pretty(rows)? + "\n"),
+        Format::Yaml => Ok(yaml(rows)),
+        Format::Dot => Ok(dot(&graph(rows))),
+    }
+}
+
+/// What the enabled system and user services among `rows` require,
+/// want and conflict with, asked of each manager. Global services have no
+/// manager to ask.
+fn graph(rows: &[Row]) -> Vec<(&'static str, Vec<Requirements>)> {
+    [ServiceScope::System, ServiceScope::User]
+        .iter()
+        .map(|scope| {
+            let enabled: Vec<String> = rows
+                .iter()
+                .filter(|row| row.enabled && row.scope == scope.name())
+                .map(|row| row.name.clone())
+                .collect();
+            (scope.name(), requirements(scope, &enabled))
+        })
+        .filter(|(_, units)| !units.is_empty())
+        .collect()
+}
+
+/// Graphviz in the colors of `systemd-analyze dot`: black for requires,
+/// grey for wants, red for conflicts. Each scope is a cluster; enabled
+/// services are filled in, the units they point at aren't.
+fn dot(graph: &[(&str, Vec<Requirements>)]) -> String {
+    let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut out = String::from("digraph units {\n    rankdir=LR;\n    node [shape=box];\n");
+    for (scope, units) in graph {
+        let id = |unit: &str| quote(&format!("{scope}/{unit}"));
+        out.push_str(&format!(
+            "    subgraph {} {{\n        label={};\n",
+            quote(&format!("cluster_{scope}")),
+            quote(scope)
+        ));
+        let mut others = BTreeSet::new();
+        for unit in units {
+            out.push_str(&format!(
+                "        {} [label={}, style=filled, fillcolor=lightyellow];\n",
+                id(&unit.unit),
+                quote(&unit.unit)
+            ));
+            let edges = [
+                (&unit.requires, "black"),
+                (&unit.wants, "grey66"),
+                (&unit.conflicts, "red"),
+            ];
+            for (targets, color) in edges {
+                for target in targets {
+                    others.insert(target.as_str());
+                    out.push_str(&format!(
+                        "        {} -> {} [color={color}];\n",
+                        id(&unit.unit),
+                        id(target)
+                    ));
+                }
+            }
+        }
+        for unit in units {
+            others.remove(unit.unit.as_str());
+        }
+        for other in others {
+            out.push_str(&format!(
+                "        {} [label={}];\n",
+                id(other),
+                quote(other)
+            ));
+        }
+        out.push_str("    }\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// A YAML sequence of mappings. Names are written as double-quoted
+/// scalars, whose escapes are JSON's, so odd unit names stay valid.
+fn yaml(rows: &[Row]) -> String {
+    let mut out = String::new();
+    for row in rows {
+        let name = serde_json::to_string(&row.name).unwrap_or_default();
+        out.push_str(&format!(
+            "- name: {name}\n  scope: {}\n  category: \"{}\"\n  enabled: {}\n  active: {}\n  masked: {}\n",
+            row.scope, row.category, row.enabled, row.active, row.masked
+        ));
+    }
+    if rows.is_empty() {
+        out.push_str("[]\n");
+    }
+    out
+}
+
+/// Write an export next to the other state files; from the TUI
+/// stdout isn't an option.
+pub fn write(format: Format, rows: &[Row]) -> Result<PathBuf> {
+    let dir = state_dir().context("No state directory (HOME unset)")?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = dir.join(format!("export-{stamp}.{}", format.extension()));
+    std::fs::write(&path, render(format, rows)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<Row> {
+        vec![Row {
+            name: "getty@tty1.service".into(),
+            scope: "system",
+            category: "Systemd Core",
+            enabled: true,
+            active: false,
+            masked: false,
+        }]
+    }
+
+    #[test]
+    fn test_yaml_rows() {
+        assert_eq!(
+            yaml(&rows()),
+            "- name: \"getty@tty1.service\"\n  scope: system\n  category: \"Systemd Core\"\n  enabled: true\n  active: false\n  masked: false\n"
+        );
+        assert_eq!(yaml(&[]), "[]\n");
+    }
+
+    #[test]
+    fn test_dot_graph() {
+        let cups = Requirements {
+            unit: "cups.service".into(),
+            requires: vec!["cups.socket".into()],
+            wants: vec!["network.target".into()],
+            conflicts: Vec::new(),
+        };
+        let dot = dot(&[("system", vec![cups])]);
+        assert!(dot.starts_with("digraph units {\n"));
+        assert!(dot.contains("    subgraph \"cluster_system\" {\n        label=\"system\";\n"));
+        assert!(dot.contains(
+            "\"system/cups.service\" [label=\"cups.service\", style=filled, fillcolor=lightyellow];"
+        ));
+        assert!(dot.contains("\"system/cups.service\" -> \"system/cups.socket\" [color=black];"));
+        assert!(
+            dot.contains("\"system/cups.service\" -> \"system/network.target\" [color=grey66];")
+        );
+        assert!(dot.contains("\"system/network.target\" [label=\"network.target\"];"));
+        assert!(dot.ends_with("    }\n}\n"));
+    }
+
+    #[test]
+    fn test_json_fields() {
+        let json = render(Format::Json, &rows()).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed[0]["scope"], "system");
+        assert_eq!(parsed[0]["enabled"], true);
+        assert!(Format::parse("toml").is_err());
+    }
+}
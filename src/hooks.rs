@@ -0,0 +1,133 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use crate::descriptions::Distro;
+use crate::systemd::write_root_file;
+use crate::trace;
+
+/// Package-manager hook that restarts a service after its package updates.
+#[derive(Debug, Clone)]
+pub struct Hook {
+    pub package: String,
+    pub path: PathBuf,
+    pub body: String,
+}
+
+/// Build the hook for `service`, whose unit file is `fragment`.
+pub fn suggest(distro: Distro, service: &str, fragment: &str) -> Result<Hook> {
+    if fragment.is_empty() {
+        bail!("{service} has no unit file");
+    }
+    let package = owning_package(distro, fragment)?;
+    let stem = service.trim_end_matches(".service");
+    let (path, body) = match distro {
+        Distro::Arch => (
+            PathBuf::from(format!("/etc/pacman.d/hooks/restart-{stem}.hook")),
+            pacman_hook(&package, service),
+        ),
+        Distro::Debian => (
+            PathBuf::from(format!("/etc/apt/apt.conf.d/80restart-{stem}")),
+            apt_hook(&package, service),
+        ),
+        _ => bail!("Restart hooks are only generated for pacman and apt"),
+    };
+    Ok(Hook {
+        package,
+        path,
+        body,
+    })
+}
+
+fn owning_package(distro: Distro, fragment: &str) -> Result<String> {
+    let mut cmd = match distro {
+        Distro::Arch => {
+            let mut cmd = Command::new("pacman");
+            cmd.args(["-Qoq", "--", fragment]);
+            cmd
+        }
+        Distro::Debian => {
+            let mut cmd = Command::new("dpkg-query");
+            cmd.args(["-S", "--", fragment]);
+            cmd
+        }
+        _ => bail!("Restart hooks are only generated for pacman and apt"),
+    };
+    let output = trace::output(&mut cmd).context("Failed to query the package database")?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match parse_owner(&stdout) {
+        Some(package) if output.status.success() => Ok(package),
+        _ => bail!("{fragment} doesn't belong to any package"),
+    }
+}
+
+/// `pacman -Qoq` prints the bare name; `dpkg-query -S` prints
+/// `package[:arch]: /path`.
+fn parse_owner(stdout: &str) -> Option<String> {
+    let line = stdout.lines().next()?.trim();
+    let name = line.split(": ").next()?;
+    let name = name.split(':').next()?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn pacman_hook(package: &str, service: &str) -> String {
+    format!(
+        "# Written by comma-services
+[Trigger]
+Operation = Upgrade
+Type = Package
+Target = {package}
+
+[Action]
+Description = Restarting {service} after {package} upgrade...
+When = PostTransaction
+Exec = /usr/bin/systemctl try-restart {service}
+"
+    )
+}
+
+fn apt_hook(package: &str, service: &str) -> String {
+    format!(
+        "// Written by comma-services. apt has no per-package hooks, so this
+// runs after every dpkg run; try-restart leaves a stopped {service} alone.
+// Package: {package}
+DPkg::Post-Invoke {{ \"systemctl try-restart '{service}' || true\"; }};
+"
+    )
+}
+
+/// Copy the hook into /etc as root, refusing to overwrite.
+pub fn install(hook: &Hook) -> Result<()> {
+    if hook.path.exists() {
+        bail!("{} already exists", hook.path.display());
+    }
+    write_root_file(&hook.path, &hook.body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_owner() {
+        assert_eq!(parse_owner("cups\n").as_deref(), Some("cups"));
+        assert_eq!(
+            parse_owner("openssh-server: /lib/systemd/system/ssh.service\n").as_deref(),
+            Some("openssh-server")
+        );
+        assert_eq!(
+            parse_owner("libvirt-daemon:amd64: /usr/lib/systemd/system/libvirtd.service\n")
+                .as_deref(),
+            Some("libvirt-daemon")
+        );
+        assert_eq!(parse_owner(""), None);
+    }
+
+    #[test]
+    fn test_pacman_hook() {
+        let hook = pacman_hook("cups", "cups.service");
+        assert!(hook.contains("Target = cups\n"));
+        assert!(hook.contains("Exec = /usr/bin/systemctl try-restart cups.service\n"));
+    }
+}
@@ -0,0 +1,178 @@
+use std::process::{Command, Stdio};
+
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command as AsyncCommand};
+use tokio::sync::mpsc;
+
+use crate::systemd::{machine, ServiceScope};
+use crate::trace;
+
+/// A systemd job announced on the bus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Job {
+    pub id: u32,
+    pub unit: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum JobEvent {
+    New(Job),
+    Removed(Job),
+}
+
+/// A row of `systemctl list-jobs`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedJob {
+    pub id: u32,
+    pub unit: String,
+    pub job_type: String, // start, stop, restart, ...
+    pub state: String,    // waiting or running
+}
+
+/// Jobs the manager currently has queued. Empty on error.
+pub fn list_jobs(scope: &ServiceScope) -> Vec<QueuedJob> {
+    let mut cmd = Command::new("systemctl");
+    if let Some(name) = machine() {
+        cmd.args(["-M", name]);
+    }
+    match scope {
+        ServiceScope::System => {}
+        ServiceScope::User => {
+            cmd.arg("--user");
+        }
+        ServiceScope::Global => return Vec::new(),
+    }
+    cmd.args(["list-jobs", "--no-legend", "--no-pager"]);
+    match trace::output(&mut cmd) {
+        Ok(output) => parse_list_jobs(&String::from_utf8_lossy(&output.stdout)),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// `JOB UNIT TYPE STATE` columns; "No jobs running." has no numeric id.
+fn parse_list_jobs(stdout: &str) -> Vec<QueuedJob> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut cols = line.split_whitespace();
+            Some(QueuedJob {
+                id: cols.next()?.parse().ok()?,
+                unit: cols.next()?.to_string(),
+                job_type: cols.next()?.to_string(),
+                state: cols.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Listens to the manager's JobNew/JobRemoved signals while an apply runs.
+/// Dropping it stops the monitor.
+pub struct JobWatch {
+    pub events: mpsc::UnboundedReceiver<JobEvent>,
+    _monitor: Child,
+}
+
+/// Start `gdbus monitor` on the bus for `scope`. systemd only emits job
+/// signals while some client is subscribed, which systemctl is for as long
+/// as it waits on a start/stop. Returns None where there's no monitor
+/// (gdbus missing, or the global scope, which has no manager).
+pub fn watch(scope: &ServiceScope) -> Option<JobWatch> {
+    // A container's bus isn't the host's system bus
+    if machine().is_some() {
+        return None;
+    }
+    let bus = match scope {
+        ServiceScope::System => "--system",
+        ServiceScope::User => "--session",
+        ServiceScope::Global => return None,
+    };
+    let mut monitor = AsyncCommand::new("gdbus")
+        .args([
+            "monitor",
+            bus,
+            "--dest",
+            "org.freedesktop.systemd1",
+            "--object-path",
+            "/org/freedesktop/systemd1",
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .ok()?;
+
+    let stdout = monitor.stdout.take()?;
+    let (tx, events) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if let Some(event) = parse_monitor_line(&line) {
+                if tx.send(event).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    Some(JobWatch {
+        events,
+        _monitor: monitor,
+    })
+}
+
+/// Parse one `gdbus monitor` line, e.g.
+/// `/org/freedesktop/systemd1: org.freedesktop.systemd1.Manager.JobNew (uint32 4321, objectpath '/org/freedesktop/systemd1/job/4321', 'cups.service')`
+fn parse_monitor_line(line: &str) -> Option<JobEvent> {
+    let (_, rest) = line.split_once("org.freedesktop.systemd1.Manager.Job")?;
+    let (signal, args) = rest.split_once(" (")?;
+    let mut args = args.trim_end_matches(')').split(", ");
+
+    let id = args.next()?.strip_prefix("uint32 ")?.parse().ok()?;
+    let _path = args.next()?;
+    let unit = args.next()?.trim_matches('\'').to_string();
+    let job = Job { id, unit };
+
+    match signal {
+        "New" => Some(JobEvent::New(job)),
+        "Removed" => Some(JobEvent::Removed(job)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_monitor_line() {
+        let new = "/org/freedesktop/systemd1: org.freedesktop.systemd1.Manager.JobNew (uint32 4321, objectpath '/org/freedesktop/systemd1/job/4321', 'cups.service')";
+        assert_eq!(
+            parse_monitor_line(new),
+            Some(JobEvent::New(Job {
+                id: 4321,
+                unit: "cups.service".into()
+            }))
+        );
+
+        let removed = "/org/freedesktop/systemd1: org.freedesktop.systemd1.Manager.JobRemoved (uint32 4321, objectpath '/org/freedesktop/systemd1/job/4321', 'cups.service', 'done')";
+        assert!(matches!(
+            parse_monitor_line(removed),
+            Some(JobEvent::Removed(Job { id: 4321, .. }))
+        ));
+
+        let other = "/org/freedesktop/systemd1: org.freedesktop.systemd1.Manager.UnitNew ('cups.service', objectpath '/org/freedesktop/systemd1/unit/cups_2eservice')";
+        assert_eq!(parse_monitor_line(other), None);
+    }
+
+    #[test]
+    fn test_parse_list_jobs() {
+        let output =
+            "4321 cups.service       start running\n4325 packagekit.service stop  waiting\n";
+        let jobs = parse_list_jobs(output);
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[1].unit, "packagekit.service");
+        assert_eq!(jobs[1].state, "waiting");
+        assert!(parse_list_jobs("No jobs running.\n").is_empty());
+    }
+}
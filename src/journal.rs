@@ -0,0 +1,259 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command as AsyncCommand};
+use tokio::sync::mpsc;
+
+use crate::systemd::{machine, ServiceScope};
+use crate::trace;
+
+const SNAPSHOT_LINES: &str = "200";
+/// Warnings and errors kept for a failed unit's row in the list.
+const FAILURE_LINES: &str = "5";
+const PERSISTENT_DIR: &str = "/var/log/journal";
+/// Once the directory exists journald stores to disk; tmpfiles sets its
+/// ownership and --flush moves the runtime journal over.
+const PERSIST_SCRIPT: &str = "mkdir -p /var/log/journal \
+    && systemd-tmpfiles --create --prefix /var/log/journal \
+    && journalctl --flush";
+/// Groups journald lets read the whole journal.
+const READER_GROUPS: &[&str] = &["systemd-journal", "adm", "wheel"];
+
+/// Why a log view came back empty when it probably shouldn't have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalProblem {
+    /// System logs (and, without a persistent journal, user logs too) are
+    /// only readable by members of systemd-journal / adm / wheel.
+    NotInGroup,
+    /// Volatile journal: user-unit logs only land in the system journal.
+    NotPersistent,
+}
+
+impl JournalProblem {
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            Self::NotInGroup => {
+                "You're not in the systemd-journal group, so journald hides these logs from you."
+            }
+            Self::NotPersistent => {
+                "The journal isn't persistent, so user services log into the system journal, which you can't read."
+            }
+        }
+    }
+
+    /// What to run by hand; `F` runs it through pkexec.
+    pub fn fix_command(&self, user: &str) -> String {
+        match self {
+            Self::NotInGroup => {
+                format!("sudo usermod -aG systemd-journal {user}  (then log in again)")
+            }
+            Self::NotPersistent => format!("sudo sh -c '{PERSIST_SCRIPT}'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogView {
+    pub unit: String,
+    pub lines: Vec<String>,
+    pub problem: Option<JournalProblem>,
+}
+
+/// journalctl arguments selecting `unit`'s messages.
+fn unit_args(scope: &ServiceScope, unit: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(name) = machine() {
+        args.extend(["-M".to_string(), name.to_string()]);
+    }
+    args.push(match scope {
+        // --user-unit also searches the system journal, where user logs go
+        // when the journal is volatile
+        ServiceScope::User | ServiceScope::Global => format!("--user-unit={unit}"),
+        ServiceScope::System => format!("--unit={unit}"),
+    });
+    args
+}
+
+/// The unit's most recent log lines, or a diagnosis if we can't see them.
+pub fn snapshot(scope: &ServiceScope, unit: &str) -> LogView {
+    let mut cmd = Command::new("journalctl");
+    cmd.args(unit_args(scope, unit));
+    cmd.args(["--lines", SNAPSHOT_LINES, "--no-pager", "--quiet"]);
+
+    let (lines, stderr) = match trace::output(&mut cmd) {
+        Ok(output) => (
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(String::from)
+                .collect::<Vec<_>>(),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ),
+        Err(e) => (Vec::new(), e.to_string()),
+    };
+    let problem = if lines.is_empty() || stderr.contains("insufficient permissions") {
+        diagnose(scope, &user_groups())
+    } else {
+        None
+    };
+    LogView {
+        unit: unit.to_string(),
+        lines,
+        problem,
+    }
+}
+
+/// The last few warnings and errors `unit` logged, oldest first, message
+/// text only: systemd's own "Failed with result" is a warning. Empty when
+/// the journal can't be read (`o` explains why).
+pub fn last_errors(scope: &ServiceScope, unit: &str) -> Vec<String> {
+    let mut cmd = Command::new("journalctl");
+    cmd.args(unit_args(scope, unit));
+    cmd.args([
+        "--priority=warning",
+        "--lines",
+        FAILURE_LINES,
+        "--output=cat",
+        "--no-pager",
+        "--quiet",
+    ]);
+    match trace::output(&mut cmd) {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// A running `journalctl -f`. Dropping it stops journalctl.
+pub struct Follow {
+    pub lines: mpsc::UnboundedReceiver<String>,
+    _journalctl: Child,
+}
+
+/// Syslog levels `p` cycles through in follow mode; None shows everything.
+pub const PRIORITIES: &[Option<u8>] = &[None, Some(3), Some(4), Some(6)];
+
+pub fn priority_name(priority: Option<u8>) -> &'static str {
+    match priority {
+        None => "all",
+        Some(0..=3) => "err",
+        Some(4) => "warning",
+        Some(5) => "notice",
+        Some(_) => "info",
+    }
+}
+
+/// Stream `unit`'s log lines, starting with the last screenful, optionally
+/// only those at `priority` or more severe.
+pub fn follow(scope: &ServiceScope, unit: &str, priority: Option<u8>) -> Option<Follow> {
+    let mut cmd = AsyncCommand::new("journalctl");
+    cmd.args(unit_args(scope, unit))
+        .args(["--follow", "--lines", "50", "--no-pager", "--quiet"]);
+    if let Some(level) = priority {
+        cmd.arg(format!("--priority={level}"));
+    }
+    let mut journalctl = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .ok()?;
+
+    let stdout = journalctl.stdout.take()?;
+    let (tx, lines) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = reader.next_line().await {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    Some(Follow {
+        lines,
+        _journalctl: journalctl,
+    })
+}
+
+fn user_groups() -> Vec<String> {
+    let mut cmd = Command::new("id");
+    cmd.arg("-Gn");
+    trace::output(&mut cmd)
+        .map(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .split_whitespace()
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn diagnose(scope: &ServiceScope, groups: &[String]) -> Option<JournalProblem> {
+    let reader = groups
+        .iter()
+        .any(|g| g == "root" || READER_GROUPS.contains(&g.as_str()));
+    if reader {
+        return None; // genuinely no logs
+    }
+    match scope {
+        ServiceScope::System => Some(JournalProblem::NotInGroup),
+        _ if Path::new(PERSISTENT_DIR).exists() => None,
+        _ => Some(JournalProblem::NotPersistent),
+    }
+}
+
+/// Run the fix as root. Group changes only apply to new logins.
+pub fn fix(problem: JournalProblem, user: &str) -> Result<String> {
+    let mut cmd = Command::new("pkexec");
+    match problem {
+        JournalProblem::NotInGroup => {
+            cmd.args(["usermod", "-aG", "systemd-journal", "--", user]);
+        }
+        JournalProblem::NotPersistent => {
+            cmd.args(["sh", "-c", PERSIST_SCRIPT]);
+        }
+    }
+    let output = trace::output(&mut cmd).context("Failed to run pkexec")?;
+    if !output.status.success() {
+        bail!(
+            "Fix failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(match problem {
+        JournalProblem::NotInGroup => {
+            format!("Added {user} to systemd-journal; log out and back in to read the journal")
+        }
+        JournalProblem::NotPersistent => {
+            "Journal is now persistent; user services' new logs will be readable".to_string()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn groups(names: &[&str]) -> Vec<String> {
+        names.iter().map(|g| g.to_string()).collect()
+    }
+
+    #[test]
+    fn test_diagnose_system_logs_need_group() {
+        assert_eq!(
+            diagnose(&ServiceScope::System, &groups(&["leo", "audio"])),
+            Some(JournalProblem::NotInGroup)
+        );
+        assert_eq!(
+            diagnose(&ServiceScope::System, &groups(&["leo", "wheel"])),
+            None
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use std::process::Stdio;
+
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command as AsyncCommand};
+use tokio::sync::mpsc;
+
+use crate::systemd::{machine, ServiceScope};
+
+/// A change to the manager's units seen on the bus.
+#[derive(Debug, PartialEq, Eq)]
+pub enum UnitEvent {
+    /// A unit's ActiveState changed to active (or reloading), or failed.
+    State {
+        unit: String,
+        active: bool,
+        failed: bool,
+    },
+    /// The unit was loaded (UnitNew); new ones may need a re-list.
+    Loaded(String),
+    /// The unit was unloaded (UnitRemoved), so it's no longer running.
+    Unloaded(String),
+    /// Unit files were enabled, disabled or reloaded by someone.
+    FilesChanged,
+}
+
+/// Listens to unit signals for as long as the TUI is open. Dropping it
+/// stops the monitor.
+pub struct UnitWatch {
+    pub events: mpsc::UnboundedReceiver<UnitEvent>,
+    _monitor: Child,
+}
+
+/// Start `gdbus monitor` on every systemd object on the bus for `scope`.
+/// Like job signals, these only flow while some client has called
+/// Subscribe (logind and desktop shells do). Returns None where there's
+/// nothing to watch: gdbus missing, the global scope, or a container.
+pub fn watch(scope: &ServiceScope) -> Option<UnitWatch> {
+    if machine().is_some() {
+        return None;
+    }
+    let bus = match scope {
+        ServiceScope::System => "--system",
+        ServiceScope::User => "--session",
+        ServiceScope::Global => return None,
+    };
+    let mut monitor = AsyncCommand::new("gdbus")
+        .args(["monitor", bus, "--dest", "org.freedesktop.systemd1"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .ok()?;
+
+    let stdout = monitor.stdout.take()?;
+    let (tx, events) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if let Some(event) = parse_monitor_line(&line) {
+                if tx.send(event).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    Some(UnitWatch {
+        events,
+        _monitor: monitor,
+    })
+}
+
+/// Parse one `gdbus monitor` line, e.g.
+/// `/org/freedesktop/systemd1/unit/cups_2eservice: org.freedesktop.DBus.Properties.PropertiesChanged ('org.freedesktop.systemd1.Unit', {'ActiveState': <'active'>, ...}, @as [])`
+fn parse_monitor_line(line: &str) -> Option<UnitEvent> {
+    let (path, rest) = line.split_once(": ")?;
+    if let Some(args) = rest.strip_prefix("org.freedesktop.DBus.Properties.PropertiesChanged (") {
+        // The Service/Socket interfaces change too, but only Unit has the state
+        if !args.starts_with("'org.freedesktop.systemd1.Unit'") {
+            return None;
+        }
+        let (_, state) = args.split_once("'ActiveState': <'")?;
+        let state = state.split('\'').next()?;
+        let unit = unescape_path(path.strip_prefix("/org/freedesktop/systemd1/unit/")?)?;
+        return Some(UnitEvent::State {
+            unit,
+            active: matches!(state, "active" | "reloading"),
+            failed: state == "failed",
+        });
+    }
+
+    let rest = rest.strip_prefix("org.freedesktop.systemd1.Manager.")?;
+    let (signal, args) = rest.split_once(" (")?;
+    let unit = || Some(args.split(", ").next()?.trim_matches('\'').to_string());
+    match signal {
+        "UnitNew" => Some(UnitEvent::Loaded(unit()?)),
+        "UnitRemoved" => Some(UnitEvent::Unloaded(unit()?)),
+        // Reloading is sent twice, before (true) and after (false)
+        "UnitFilesChanged" | "Reloading" if !args.starts_with("true") => {
+            Some(UnitEvent::FilesChanged)
+        }
+        _ => None,
+    }
+}
+
+/// Undo systemd's object-path escaping: `cups_2eservice` -> `cups.service`.
+fn unescape_path(label: &str) -> Option<String> {
+    let mut name = Vec::new();
+    let mut bytes = label.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'_' {
+            let hex = [bytes.next()?, bytes.next()?];
+            name.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            name.push(b);
+        }
+    }
+    String::from_utf8(name).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_monitor_line() {
+        let changed = "/org/freedesktop/systemd1/unit/cups_2eservice: org.freedesktop.DBus.Properties.PropertiesChanged ('org.freedesktop.systemd1.Unit', {'ActiveState': <'inactive'>, 'SubState': <'dead'>}, @as [])";
+        assert_eq!(
+            parse_monitor_line(changed),
+            Some(UnitEvent::State {
+                unit: "cups.service".into(),
+                active: false,
+                failed: false
+            })
+        );
+
+        let service_iface = "/org/freedesktop/systemd1/unit/cups_2eservice: org.freedesktop.DBus.Properties.PropertiesChanged ('org.freedesktop.systemd1.Service', {'MainPID': <uint32 0>}, @as [])";
+        assert_eq!(parse_monitor_line(service_iface), None);
+
+        let removed = "/org/freedesktop/systemd1: org.freedesktop.systemd1.Manager.UnitRemoved ('cups.service', objectpath '/org/freedesktop/systemd1/unit/cups_2eservice')";
+        assert_eq!(
+            parse_monitor_line(removed),
+            Some(UnitEvent::Unloaded("cups.service".into()))
+        );
+
+        let files =
+            "/org/freedesktop/systemd1: org.freedesktop.systemd1.Manager.UnitFilesChanged ()";
+        assert_eq!(parse_monitor_line(files), Some(UnitEvent::FilesChanged));
+        let reloading =
+            "/org/freedesktop/systemd1: org.freedesktop.systemd1.Manager.Reloading (true,)";
+        assert_eq!(parse_monitor_line(reloading), None);
+    }
+
+    #[test]
+    fn test_unescape_path() {
+        assert_eq!(
+            unescape_path("systemd_2dresolved_2eservice").as_deref(),
+            Some("systemd-resolved.service")
+        );
+        assert_eq!(
+            unescape_path("getty_40tty1_2eservice").as_deref(),
+            Some("getty@tty1.service")
+        );
+        assert_eq!(unescape_path("bad_2"), None);
+    }
+}
@@ -1,68 +1,508 @@
+mod advisor;
 mod app;
+mod boot;
 mod categories;
+mod compare;
+mod config;
+mod cron;
+mod demo;
+mod deps;
+mod descriptions;
+mod drift;
+mod export;
+mod hooks;
+mod jobs;
+mod journal;
+mod live;
+mod metrics;
+mod pager;
+mod polkit;
+mod profiles;
+mod rescue;
+mod sched_ext;
+mod status;
 mod systemd;
+mod templates;
+mod trace;
 mod tui;
+mod tutorial;
+mod unit_cache;
 
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use crossterm::event;
-use tokio::sync::oneshot;
+use tokio::sync::{mpsc, oneshot};
 
+use anyhow::bail;
 use app::{App, Mode};
-use systemd::{apply_changes, ChangeResult};
+use compare::RemoteState;
+use config::Config;
+use descriptions::online;
+use systemd::{
+    apply_changes, edit_command, list_services_on, schedule_changes, ApplyEvent, ServiceScope,
+};
 use tui::handler::{handle_event, Action};
 use tui::ui::render;
 
+/// Delay between replayed events so a maintainer can follow along.
+const REPLAY_STEP: Duration = Duration::from_millis(150);
+/// Input poll timeout while something is in flight, and the idle ceiling it
+/// backs off to (doubling per quiet poll) when nothing is.
+const POLL_BUSY: Duration = Duration::from_millis(50);
+const POLL_IDLE: Duration = Duration::from_millis(500);
+/// Redraw even when idle now and then, so "next in 3h"-style times move.
+const CLOCK_REDRAW: Duration = Duration::from_secs(30);
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    let mut terminal = ratatui::init();
-    let result = run(&mut terminal).await;
-    ratatui::restore();
+    let options = parse_args(std::env::args().skip(1))?;
+    if let Some(group) = &options.polkit_group {
+        print!("{}", polkit::rule(group)?);
+        return Ok(());
+    }
+    if let Some(path) = &options.record {
+        trace::start_recording(path)?;
+    }
+    if let Some(path) = &options.replay {
+        trace::start_replay(path)?;
+    }
+    if options.tutorial {
+        trace::start_demo();
+    }
+    if let Some(name) = &options.machine {
+        systemd::set_machine(name)?;
+    }
+    if let Some(format) = options.export {
+        print!("{}", export::render(format, &export::collect()?)?);
+        return Ok(());
+    }
+    if options.status {
+        let format = options.status_format.unwrap_or(status::Format::Waybar);
+        print!("{}", status::render(format, &status::collect()));
+        return Ok(());
+    }
+    if let Some((left, right)) = &options.diff {
+        let label = |path: &PathBuf| path.display().to_string();
+        let diffs =
+            compare::diff_states(&compare::load_export(left)?, &compare::load_export(right)?);
+        print!(
+            "{}",
+            compare::render_diff(&label(left), &label(right), &diffs)
+        );
+        return Ok(());
+    }
+    if options.metrics {
+        let text = metrics::render(&metrics::collect()?);
+        match &options.metrics_output {
+            Some(path) => metrics::write(path, &text)?,
+            None => print!("{text}"),
+        }
+        return Ok(());
+    }
+    let mut config = Config::load()?;
+    // The demo machine's state is no baseline for the real one
+    config.track_drift &= !options.tutorial;
+    // The tutorial walks through staging and review
+    config.instant &= !options.tutorial;
+    if let Some(secs) = options.timeout {
+        config.timeout = Some(secs);
+    }
+    systemd::set_timeout_policy(config.timeout_policy());
+
+    // Fetch before entering the TUI so ssh can prompt for a password
+    let remote = match options.compare_host {
+        // An export saved on (or copied from) another machine
+        Some(file) if Path::new(&file).is_file() => {
+            Some(RemoteState::from_export(Path::new(&file))?)
+        }
+        Some(host) => {
+            let services = list_services_on(&ServiceScope::System, Some(&host))?;
+            Some(RemoteState::new(host, &services))
+        }
+        None => None,
+    };
+
+    if let Some(url) = config.descriptions_url.clone() {
+        if online::cache_is_stale() {
+            // Best effort: the next info lookup picks up the fresh cache
+            tokio::spawn(async move {
+                let _ = online::refresh(&url).await;
+            });
+        }
+    }
+
+    let mut terminal = tui::init();
+    let result = run(&mut terminal, config, remote).await;
+    tui::restore();
     result
 }
 
-async fn run(terminal: &mut ratatui::DefaultTerminal) -> Result<()> {
-    let mut app = App::new()?;
-    let mut pending_apply: Option<oneshot::Receiver<Vec<ChangeResult>>> = None;
+#[derive(Debug, Default)]
+struct Options {
+    compare_host: Option<String>,
+    record: Option<PathBuf>,
+    replay: Option<PathBuf>,
+    polkit_group: Option<String>,
+    machine: Option<String>,
+    export: Option<export::Format>,
+    tutorial: bool,
+    timeout: Option<u64>, // seconds, over the config's
+    status: bool,         // `status`: print a status bar summary and exit
+    status_format: Option<status::Format>,
+    metrics: bool, // `metrics`: print a Prometheus snapshot and exit
+    metrics_output: Option<PathBuf>,
+    diff: Option<(PathBuf, PathBuf)>, // `diff A B`: compare two exports and exit
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options> {
+    let mut options = Options::default();
+    let mut args = args;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--compare" => match args.next() {
+                Some(host) => options.compare_host = Some(host),
+                None => bail!("--compare needs a host or an export (e.g. --compare user@laptop)"),
+            },
+            "--record" => match args.next() {
+                Some(path) => options.record = Some(PathBuf::from(path)),
+                None => bail!("--record needs a file to write the session trace to"),
+            },
+            "--replay" => match args.next() {
+                Some(path) => options.replay = Some(PathBuf::from(path)),
+                None => bail!("--replay needs a session trace file"),
+            },
+            "-M" | "--machine" => match args.next() {
+                Some(name) => options.machine = Some(name),
+                None => bail!("--machine needs a container name (see machinectl list)"),
+            },
+            "--polkit-rule" => match args.next() {
+                Some(group) => options.polkit_group = Some(group),
+                None => bail!("--polkit-rule needs a group (e.g. --polkit-rule wheel)"),
+            },
+            "status" => options.status = true,
+            "metrics" => options.metrics = true,
+            "diff" => match (args.next(), args.next()) {
+                (Some(left), Some(right)) => {
+                    options.diff = Some((PathBuf::from(left), PathBuf::from(right)))
+                }
+                _ => bail!("diff needs two JSON exports (comma-services diff a.json b.json)"),
+            },
+            "--output" => match args.next() {
+                Some(path) => options.metrics_output = Some(PathBuf::from(path)),
+                None => {
+                    bail!("--output needs a file (e.g. /var/lib/node_exporter/comma-services.prom)")
+                }
+            },
+            "--format" => match args.next() {
+                Some(format) => options.status_format = Some(status::Format::parse(&format)?),
+                None => bail!("--format needs a status bar (waybar or i3blocks)"),
+            },
+            "--tutorial" => options.tutorial = true,
+            "--timeout" => match args.next().and_then(|secs| secs.parse().ok()) {
+                Some(secs) if secs > 0 => options.timeout = Some(secs),
+                _ => bail!("--timeout needs a number of seconds (e.g. --timeout 30)"),
+            },
+            "--export" => match args.next() {
+                Some(format) => options.export = Some(export::Format::parse(&format)?),
+                None => bail!("--export needs a format (json, yaml or dot)"),
+            },
+            _ => bail!("Unknown argument: {arg}"),
+        }
+    }
+
+    if options.status_format.is_some() && !options.status {
+        bail!("--format goes with the status command (comma-services status --format waybar)");
+    }
+    if options.metrics_output.is_some() && !options.metrics {
+        bail!("--output goes with the metrics command (comma-services metrics --output FILE)");
+    }
+    if options.compare_host.is_some() && options.machine.is_some() {
+        bail!("--compare and --machine can't be combined");
+    }
+    if options.record.is_some() && options.replay.is_some() {
+        bail!("--record and --replay can't be combined");
+    }
+    let real_backend = options.record.is_some()
+        || options.replay.is_some()
+        || options.machine.is_some()
+        || options.compare_host.is_some()
+        || options.export.is_some()
+        || options.status
+        || options.metrics
+        || options.diff.is_some();
+    if options.tutorial && real_backend {
+        bail!("--tutorial runs on a demo machine and can't be combined with other options");
+    }
+    Ok(options)
+}
+
+async fn run(
+    terminal: &mut ratatui::DefaultTerminal,
+    config: Config,
+    remote: Option<RemoteState>,
+) -> Result<()> {
+    let mut app = App::new(config)?;
+    app.remote = remote;
+    if trace::is_demo() {
+        app.tutorial = Some(tutorial::Step::Move);
+    }
+    if let Some(boot_report) = boot::compare_with_record() {
+        app.notice = Some(boot_report);
+    }
+    let mut pending_apply: Option<mpsc::UnboundedReceiver<ApplyEvent>> = None;
+    let mut pending_schedule: Option<oneshot::Receiver<Result<String, String>>> = None;
+    let mut pending_update: Option<oneshot::Receiver<Result<String>>> = None;
+    let mut job_watch: Option<jobs::JobWatch> = None;
+    let mut log_follow: Option<journal::Follow> = None;
+    let mut unit_watch: Option<live::UnitWatch> = None;
+    let mut watched_scope = None;
+    let mut redraw = true;
+    let mut last_draw = Instant::now();
+    let mut poll_timeout = POLL_BUSY;
 
     loop {
-        terminal.draw(|frame| render(frame, &app))?;
+        redraw |= app.advance_tutorial();
+        if redraw || last_draw.elapsed() >= CLOCK_REDRAW {
+            let mut viewport = None;
+            terminal.draw(|frame| viewport = Some(render(frame, &app)))?;
+            if let Some(view) = viewport {
+                app.fit_viewport(view.list_offset, view.modal_scroll_max);
+            }
+            redraw = false;
+            last_draw = Instant::now();
+        }
+
+        if let Some(watch) = &mut job_watch {
+            while let Ok(job_event) = watch.events.try_recv() {
+                app.job_event(job_event);
+                redraw = true;
+            }
+        }
+
+        // Follows the tab: one monitor per manager
+        if !trace::is_offline() && watched_scope.as_ref() != Some(&app.scope()) {
+            watched_scope = Some(app.scope());
+            unit_watch = live::watch(&app.scope());
+        }
+        if let Some(watch) = &mut unit_watch {
+            while let Ok(unit_event) = watch.events.try_recv() {
+                app.unit_event(unit_event);
+                redraw = true;
+            }
+        }
+        app.sync_units();
+
+        if let Some(follow) = &mut log_follow {
+            while let Ok(line) = follow.lines.try_recv() {
+                app.follow_line(line);
+                redraw = true;
+            }
+        }
+        if app.mode != Mode::Follow {
+            log_follow = None; // stops journalctl
+        }
+
+        // Every finished task clears its slot, which is worth a redraw
+        let in_flight = (
+            pending_apply.is_some(),
+            pending_schedule.is_some(),
+            pending_update.is_some(),
+        );
 
         // Check if background apply has completed
-        if let Some(ref mut rx) = pending_apply {
+        while let Some(ref mut rx) = pending_apply {
             match rx.try_recv() {
-                Ok(results) => {
-                    let _ = app.apply_done(results);
+                Ok(event) => {
+                    redraw = true;
+                    if app.apply_event(event).unwrap_or(true) {
+                        // Unless the results opened themselves on a failure
+                        if app.mode == Mode::Applying {
+                            app.mode = Mode::Normal;
+                        }
+                        pending_apply = None;
+                        job_watch = None;
+                    }
+                }
+                Err(mpsc::error::TryRecvError::Empty) => {
+                    // Still running, keep spinning
+                    break;
+                }
+                Err(mpsc::error::TryRecvError::Disconnected) => {
+                    // Task panicked or was dropped; some changes may have
+                    // landed, so reread state rather than trust the staging
                     app.mode = Mode::Normal;
                     pending_apply = None;
+                    job_watch = None;
+                    app.jobs.clear();
+                    app.begin_apply(0);
+                    let _ = app.refresh();
                 }
-                Err(oneshot::error::TryRecvError::Empty) => {
-                    // Still running, keep spinning
+            }
+        }
+
+        if let Some(ref mut rx) = pending_schedule {
+            match rx.try_recv() {
+                Ok(result) => {
+                    let _ = app.schedule_done(result);
+                    app.mode = Mode::Normal;
+                    pending_schedule = None;
                 }
+                Err(oneshot::error::TryRecvError::Empty) => {}
                 Err(oneshot::error::TryRecvError::Closed) => {
-                    // Task panicked or was dropped
                     app.mode = Mode::Normal;
-                    pending_apply = None;
+                    pending_schedule = None;
                 }
             }
         }
 
-        if event::poll(Duration::from_millis(50))? {
-            let action = handle_event(&mut app, event::read()?);
+        if let Some(ref mut rx) = pending_update {
+            match rx.try_recv() {
+                Ok(result) => {
+                    app.notice = Some(match result {
+                        Ok(summary) => summary,
+                        Err(e) => format!("Description update failed: {e:#}"),
+                    });
+                    pending_update = None;
+                }
+                Err(oneshot::error::TryRecvError::Empty) => {}
+                Err(oneshot::error::TryRecvError::Closed) => pending_update = None,
+            }
+        }
+
+        redraw |= in_flight
+            != (
+                pending_apply.is_some(),
+                pending_schedule.is_some(),
+                pending_update.is_some(),
+            );
+
+        // Quick polls while anything can change the screen without input
+        let busy = in_flight != (false, false, false) || app.mode == Mode::Follow;
+        if busy || redraw {
+            poll_timeout = POLL_BUSY;
+        }
+
+        // Replayed input waits for any in-flight apply, like a real user would
+        let replayed = if trace::is_replaying() && app.mode != Mode::Applying {
+            std::thread::sleep(REPLAY_STEP);
+            trace::next_replay_event()
+        } else {
+            None
+        };
+
+        if replayed.is_some() || event::poll(poll_timeout)? {
+            redraw = true;
+            poll_timeout = POLL_BUSY;
+            let event = match replayed {
+                Some(event) => event,
+                None => event::read()?,
+            };
+            trace::record_event(&event);
+            let action = handle_event(&mut app, event);
+
+            match action {
+                // One batch at a time: a second one would race the first's
+                // refresh and leave original_state half-updated
+                Action::ApplyChanges | Action::ScheduleChanges(_)
+                    if pending_apply.is_some() || pending_schedule.is_some() =>
+                {
+                    app.notice = Some("Changes are already being applied".to_string());
+                }
+                Action::ApplyChanges => {
+                    let changes = app.pending_changes();
+                    app.mode = Mode::Applying;
+                    app.notice = None;
+
+                    if rescue::needs_rescue(&changes) {
+                        app.notice = Some(match rescue::write_rescue_script(&changes) {
+                            Ok(path) => format!("Rescue plan: {}", path.display()),
+                            Err(e) => format!("Could not write rescue plan: {e}"),
+                        });
+                    }
 
-            if let Action::ApplyChanges = action {
-                let changes = app.pending_changes();
-                app.mode = Mode::Applying;
+                    if !trace::is_offline() {
+                        job_watch = jobs::watch(&app.scope());
+                    }
 
-                let (tx, rx) = oneshot::channel();
-                pending_apply = Some(rx);
+                    let (tx, rx) = mpsc::unbounded_channel();
+                    pending_apply = Some(rx);
+                    let cancel = app.begin_apply(changes.len());
 
-                tokio::spawn(async move {
-                    let results = apply_changes(changes).await;
-                    let _ = tx.send(results);
-                });
+                    tokio::spawn(apply_changes(changes, tx, cancel));
+                }
+                Action::ScheduleChanges(when) => {
+                    let changes = app.pending_changes();
+                    app.mode = Mode::Applying;
+                    app.notice = None;
+
+                    let (tx, rx) = oneshot::channel();
+                    pending_schedule = Some(rx);
+
+                    tokio::spawn(async move {
+                        let result = schedule_changes(changes, when).await;
+                        let _ = tx.send(result);
+                    });
+                }
+                Action::UpdateDescriptions if pending_update.is_none() => {
+                    if let Some(url) = app.config.descriptions_url.clone() {
+                        app.notice = Some("Checking for description updates...".to_string());
+
+                        let (tx, rx) = oneshot::channel();
+                        pending_update = Some(rx);
+
+                        tokio::spawn(async move {
+                            let _ = tx.send(online::refresh(&url).await);
+                        });
+                    }
+                }
+                Action::EditUnit { name, full } => {
+                    let status = if trace::is_offline() {
+                        Ok(std::process::ExitStatus::default())
+                    } else {
+                        // Hand the terminal to the editor until it exits
+                        tui::restore();
+                        let status = edit_command(&app.scope(), &name, full).status();
+                        *terminal = tui::init();
+                        status
+                    };
+                    app.edit_done(&name, full, status);
+                }
+                Action::Page(text) => {
+                    if !trace::is_replaying() {
+                        tui::restore();
+                        let shown = pager::show(&text);
+                        *terminal = tui::init();
+                        if let Err(e) = shown {
+                            app.notice = Some(format!("{e:#}"));
+                        }
+                    }
+                }
+                Action::FollowLogs => {
+                    log_follow = None;
+                    if let Some(follow) = &app.follow {
+                        if trace::is_offline() {
+                            // There's no journal to follow, which is no failure
+                            app.notice = Some(
+                                "Following logs isn't available in the tutorial or a replay"
+                                    .to_string(),
+                            );
+                        } else {
+                            log_follow =
+                                journal::follow(&app.scope(), &follow.unit, follow.priority);
+                            if log_follow.is_none() {
+                                app.notice =
+                                    Some("Could not start journalctl --follow".to_string());
+                            }
+                        }
+                    }
+                }
+                Action::UpdateDescriptions | Action::None => {}
             }
+        } else {
+            poll_timeout = (poll_timeout * 2).min(POLL_IDLE);
         }
 
         if app.should_quit {
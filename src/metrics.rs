@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::systemd::{failed_units, list_services, machine, memory_by_unit, ServiceScope};
+
+/// One manager's numbers for the snapshot.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ScopeMetrics {
+    pub scope: &'static str,
+    pub enabled: usize,
+    pub active: usize,
+    pub failed: usize,              // units of any type, as `status` counts them
+    pub memory: Vec<(String, u64)>, // running services with accounting on
+}
+
+/// The system and user managers (only the container's with `--machine`).
+/// Global services have no manager of their own to count or measure.
+pub fn collect() -> Result<Vec<ScopeMetrics>> {
+    let scopes: &[ServiceScope] = if machine().is_some() {
+        &[ServiceScope::System]
+    } else {
+        &[ServiceScope::System, ServiceScope::User]
+    };
+    let mut metrics = Vec::new();
+    for scope in scopes {
+        let services = list_services(scope)?;
+        let active: Vec<String> = services
+            .iter()
+            .filter(|svc| svc.active && svc.name.ends_with(".service"))
+            .map(|svc| svc.name.clone())
+            .collect();
+        let mut memory = memory_by_unit(scope, &active);
+        memory.sort();
+        metrics.push(ScopeMetrics {
+            scope: scope.name(),
+            enabled: services.iter().filter(|svc| svc.enabled).count(),
+            active: services.iter().filter(|svc| svc.active).count(),
+            failed: failed_units(scope).len(),
+            memory,
+        });
+    }
+    Ok(metrics)
+}
+
+/// The Prometheus text exposition format, as node_exporter's textfile
+/// collector reads it.
+pub fn render(metrics: &[ScopeMetrics]) -> String {
+    let mut out = String::from(
+        "# HELP comma_services_units Units by systemd manager and state.
+# TYPE comma_services_units gauge
+",
+    );
+    for scope in metrics {
+        for (state, count) in [
+            ("enabled", scope.enabled),
+            ("active", scope.active),
+            ("failed", scope.failed),
+        ] {
+            out.push_str(&format!(
+                "comma_services_units{{scope=\"{}\",state=\"{state}\"}} {count}\n",
+                label(scope.scope)
+            ));
+        }
+    }
+    out.push_str(
+        "# HELP comma_services_memory_bytes Memory in use by each running service (MemoryCurrent).
+# TYPE comma_services_memory_bytes gauge
+",
+    );
+    for scope in metrics {
+        for (unit, bytes) in &scope.memory {
+            out.push_str(&format!(
+                "comma_services_memory_bytes{{scope=\"{}\",unit=\"{}\"}} {bytes}\n",
+                label(scope.scope),
+                label(unit)
+            ));
+        }
+    }
+    out
+}
+
+/// Label values escape backslash, double quote and newline.
+fn label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Replace `path` in one step, so the collector never scrapes half a file.
+/// The temporary name doesn't end in `.prom`, which the collector skips.
+pub fn write(path: &Path, text: &str) -> Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+    std::fs::write(&tmp, text).with_context(|| format!("Failed to write {}", path.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to replace {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_textfile() {
+        let metrics = [
+            ScopeMetrics {
+                scope: "system",
+                enabled: 42,
+                active: 30,
+                failed: 1,
+                memory: vec![("cups.service".into(), 4_194_304)],
+            },
+            ScopeMetrics {
+                scope: "user",
+                enabled: 3,
+                active: 2,
+                failed: 0,
+                memory: vec![("odd\"name\\.service".into(), 1024)],
+            },
+        ];
+        let text = render(&metrics);
+        assert!(text.starts_with("# HELP comma_services_units "));
+        assert!(text.contains("comma_services_units{scope=\"system\",state=\"enabled\"} 42\n"));
+        assert!(text.contains("comma_services_units{scope=\"user\",state=\"failed\"} 0\n"));
+        assert!(text.contains(
+            "comma_services_memory_bytes{scope=\"system\",unit=\"cups.service\"} 4194304\n"
+        ));
+        assert!(text.contains("unit=\"odd\\\"name\\\\.service\"} 1024\n"));
+        assert_eq!(text.matches("# TYPE").count(), 2);
+    }
+}
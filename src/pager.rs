@@ -0,0 +1,52 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+
+const DEFAULT_PAGER: &str = "less";
+
+/// `$PAGER` split into program and arguments (e.g. "less -R", "bat -p").
+fn argv(pager: Option<&str>) -> Vec<String> {
+    let words: Vec<String> = pager
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+    if words.is_empty() {
+        vec![DEFAULT_PAGER.to_string()]
+    } else {
+        words
+    }
+}
+
+/// Feed `text` to the user's pager and wait for them to quit it. The
+/// caller hands over the terminal first.
+pub fn show(text: &str) -> Result<()> {
+    let argv = argv(std::env::var("PAGER").ok().as_deref());
+    let mut pager = Command::new(&argv[0])
+        .args(&argv[1..])
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Could not start {}", argv[0]))?;
+    if let Some(mut stdin) = pager.stdin.take() {
+        // A pager quit before reading everything closes the pipe; that's fine
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    let status = pager.wait()?;
+    if !status.success() {
+        bail!("{} exited with {status}", argv[0]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_argv() {
+        assert_eq!(argv(Some("less -R")), ["less", "-R"]);
+        assert_eq!(argv(Some("  ")), ["less"]);
+        assert_eq!(argv(None), ["less"]);
+    }
+}
@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use crate::systemd::batch_prelude;
+
+/// Where the rule is meant to go; printed in its header comment.
+pub const RULE_PATH: &str = "/etc/polkit-1/rules.d/50-comma-services.rules";
+
+const SYSTEMCTL_PATHS: &[&str] = &["/usr/bin/systemctl", "/bin/systemctl"];
+
+/// The absolute path pkexec reports as `program`.
+fn systemctl_path() -> &'static str {
+    SYSTEMCTL_PATHS
+        .iter()
+        .find(|path| Path::new(path).exists())
+        .unwrap_or(&SYSTEMCTL_PATHS[0])
+}
+
+/// A polkit rule letting active local members of `group` run exactly the
+/// `pkexec systemctl` commands an apply issues, and the one-line batch
+/// script a system apply runs them in, without a password. Other pkexec
+/// uses (editing units, hooks, usermod) still ask.
+pub fn rule(group: &str) -> Result<String> {
+    let valid = group
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && group
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if !valid {
+        bail!("Not a group name: {group:?}");
+    }
+    Ok(render(group, systemctl_path(), &batch_prelude()))
+}
+
+fn render(group: &str, systemctl: &str, prelude: &str) -> String {
+    let program = systemctl.replace('/', "\\/");
+    let prelude = prelude.replace('\\', "\\\\").replace('"', "\\\"");
+    format!(
+        "// Written by comma-services: members of {group} can enable, disable,
+// mask, unmask, start, stop and restart units and daemon-reload without
+// a password.
+// Install: comma-services --polkit-rule {group} | sudo tee {RULE_PATH}
+polkit.addRule(function(action, subject) {{
+    if (action.id != \"org.freedesktop.policykit.exec\" ||
+        !subject.isInGroup(\"{group}\") || !subject.local || !subject.active) {{
+        return polkit.Result.NOT_HANDLED;
+    }}
+    var program = action.lookup(\"program\");
+    var line = action.lookup(\"command_line\");
+    if (program == \"{systemctl}\" &&
+        (/^{program} (--global )?(enable|disable|mask|unmask|start|stop|restart)( --runtime)? -- [A-Za-z0-9:_.@\\\\-]+$/.test(line) ||
+         line == \"{systemctl} daemon-reload\")) {{
+        return polkit.Result.YES;
+    }}
+    // A system apply: the fixed step function, then only those same verbs
+    var batch = \"/bin/sh -c {prelude}\";
+    var step = /r [0-9]+ [0-9]+ (--global )?(enable|disable|mask|unmask|start|stop|restart)( --runtime)? -- '[A-Za-z0-9:_.@\\\\-]+'/.source;
+    var steps = new RegExp(\"^(\" + step + \"( && \" + step + \")?; )+exit 0$\");
+    if (program == \"/bin/sh\" && line.indexOf(batch) == 0 &&
+        steps.test(line.substr(batch.length))) {{
+        return polkit.Result.YES;
+    }}
+    return polkit.Result.NOT_HANDLED;
+}});
+"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_rejects_odd_groups() {
+        assert!(rule("wheel").is_ok());
+        assert!(rule("systemd-admins").is_ok());
+        assert!(rule("wheel\"); polkit.Result.YES; (\"").is_err());
+        assert!(rule("").is_err());
+    }
+
+    #[test]
+    fn test_render_scopes_to_apply_commands() {
+        let rule = render("wheel", "/usr/bin/systemctl", "r() { \"$@\"; }; ");
+        assert!(rule.contains("subject.isInGroup(\"wheel\")"));
+        assert!(rule.contains(
+            "/^\\/usr\\/bin\\/systemctl (--global )?(enable|disable|mask|unmask|start|stop|restart)"
+        ));
+        assert!(rule.contains("[A-Za-z0-9:_.@\\\\-]+$/"));
+        assert!(rule.contains("var batch = \"/bin/sh -c r() { \\\"$@\\\"; }; \";"));
+    }
+}
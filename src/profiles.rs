@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{config_dir, state_dir};
+use crate::drift::now_secs;
+use crate::systemd::{Service, ServiceScope};
+
+/// A named set of enablement states for one scope, saved with `p`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Profile {
+    pub saved_at: u64, // unix seconds
+    pub scope: String,
+    pub enabled: BTreeMap<String, bool>,
+}
+
+/// A saved profile as listed in the picker.
+#[derive(Debug, Clone)]
+pub struct ProfileEntry {
+    pub name: String,
+    pub saved_at: u64,
+    pub services: usize,
+}
+
+/// What restoring a profile would stage.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Restore {
+    pub changes: Vec<(String, bool)>, // service, enabled as saved
+    pub missing: Vec<String>,         // saved but not installed anymore
+}
+
+/// `~/.config/comma-services/profiles`, one JSON file per profile.
+fn profile_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("profiles"))
+}
+
+fn profile_path(name: &str) -> Result<PathBuf> {
+    let dir = profile_dir().context("No config directory (HOME unset)")?;
+    Ok(dir.join(format!("{name}.json")))
+}
+
+/// Profile names become file names, so keep them to one plain component.
+pub fn check_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        bail!("Profile names use letters, digits, '-', '_' and '.'");
+    }
+    Ok(())
+}
+
+/// Save the on-disk enablement of `services`. Generated and masked units
+/// are left out: neither can be restored by enabling or disabling.
+pub fn save(name: &str, scope: &ServiceScope, services: &[Service]) -> Result<PathBuf> {
+    check_name(name)?;
+    let path = profile_path(name)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let profile = Profile {
+        saved_at: now_secs(),
+        scope: scope.name().to_string(),
+        enabled: services
+            .iter()
+            .filter(|svc| !svc.generated && !svc.masked)
+            .map(|svc| (svc.name.clone(), svc.enabled))
+            .collect(),
+    };
+    std::fs::write(&path, serde_json::to_string_pretty(&profile)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+pub fn load(name: &str) -> Result<Profile> {
+    let path = profile_path(name)?;
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&contents).with_context(|| format!("{} is not a profile", path.display()))
+}
+
+pub fn delete(name: &str) -> Result<()> {
+    let path = profile_path(name)?;
+    std::fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))
+}
+
+/// Saved profiles for `scope`, by name. Unreadable files are skipped.
+pub fn list(scope: &ServiceScope) -> Vec<ProfileEntry> {
+    let Some(entries) = profile_dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let mut profiles: Vec<ProfileEntry> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = path
+                .file_name()?
+                .to_str()?
+                .strip_suffix(".json")?
+                .to_string();
+            let profile: Profile =
+                serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+            (profile.scope == scope.name()).then_some(ProfileEntry {
+                name,
+                saved_at: profile.saved_at,
+                services: profile.enabled.len(),
+            })
+        })
+        .collect();
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    profiles
+}
+
+/// What maintenance mode stopped in `scope`, one unit per line, so
+/// leaving it starts just those again, even after a restart.
+fn maintenance_path(scope: &ServiceScope) -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join(format!("maintenance-{}", scope.name())))
+}
+
+/// The units maintenance mode stopped; empty when it's off.
+pub fn maintenance_stopped(scope: &ServiceScope) -> Vec<String> {
+    maintenance_path(scope)
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|text| text.lines().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Remember what maintenance mode stopped; nothing turns it off.
+pub fn record_maintenance(scope: &ServiceScope, stopped: &[String]) -> Result<()> {
+    let path = maintenance_path(scope).context("No state directory (HOME unset)")?;
+    if stopped.is_empty() {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove {}", path.display()))
+            }
+            _ => Ok(()),
+        };
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, stopped.join("\n") + "\n")
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// The toggles that bring `services` back to `profile`. Services installed
+/// since the profile was saved are left as they are.
+pub fn restore(profile: &Profile, services: &[Service]) -> Restore {
+    let mut plan = Restore::default();
+    for (name, &enabled) in &profile.enabled {
+        match services.iter().find(|svc| &svc.name == name) {
+            Some(svc) if svc.enabled != enabled => plan.changes.push((name.clone(), enabled)),
+            Some(_) => {}
+            None => plan.missing.push(name.clone()),
+        }
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(name: &str, enabled: bool) -> Service {
+        Service {
+            name: name.into(),
+            enabled,
+            active: enabled,
+            enabled_globally: false,
+            generated: false,
+            masked: false,
+            preset: None,
+        }
+    }
+
+    #[test]
+    fn test_check_name() {
+        assert!(check_name("laptop-minimal").is_ok());
+        assert!(check_name("gaming_2.0").is_ok());
+        assert!(check_name("").is_err());
+        assert!(check_name("../escape").is_err());
+        assert!(check_name(".hidden").is_err());
+    }
+
+    #[test]
+    fn test_restore_stages_differences() {
+        let profile = Profile {
+            saved_at: 0,
+            scope: "system".into(),
+            enabled: BTreeMap::from([
+                ("bluetooth.service".to_string(), false),
+                ("cups.service".to_string(), true),
+                ("gone.service".to_string(), true),
+            ]),
+        };
+        let services = [
+            service("bluetooth.service", true),
+            service("cups.service", true),
+            service("new.service", true),
+        ];
+        assert_eq!(
+            restore(&profile, &services),
+            Restore {
+                changes: vec![("bluetooth.service".into(), false)],
+                missing: vec!["gone.service".into()],
+            }
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use crate::categories::categorize;
+use crate::config::state_dir;
+use crate::systemd::{changes_script, ChangeAction, PendingChange};
+
+/// Login-path services outside the Network/Display categories. Breaking
+/// any of these can leave you without a way back in.
+const LOGIN_SERVICES: &[&str] = &["sshd", "systemd-logind", "getty@", "seatd", "polkit"];
+
+/// True when the batch touches services whose failure could lock you out
+/// of a graphical login or the network.
+pub fn needs_rescue(changes: &[PendingChange]) -> bool {
+    changes.iter().any(|change| {
+        let name = change.service.as_str();
+        matches!(categorize(name), "Network" | "Display")
+            || LOGIN_SERVICES.iter().any(|p| name.starts_with(p))
+    })
+}
+
+/// Write a script that undoes `changes` and return where it was saved.
+/// Lives in ~/.local/state so it's reachable from a bare TTY.
+pub fn write_rescue_script(changes: &[PendingChange]) -> Result<PathBuf> {
+    let dir = state_dir().context("No state directory (HOME unset)")?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = dir.join(format!("rescue-{stamp}.sh"));
+
+    std::fs::write(&path, rescue_script(changes))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+fn rescue_script(changes: &[PendingChange]) -> String {
+    let undo: Vec<PendingChange> = changes
+        .iter()
+        .map(|change| PendingChange {
+            service: change.service.clone(),
+            scope: change.scope.clone(),
+            action: match change.action {
+                ChangeAction::Enable => ChangeAction::Disable,
+                ChangeAction::Disable => ChangeAction::Enable,
+                ChangeAction::EnableOnly => ChangeAction::DisableOnly,
+                ChangeAction::DisableOnly => ChangeAction::EnableOnly,
+                ChangeAction::Start => ChangeAction::Stop,
+                // It was running before the restart
+                ChangeAction::Stop | ChangeAction::Restart => ChangeAction::Start,
+                ChangeAction::Mask => ChangeAction::Unmask,
+                ChangeAction::Unmask => ChangeAction::Mask,
+            },
+            runtime: change.runtime,
+        })
+        .collect();
+
+    let needs_root = changes.iter().any(|c| c.scope.needs_root());
+    let run_as = if needs_root {
+        "# Run as root from a TTY: sudo sh <this file>\n"
+    } else {
+        "# Run as your user: sh <this file>\n"
+    };
+
+    format!(
+        "#!/bin/sh\n# comma-services rescue plan: restores the state before the last apply.\n{run_as}{}\n",
+        changes_script(&undo)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systemd::ServiceScope;
+
+    fn change(service: &str, action: ChangeAction) -> PendingChange {
+        PendingChange {
+            service: service.to_string(),
+            scope: ServiceScope::System,
+            action,
+            runtime: false,
+        }
+    }
+
+    #[test]
+    fn test_needs_rescue_for_network_display_login() {
+        assert!(needs_rescue(&[change(
+            "NetworkManager.service",
+            ChangeAction::Disable
+        )]));
+        assert!(needs_rescue(&[change(
+            "sddm.service",
+            ChangeAction::Enable
+        )]));
+        assert!(needs_rescue(&[change(
+            "sshd.service",
+            ChangeAction::Disable
+        )]));
+        assert!(!needs_rescue(&[change(
+            "cups.service",
+            ChangeAction::Disable
+        )]));
+    }
+
+    #[test]
+    fn test_rescue_script_inverts_changes() {
+        let script = rescue_script(&[
+            change("sddm.service", ChangeAction::Enable),
+            change("gdm.service", ChangeAction::Disable),
+        ]);
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("sudo sh"));
+        assert!(script.contains(
+            "systemctl disable --now -- 'sddm.service'; systemctl enable --now -- 'gdm.service'"
+        ));
+    }
+}
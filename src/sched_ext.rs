@@ -0,0 +1,98 @@
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use crate::trace;
+
+/// The CachyOS loader service; switching goes through its D-Bus API.
+pub const LOADER_SERVICE: &str = "scx_loader.service";
+
+const STATE: &str = "/sys/kernel/sched_ext/state";
+const OPS: &str = "/sys/kernel/sched_ext/root/ops";
+
+/// Name of the sched-ext scheduler the kernel is running, if any.
+pub fn current() -> Option<String> {
+    let state = std::fs::read_to_string(STATE).ok()?;
+    if state.trim() != "enabled" {
+        return None;
+    }
+    let ops = std::fs::read_to_string(OPS).ok()?;
+    // The kernel reports "lavd_1.0.3_..."-style ops names; scx_loader uses "scx_lavd"
+    let name = ops.trim().split('_').next().filter(|n| !n.is_empty())?;
+    Some(format!("scx_{name}"))
+}
+
+fn loader_call(method: &str, args: &[&str]) -> Result<String> {
+    let mut cmd = Command::new("gdbus");
+    cmd.args([
+        "call",
+        "--system",
+        "--dest",
+        "org.scx.Loader",
+        "--object-path",
+        "/org/scx/Loader",
+        "--method",
+        method,
+    ])
+    .args(args);
+    let output = trace::output(&mut cmd).context("Failed to run gdbus")?;
+    if !output.status.success() {
+        bail!(
+            "scx_loader: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Schedulers scx_loader knows how to start.
+pub fn supported() -> Result<Vec<String>> {
+    let reply = loader_call(
+        "org.freedesktop.DBus.Properties.Get",
+        &["org.scx.Loader", "SupportedSchedulers"],
+    )?;
+    Ok(parse_string_array(&reply))
+}
+
+/// Ask scx_loader to switch (or start) `name` in its default mode. The
+/// loader persists nothing, so the choice lasts until it restarts.
+pub fn switch(name: &str) -> Result<()> {
+    if !name.starts_with("scx_") || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        bail!("Not a scheduler name: {name:?}");
+    }
+    // Mode 0 = Auto
+    loader_call("org.scx.Loader.SwitchScheduler", &[name, "0"]).map(|_| ())
+}
+
+/// `(<['scx_bpfland', 'scx_lavd']>,)` -> ["scx_bpfland", "scx_lavd"]
+fn parse_string_array(reply: &str) -> Vec<String> {
+    let Some(start) = reply.find('[') else {
+        return Vec::new();
+    };
+    let end = reply.rfind(']').unwrap_or(reply.len());
+    reply[start + 1..end]
+        .split(',')
+        .map(|item| item.trim().trim_matches('\'').to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_string_array() {
+        assert_eq!(
+            parse_string_array("(<['scx_bpfland', 'scx_lavd', 'scx_rusty']>,)\n"),
+            ["scx_bpfland", "scx_lavd", "scx_rusty"]
+        );
+        assert!(parse_string_array("(<@as []>,)\n").is_empty());
+    }
+
+    #[test]
+    fn test_switch_rejects_odd_names() {
+        assert!(switch("scx_lavd; reboot").is_err());
+        assert!(switch("lavd").is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use crate::systemd::{failed_units, machine, ServiceScope};
+
+/// Which status bar `comma-services status` prints for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Waybar,
+    I3blocks,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "waybar" => Ok(Format::Waybar),
+            "i3blocks" => Ok(Format::I3blocks),
+            _ => bail!("Unknown status format {name:?} (waybar or i3blocks)"),
+        }
+    }
+}
+
+/// What's worth a glance from the bar.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub failed: Vec<String>, // user units as `user/name`
+    pub reboot: bool,        // updates wait for a reboot to take effect
+}
+
+/// Failed units of the system and user managers (only the container's
+/// with `--machine`), and whether a reboot is pending here.
+pub fn collect() -> Summary {
+    let mut failed: Vec<String> = failed_units(&ServiceScope::System).into_iter().collect();
+    failed.sort();
+    if machine().is_none() {
+        let mut user: Vec<String> = failed_units(&ServiceScope::User)
+            .into_iter()
+            .map(|name| format!("user/{name}"))
+            .collect();
+        user.sort();
+        failed.extend(user);
+    }
+    Summary {
+        failed,
+        reboot: machine().is_none() && reboot_pending(),
+    }
+}
+
+/// Debian's update flag file, or (Arch and friends, which replace the
+/// running kernel's modules) no module directory for the running kernel
+/// among those installed. Containers have none at all, which says nothing.
+fn reboot_pending() -> bool {
+    if Path::new("/run/reboot-required").exists() {
+        return true;
+    }
+    let Ok(release) = std::fs::read_to_string("/proc/sys/kernel/osrelease") else {
+        return false;
+    };
+    let release = release.trim();
+    let dirs = ["/usr/lib/modules", "/lib/modules"].map(Path::new);
+    dirs.iter().any(|dir| dir.is_dir()) && !dirs.iter().any(|dir| dir.join(release).exists())
+}
+
+pub fn render(format: Format, summary: &Summary) -> String {
+    let mut parts = Vec::new();
+    if !summary.failed.is_empty() {
+        parts.push(format!("{} failed", summary.failed.len()));
+    }
+    if summary.reboot {
+        parts.push("reboot".to_string());
+    }
+    let text = parts.join(" · ");
+    match format {
+        // Empty text hides the module; class drives the bar's CSS
+        Format::Waybar => {
+            let class = match (summary.failed.is_empty(), summary.reboot) {
+                (false, _) => "failed",
+                (true, true) => "reboot",
+                (true, false) => "ok",
+            };
+            let mut tooltip: Vec<String> = summary.failed.clone();
+            if summary.reboot {
+                tooltip.push("Reboot pending".to_string());
+            }
+            let json = serde_json::json!({
+                "text": text,
+                "tooltip": tooltip.join("\n"),
+                "class": class,
+            });
+            format!("{json}\n")
+        }
+        // full_text, short_text, color
+        Format::I3blocks if text.is_empty() => String::new(),
+        Format::I3blocks => {
+            let color = if summary.failed.is_empty() {
+                "#E5C07B"
+            } else {
+                "#E06C75"
+            };
+            let short = match summary.failed.len() {
+                0 => "↻".to_string(),
+                n => format!("✗{n}"),
+            };
+            format!("{text}\n{short}\n{color}\n")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> Summary {
+        Summary {
+            failed: vec!["cups.service".into(), "user/syncthing.service".into()],
+            reboot: true,
+        }
+    }
+
+    #[test]
+    fn test_render_waybar() {
+        let line = render(Format::Waybar, &summary());
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["text"], "2 failed · reboot");
+        assert_eq!(json["class"], "failed");
+        assert_eq!(
+            json["tooltip"],
+            "cups.service\nuser/syncthing.service\nReboot pending"
+        );
+        let quiet = render(Format::Waybar, &Summary::default());
+        assert!(quiet.contains("\"text\":\"\""));
+    }
+
+    #[test]
+    fn test_render_i3blocks() {
+        assert_eq!(
+            render(Format::I3blocks, &summary()),
+            "2 failed · reboot\n✗2\n#E06C75\n"
+        );
+        assert_eq!(render(Format::I3blocks, &Summary::default()), "");
+    }
+}
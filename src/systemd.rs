@@ -1,16 +1,126 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
+use serde::Deserialize;
 use tokio::process::Command as AsyncCommand;
+use tokio::sync::mpsc;
 use tokio::time::timeout;
 
-const CMD_TIMEOUT: Duration = Duration::from_secs(10);
+use crate::categories::{is_display_manager, is_network_facing, snap_name};
+use crate::config::{state_dir, user_unit_dir};
+use crate::descriptions;
+use crate::trace;
+use crate::unit_cache;
+
+/// How long a systemctl call may take unless configured otherwise.
+pub const CMD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long each apply step may take and how often one that timed out is
+/// tried again (`timeout`, `timeout_retries` and `[timeouts]` in the
+/// config, `--timeout` on the command line).
+#[derive(Debug, Clone)]
+pub struct TimeoutPolicy {
+    pub timeout: Duration,
+    pub retries: u32,
+    pub per_unit: HashMap<String, Duration>, // for services known to be slow
+}
+
+impl Default for TimeoutPolicy {
+    fn default() -> Self {
+        TimeoutPolicy {
+            timeout: CMD_TIMEOUT,
+            retries: 0,
+            per_unit: HashMap::new(),
+        }
+    }
+}
+
+impl TimeoutPolicy {
+    fn for_unit(&self, unit: &str) -> Duration {
+        self.per_unit.get(unit).copied().unwrap_or(self.timeout)
+    }
+}
+
+static TIMEOUTS: OnceLock<TimeoutPolicy> = OnceLock::new();
+
+/// Set once at startup, before any apply.
+pub fn set_timeout_policy(policy: TimeoutPolicy) {
+    let _ = TIMEOUTS.set(policy);
+}
+
+fn timeouts() -> &'static TimeoutPolicy {
+    TIMEOUTS.get_or_init(TimeoutPolicy::default)
+}
+
+/// Container whose system manager every command talks to (`--machine`).
+static MACHINE: OnceLock<String> = OnceLock::new();
+
+/// Point every systemctl call at `name`'s systemd (`systemctl -M`). Only
+/// the system manager is reachable this way.
+pub fn set_machine(name: &str) -> Result<()> {
+    if !valid_machine_name(name) {
+        anyhow::bail!("Not a machine name: {name:?}");
+    }
+    let _ = MACHINE.set(name.to_string());
+    Ok(())
+}
+
+/// machined's rules: a hostname-like label, which also keeps it out of
+/// the shell scripts `changes_script` builds.
+fn valid_machine_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && !name.starts_with(['-', '.'])
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+}
+
+/// The container being managed, if not this host.
+pub fn machine() -> Option<&'static str> {
+    MACHINE.get().map(String::as_str)
+}
+
+/// `-M <name>` in container mode, placed right after `systemctl`.
+fn machine_args() -> Vec<&'static str> {
+    machine().map(|name| vec!["-M", name]).unwrap_or_default()
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ServiceScope {
     System,
     User,
+    Global, // user units enabled for every account (`systemctl --global`)
+}
+
+impl ServiceScope {
+    /// As written in exports and profiles.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServiceScope::System => "system",
+            ServiceScope::User => "user",
+            ServiceScope::Global => "global",
+        }
+    }
+
+    /// Changes here go through pkexec.
+    pub fn needs_root(&self) -> bool {
+        matches!(self, ServiceScope::System | ServiceScope::Global)
+    }
+
+    /// The running manager that loads this scope's units. There is no
+    /// global manager; each user's manager reads /etc/systemd/user.
+    fn manager(&self) -> ServiceScope {
+        match self {
+            ServiceScope::System => ServiceScope::System,
+            ServiceScope::User | ServiceScope::Global => ServiceScope::User,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -18,75 +128,280 @@ pub struct Service {
     pub name: String,
     pub enabled: bool,
     pub active: bool,
+    pub enabled_globally: bool, // user unit enabled in /etc/systemd/user
+    pub generated: bool,        // made by a generator; read-only
+    pub masked: bool,           // linked to /dev/null; can't be started at all
+    pub preset: Option<bool>,   // what the preset files say, if they mention it
+}
+
+impl Service {
+    /// Enablement (as staged) disagrees with the preset files. A masked
+    /// unit was masked on purpose, so it's left out.
+    pub fn differs_from_preset(&self) -> bool {
+        !self.masked && self.preset.is_some_and(|preset| preset != self.enabled)
+    }
 }
 
 pub fn list_services(scope: &ServiceScope) -> Result<Vec<Service>> {
-    // Get unit-file states (enabled/disabled)
-    let mut cmd = Command::new("systemctl");
+    let mut services = list_services_on(scope, None)?;
+
+    // `--user list-unit-files` folds global links into plain "enabled"
     if *scope == ServiceScope::User {
-        cmd.arg("--user");
+        let global: HashSet<String> = list_services_on(&ServiceScope::Global, None)?
+            .into_iter()
+            .filter(|svc| svc.enabled)
+            .map(|svc| svc.name)
+            .collect();
+        for svc in &mut services {
+            svc.enabled_globally = global.contains(&svc.name);
+        }
     }
+    Ok(services)
+}
+
+/// List services on this machine, or on `host` over SSH (`systemctl -H`).
+pub fn list_services_on(scope: &ServiceScope, host: Option<&str>) -> Result<Vec<Service>> {
+    // Get unit-file states (enabled/disabled)
+    let mut cmd = systemctl(scope, host);
+    // Sockets are listed with services: disabling a socket-activated
+    // service is only half the job
     cmd.args([
         "list-unit-files",
-        "--type=service",
+        "--type=service,socket",
         "--no-pager",
         "--no-legend",
     ]);
 
-    let output = cmd.output().context("Failed to run systemctl")?;
+    let output = trace::output(&mut cmd).context("Failed to run systemctl")?;
+    if host.is_some() && !output.status.success() {
+        anyhow::bail!(
+            "systemctl -H failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
     let stdout = String::from_utf8_lossy(&output.stdout);
 
-    // Get active/running states
-    let active_set = get_active_services(scope);
+    // Get active/running states (nothing runs "globally")
+    let active_set = match scope {
+        ServiceScope::Global => HashSet::new(),
+        _ => get_active_units(scope, host, "service,socket"),
+    };
+
+    Ok(parse_unit_files(&stdout, &active_set))
+}
+
+/// Units written by generators at boot (fstab mounts, SysV wrappers).
+/// They have no [Install] section of their own, so they're listed read-only.
+pub fn list_generated(scope: &ServiceScope) -> Vec<Service> {
+    let mut cmd = systemctl(scope, None);
+    cmd.args([
+        "list-unit-files",
+        "--type=service,mount",
+        "--state=generated",
+        "--no-pager",
+        "--no-legend",
+    ]);
+    let Ok(output) = trace::output(&mut cmd) else {
+        return Vec::new();
+    };
+    let active_set = get_active_units(scope, None, "service");
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|name| Service {
+            name: name.to_string(),
+            enabled: false,
+            active: active_set.contains(name),
+            enabled_globally: false,
+            generated: true,
+            masked: false,
+            preset: None,
+        })
+        .collect()
+}
+
+/// Enablement of every toggleable socket and timer, keyed by unit name,
+/// so a service can be matched with its same-named trigger.
+pub fn list_triggers(scope: &ServiceScope) -> HashMap<String, bool> {
+    let mut cmd = systemctl(scope, None);
+    cmd.args([
+        "list-unit-files",
+        "--type=socket,timer",
+        "--no-pager",
+        "--no-legend",
+    ]);
+    let Ok(output) = trace::output(&mut cmd) else {
+        return HashMap::new();
+    };
+    parse_unit_files(&String::from_utf8_lossy(&output.stdout), &HashSet::new())
+        .into_iter()
+        .map(|unit| (unit.name, unit.enabled))
+        .collect()
+}
+
+/// Installed timers, shaped like services so the Timers tab can stage and
+/// apply them the same way (enable --now starts the schedule).
+pub fn list_timers(scope: &ServiceScope) -> Result<Vec<Service>> {
+    let mut cmd = systemctl(scope, None);
+    cmd.args([
+        "list-unit-files",
+        "--type=timer",
+        "--no-pager",
+        "--no-legend",
+    ]);
+    let output = trace::output(&mut cmd).context("Failed to run systemctl")?;
+    let active_set = get_active_units(scope, None, "timer");
+    Ok(parse_unit_files(
+        &String::from_utf8_lossy(&output.stdout),
+        &active_set,
+    ))
+}
+
+/// When a timer last fired and fires next, in unix seconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimerTimes {
+    pub next: Option<u64>,
+    pub last: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ListTimersRow {
+    unit: String,
+    next: Option<u64>, // microseconds; null when nothing is scheduled
+    last: Option<u64>,
+}
+
+pub fn timer_times(scope: &ServiceScope) -> HashMap<String, TimerTimes> {
+    let mut cmd = systemctl(scope, None);
+    cmd.args(["list-timers", "--all", "--output=json", "--no-pager"]);
+    match trace::output(&mut cmd) {
+        Ok(output) => parse_list_timers(&String::from_utf8_lossy(&output.stdout)),
+        Err(_) => HashMap::new(),
+    }
+}
+
+fn parse_list_timers(json: &str) -> HashMap<String, TimerTimes> {
+    let rows: Vec<ListTimersRow> = serde_json::from_str(json).unwrap_or_default();
+    let secs = |usec: Option<u64>| usec.filter(|&u| u > 0).map(|u| u / 1_000_000);
+    rows.into_iter()
+        .map(|row| {
+            let times = TimerTimes {
+                next: secs(row.next),
+                last: secs(row.last),
+            };
+            (row.unit, times)
+        })
+        .collect()
+}
+
+/// `cups.service` -> `cups.socket` (or `.timer`) if one is installed.
+pub fn paired_trigger(service: &str, triggers: &HashMap<String, bool>) -> Option<String> {
+    let stem = service.strip_suffix(".service")?;
+    ["socket", "timer"]
+        .iter()
+        .map(|kind| format!("{stem}.{kind}"))
+        .find(|unit| triggers.contains_key(unit))
+}
+
+fn systemctl(scope: &ServiceScope, host: Option<&str>) -> Command {
+    let mut cmd = Command::new("systemctl");
+    cmd.args(machine_args());
+    match scope {
+        ServiceScope::System => {}
+        ServiceScope::User => {
+            cmd.arg("--user");
+        }
+        ServiceScope::Global => {
+            cmd.arg("--global");
+        }
+    }
+    if let Some(host) = host {
+        cmd.args(["-H", host]);
+    }
+    cmd
+}
 
-    let services = stdout
+fn parse_unit_files(stdout: &str, active_set: &HashSet<String>) -> Vec<Service> {
+    stdout
         .lines()
         .filter_map(|line| {
             let parts: Vec<&str> = line.split_whitespace().collect();
             if parts.len() >= 2 {
                 let name = parts[0].to_string();
                 let state = parts[1];
-                // Only include services that can be manually enabled/disabled.
-                // Skip static, generated, alias, transient, indirect, masked.
+                // Only include services that can be manually enabled/disabled,
+                // plus masked ones so they can be unmasked.
+                // Skip static, generated, alias, transient, indirect.
                 let toggleable = matches!(
                     state,
-                    "enabled" | "enabled-runtime" | "disabled" | "linked" | "linked-runtime"
+                    "enabled"
+                        | "enabled-runtime"
+                        | "disabled"
+                        | "linked"
+                        | "linked-runtime"
+                        | "masked"
+                        | "masked-runtime"
                 );
                 if !toggleable {
                     return None;
                 }
+                let masked = state.starts_with("masked");
                 let enabled = matches!(state, "enabled" | "enabled-runtime" | "linked");
                 let active = active_set.contains(&name);
+                // Third column (systemd 245+); "ignored" and "-" mean no opinion
+                let preset = match parts.get(2) {
+                    Some(&"enabled") => Some(true),
+                    Some(&"disabled") => Some(false),
+                    _ => None,
+                };
                 Some(Service {
                     name,
                     enabled,
                     active,
+                    enabled_globally: false,
+                    generated: false,
+                    masked,
+                    preset,
                 })
             } else {
                 None
             }
         })
-        .collect();
+        .collect()
+}
 
-    Ok(services)
+fn get_active_units(scope: &ServiceScope, host: Option<&str>, kind: &str) -> HashSet<String> {
+    units_in_state(scope, host, kind, "active")
 }
 
-fn get_active_services(scope: &ServiceScope) -> std::collections::HashSet<String> {
-    let mut cmd = Command::new("systemctl");
-    if *scope == ServiceScope::User {
-        cmd.arg("--user");
+/// Services and sockets whose last run failed (ActiveState=failed).
+pub fn failed_units(scope: &ServiceScope) -> HashSet<String> {
+    match scope {
+        ServiceScope::Global => HashSet::new(),
+        _ => units_in_state(scope, None, "service,socket,timer", "failed"),
     }
+}
+
+fn units_in_state(
+    scope: &ServiceScope,
+    host: Option<&str>,
+    kind: &str,
+    state: &str,
+) -> HashSet<String> {
+    let mut cmd = systemctl(scope, host);
     cmd.args([
         "list-units",
-        "--type=service",
-        "--state=active",
+        &format!("--type={kind}"),
+        &format!("--state={state}"),
         "--no-pager",
         "--no-legend",
+        "--plain", // no "●" status column before the name
     ]);
 
-    let output = match cmd.output() {
+    let output = match trace::output(&mut cmd) {
         Ok(o) => o,
-        Err(_) => return std::collections::HashSet::new(),
+        Err(_) => return HashSet::new(),
     };
 
     String::from_utf8_lossy(&output.stdout)
@@ -95,312 +410,2337 @@ fn get_active_services(scope: &ServiceScope) -> std::collections::HashSet<String
         .collect()
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct ServiceInfo {
-    pub description: String,
-    pub active_state: String,
-    pub sub_state: String,
-    pub fragment_path: String,
-    pub triggered_by: String,
-    pub documentation: String,
-    pub extra_info: String,
+/// Make systemd pick up new or edited unit files.
+pub fn daemon_reload(scope: &ServiceScope) -> Result<()> {
+    unit_cache::clear();
+    let scope = &scope.manager();
+    let mut cmd = match scope {
+        ServiceScope::User | ServiceScope::Global => systemctl(scope, None),
+        ServiceScope::System => {
+            let mut c = Command::new("pkexec");
+            c.arg("systemctl").args(machine_args());
+            c
+        }
+    };
+    cmd.arg("daemon-reload");
+
+    let output = trace::output(&mut cmd).context("Failed to run systemctl daemon-reload")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "daemon-reload failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
 }
 
-pub fn get_service_info(scope: &ServiceScope, service: &str) -> ServiceInfo {
-    let is_template = service.contains('@');
+/// `enable --now` one unit right away, outside any staged apply; for units
+/// we just created in the user's own manager.
+pub fn enable_now(scope: &ServiceScope, unit: &str) -> Result<()> {
+    let mut cmd = systemctl(scope, None);
+    cmd.args(["enable", "--now", "--", unit]);
+    let output = trace::output(&mut cmd).context("Failed to run systemctl enable")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "enable {unit} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
 
-    // For template units, try instantiated form or fall back to systemctl cat
-    let mut info = if is_template {
-        get_info_from_cat(scope, service)
-    } else {
-        get_info_from_show(scope, service)
-    };
+/// Which of `names` run as root (no User=, no DynamicUser=), from one
+/// `systemctl show` call.
+pub fn root_services(scope: &ServiceScope, names: &[String]) -> HashSet<String> {
+    let blocks = unit_cache::show(scope, names, &["User", "DynamicUser"], |names, props| {
+        show_properties(scope, names, props)
+    });
+    parse_root_services(&blocks.join("\n"))
+}
 
-    // Enrich with curated descriptions when systemd's own description is generic
-    if let Some(extra) = curated_description(service) {
-        info.extra_info = extra.to_string();
+/// One `systemctl show` for several units; blocks are separated by blank lines.
+fn show_properties(scope: &ServiceScope, names: &[String], props: &str) -> String {
+    let mut cmd = systemctl(scope, None);
+    cmd.arg("show")
+        .arg(format!("--property={props}"))
+        .args(["--no-pager", "--"])
+        .args(names);
+    trace::output(&mut cmd)
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .unwrap_or_default()
+}
+
+/// What a unit pulls in or pushes out, for the dependency graph export.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Requirements {
+    pub unit: String,
+    pub requires: Vec<String>, // Requires=, Requisite= and BindsTo=
+    pub wants: Vec<String>,
+    pub conflicts: Vec<String>,
+}
+
+/// The requirement dependencies of `names`, from one `systemctl show`.
+pub fn requirements(scope: &ServiceScope, names: &[String]) -> Vec<Requirements> {
+    if names.is_empty() {
+        return Vec::new();
     }
+    let props = "Id,Requires,Requisite,BindsTo,Wants,Conflicts";
+    parse_requirements(&show_properties(scope, names, props))
+}
 
-    info
+fn parse_requirements(stdout: &str) -> Vec<Requirements> {
+    stdout
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut req = Requirements::default();
+            for line in block.lines() {
+                let Some((key, value)) = line.split_once('=') else {
+                    continue;
+                };
+                let units = value.split_whitespace().map(String::from);
+                match key {
+                    "Id" => req.unit = value.to_string(),
+                    "Requires" | "Requisite" | "BindsTo" => req.requires.extend(units),
+                    "Wants" => req.wants.extend(units),
+                    // Every unit with default dependencies conflicts with it
+                    "Conflicts" => req
+                        .conflicts
+                        .extend(units.filter(|unit| unit != "shutdown.target")),
+                    _ => {}
+                }
+            }
+            (!req.unit.is_empty()).then_some(req)
+        })
+        .collect()
 }
 
-fn get_info_from_show(scope: &ServiceScope, service: &str) -> ServiceInfo {
-    let mut cmd = Command::new("systemctl");
-    if *scope == ServiceScope::User {
-        cmd.arg("--user");
+fn parse_root_services(stdout: &str) -> HashSet<String> {
+    stdout
+        .split("\n\n")
+        .map(parse_show_block)
+        .filter(|(_, info)| info.runs_as_root())
+        .filter_map(|(id, _)| id)
+        .collect()
+}
+
+/// When each timer-triggered service next runs (unix seconds), from the
+/// loaded timers' Triggers= and NextElapseUSecRealtime=.
+pub fn next_timer_runs(scope: &ServiceScope) -> HashMap<String, u64> {
+    if *scope == ServiceScope::Global {
+        return HashMap::new();
     }
+    let mut cmd = systemctl(scope, None);
     cmd.args([
         "show",
-        service,
-        "-p",
-        "Description,ActiveState,SubState,FragmentPath,TriggeredBy,Documentation",
+        "--timestamp=unix",
+        "--property=Triggers,NextElapseUSecRealtime",
         "--no-pager",
+        "--",
+        "*.timer",
     ]);
+    match trace::output(&mut cmd) {
+        Ok(output) => parse_timer_runs(&String::from_utf8_lossy(&output.stdout)),
+        Err(_) => HashMap::new(),
+    }
+}
 
-    let output = match cmd.output() {
-        Ok(o) => o,
-        Err(_) => return ServiceInfo::default(),
-    };
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut info = ServiceInfo::default();
-
-    for line in stdout.lines() {
-        if let Some((key, value)) = line.split_once('=') {
-            match key {
-                "Description" => info.description = value.to_string(),
-                "ActiveState" => info.active_state = value.to_string(),
-                "SubState" => info.sub_state = value.to_string(),
-                "FragmentPath" => info.fragment_path = value.to_string(),
-                "TriggeredBy" => info.triggered_by = value.to_string(),
-                "Documentation" => info.documentation = value.to_string(),
-                _ => {}
-            }
+fn parse_timer_runs(stdout: &str) -> HashMap<String, u64> {
+    let mut runs = HashMap::new();
+    for block in stdout.split("\n\n") {
+        let prop = |key: &str| {
+            block
+                .lines()
+                .find_map(|l| l.strip_prefix(key)?.strip_prefix('='))
+        };
+        // Empty for timers with nothing scheduled (e.g. inactive ones)
+        let Some(next) = prop("NextElapseUSecRealtime")
+            .and_then(|v| v.strip_prefix('@'))
+            .and_then(|v| v.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        for unit in prop("Triggers").unwrap_or("").split_whitespace() {
+            // Two timers on one service: the sooner one is what users see
+            let entry = runs.entry(unit.to_string()).or_insert(next);
+            *entry = (*entry).min(next);
         }
     }
-
-    info
+    runs
 }
 
-fn get_info_from_cat(scope: &ServiceScope, service: &str) -> ServiceInfo {
-    let mut cmd = Command::new("systemctl");
-    if *scope == ServiceScope::User {
-        cmd.arg("--user");
+/// Rough time until `at`: "in 3h 20min", "in 2d 4h", "now".
+pub fn format_until(at: u64, now: u64) -> String {
+    match rough_span(at.saturating_sub(now)) {
+        Some(span) => format!("in {span}"),
+        None => "now".to_string(),
     }
-    cmd.args(["cat", service, "--no-pager"]);
+}
 
-    let output = match cmd.output() {
-        Ok(o) if o.status.success() => o,
-        _ => return ServiceInfo::default(),
-    };
+/// Rough time since `at`: "3h 20min ago", "just now".
+pub fn format_ago(at: u64, now: u64) -> String {
+    match rough_span(now.saturating_sub(at)) {
+        Some(span) => format!("{span} ago"),
+        None => "just now".to_string(),
+    }
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut info = ServiceInfo::default();
+/// Two most significant units; None under a minute.
+fn rough_span(secs: u64) -> Option<String> {
+    let (days, hours, mins) = (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60);
+    match (days, hours, mins) {
+        (0, 0, 0) => None,
+        (0, 0, m) => Some(format!("{m}min")),
+        (0, h, 0) => Some(format!("{h}h")),
+        (0, h, m) => Some(format!("{h}h {m}min")),
+        (d, 0, _) => Some(format!("{d}d")),
+        (d, h, _) => Some(format!("{d}d {h}h")),
+    }
+}
 
-    for line in stdout.lines() {
-        let trimmed = line.trim();
-        if let Some(val) = trimmed.strip_prefix("Description=") {
-            info.description = val.to_string();
-        } else if let Some(val) = trimmed.strip_prefix("Documentation=") {
-            info.documentation = val.to_string();
-        } else if trimmed.starts_with("# /") {
-            info.fragment_path = trimmed.trim_start_matches("# ").to_string();
-        }
+/// Total MemoryCurrent of `names`, in bytes: roughly what stopping them frees.
+pub fn memory_usage(scope: &ServiceScope, names: &[String]) -> u64 {
+    if names.is_empty() || *scope == ServiceScope::Global {
+        return 0;
+    }
+    let mut cmd = systemctl(scope, None);
+    cmd.args(["show", "--property=MemoryCurrent", "--no-pager", "--"])
+        .args(names);
+    match trace::output(&mut cmd) {
+        Ok(output) => parse_memory_current(&String::from_utf8_lossy(&output.stdout)),
+        Err(_) => 0,
     }
+}
 
-    // Template units aren't running instances, so state isn't meaningful
-    info.active_state = "template".to_string();
-    info.sub_state = "n/a".to_string();
+/// MemoryCurrent of each of `names` that has one (running, with memory
+/// accounting on), in bytes.
+pub fn memory_by_unit(scope: &ServiceScope, names: &[String]) -> Vec<(String, u64)> {
+    if names.is_empty() || *scope == ServiceScope::Global {
+        return Vec::new();
+    }
+    parse_memory_by_unit(&show_properties(scope, names, "Id,MemoryCurrent"))
+}
 
-    info
+fn parse_memory_by_unit(stdout: &str) -> Vec<(String, u64)> {
+    stdout
+        .split("\n\n")
+        .filter_map(|block| {
+            let prop = |key: &str| {
+                block
+                    .lines()
+                    .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            };
+            let bytes = prop("MemoryCurrent")?.parse::<u64>().ok()?;
+            let id = prop("Id")?;
+            (bytes != u64::MAX).then(|| (id.to_string(), bytes))
+        })
+        .collect()
 }
 
-fn curated_description(service: &str) -> Option<&'static str> {
-    let name = service.trim_end_matches(".service");
-    // Strip template suffix for matching (e.g., "ly@" -> "ly")
-    let base = name.split('@').next().unwrap_or(name);
-
-    match base {
-        // Display managers
-        "gdm" => Some("GNOME Display Manager. Provides graphical login screen and manages user sessions. Handles X11/Wayland session startup."),
-        "sddm" => Some("Simple Desktop Display Manager. Qt-based login screen, commonly used with KDE Plasma."),
-        "lightdm" => Some("Lightweight Display Manager. Cross-desktop login screen supporting multiple greeters."),
-        "ly" => Some("Lightweight TUI display manager. Provides a terminal-based login screen as an alternative to graphical display managers."),
-        "greetd" => Some("Minimal login daemon. Supports pluggable greeter frontends (tuigreet, gtkgreet, etc.)."),
-
-        // Network
-        "NetworkManager" => Some("Desktop network management daemon. Manages WiFi, Ethernet, VPN, and mobile broadband connections. Provides nm-applet tray icon."),
-        "NetworkManager-dispatcher" => Some("Runs scripts in response to network events (connect/disconnect). Scripts live in /etc/NetworkManager/dispatcher.d/."),
-        "NetworkManager-wait-online" => Some("Blocks boot until network is fully connected. Needed by services requiring network at startup. Can slow boot if network is slow."),
-        "systemd-networkd" => Some("Systemd's built-in network manager. Lighter alternative to NetworkManager, configured via .network files in /etc/systemd/network/."),
-        "systemd-resolved" => Some("Systemd DNS resolver. Provides DNS caching, DNSSEC validation, and DNS-over-TLS. Manages /etc/resolv.conf."),
-        "wpa_supplicant" => Some("WiFi authentication daemon (WPA/WPA2/WPA3). Usually managed by NetworkManager, but can run standalone for simpler setups."),
-        "iwd" => Some("Intel Wireless Daemon. Modern alternative to wpa_supplicant with simpler config. Can be used as NetworkManager's WiFi backend."),
-
-        // Audio
-        "pipewire" => Some("Modern audio/video server replacing PulseAudio and JACK. Handles screen sharing, Bluetooth audio, and low-latency audio."),
-        "wireplumber" => Some("Session manager for PipeWire. Handles audio routing policy, device management, and Bluetooth audio profiles."),
-        "pulseaudio" => Some("Legacy audio server. Being replaced by PipeWire on most modern Linux desktops."),
-
-        // Bluetooth
-        "bluetooth" => Some("BlueZ Bluetooth daemon. Manages Bluetooth device pairing, connections, and profiles (A2DP, HFP, etc.)."),
-        "blueman-mechanism" => Some("Blueman privilege helper. Allows the Blueman Bluetooth manager applet to perform system-level Bluetooth operations."),
-
-        // Printing
-        "cups" => Some("Common Unix Printing System. Manages print queues, printer discovery (via Avahi/mDNS), and IPP printing. Web UI at localhost:631."),
-        "avahi-daemon" => Some("mDNS/DNS-SD daemon for zero-configuration networking. Enables .local hostname resolution and network service discovery (printers, etc.)."),
-        "avahi-dnsconfd" => Some("Configures DNS servers discovered via Avahi. Rarely needed if using NetworkManager or systemd-resolved."),
-
-        // Security / Firewall
-        "sshd" => Some("OpenSSH server daemon. Accepts incoming SSH connections for remote shell access, file transfer (scp/sftp), and tunneling."),
-        "ufw" => Some("Uncomplicated Firewall. User-friendly frontend for iptables/nftables. Manages incoming/outgoing traffic rules."),
-        "firewalld" => Some("Dynamic firewall daemon with zones. Uses nftables backend. Supports runtime changes without restarting."),
-        "nftables" => Some("Netfilter tables. Modern kernel packet filtering framework replacing iptables. Rules in /etc/nftables.conf."),
-        "apparmor" => Some("Mandatory Access Control security framework. Confines programs to limited resources using per-program profiles."),
-        "auditd" => Some("Linux Audit daemon. Logs security-relevant events (file access, syscalls, authentication) per configured rules."),
-        "fail2ban" => Some("Intrusion prevention. Monitors log files and bans IPs showing malicious signs (brute-force SSH, etc.) via firewall rules."),
-
-        // Power / Hardware
-        "upower" => Some("Power management abstraction. Provides battery info, suspend/hibernate support. Used by desktop environments for power status."),
-        "power-profiles-daemon" => Some("Provides power profile switching (balanced, power-saver, performance). Used by GNOME/KDE power settings."),
-        "cpupower" => Some("CPU frequency scaling. Sets CPU governor (performance/powersave/schedutil) at boot. Config in /etc/default/cpupower."),
-        "lm_sensors" => Some("Hardware monitoring. Reads CPU/GPU temperatures, fan speeds, and voltages from sensor chips."),
-        "smartd" => Some("S.M.A.R.T. disk monitoring daemon. Watches hard drive health indicators and warns of impending failures."),
-        "fancontrol" => Some("Fan speed control daemon. Uses lm_sensors data to dynamically adjust fan speeds based on temperature."),
-
-        // Containers
-        "docker" => Some("Docker container runtime. Manages container images, networks, and volumes. API on /var/run/docker.sock."),
-        "podman" => Some("Daemonless container engine. Docker-compatible CLI but runs rootless by default. No persistent daemon needed."),
-        "containerd" => Some("Container runtime daemon. Low-level container execution used by Docker and Kubernetes."),
-
-        // Systemd core
-        "systemd-timesyncd" => Some("Simple NTP client. Synchronizes system clock with network time servers. Lighter alternative to chrony/ntpd."),
-        "systemd-oomd" => Some("Out-of-memory daemon. Monitors memory pressure and kills cgroup trees before the kernel OOM killer triggers."),
-        "systemd-homed" => Some("Portable home directory manager. Stores home dirs as LUKS-encrypted images that can move between machines."),
-        "systemd-boot-update" => Some("Automatically updates systemd-boot EFI bootloader when systemd is upgraded."),
-        "systemd-pstore" => Some("Persistent storage for kernel crash dumps. Copies pstore data (dmesg, etc.) from /sys/fs/pstore to /var/lib/systemd/pstore."),
-
-        // Misc system services
-        "accounts-daemon" => Some("D-Bus service for user account management. Used by GDM and GNOME Settings for user info, avatar, and language preferences."),
-        "rtkit-daemon" => Some("RealtimeKit. Safely grants realtime scheduling priority to user processes (PipeWire, audio apps) without running them as root."),
-        "udisks2" => Some("Disk management daemon. Provides D-Bus API for mounting/unmounting drives, used by file managers for removable media."),
-        "ModemManager" => Some("Mobile broadband modem management. Controls 3G/4G/5G modems and provides connection setup. Safe to disable without mobile broadband."),
-        "haveged" => Some("Entropy harvesting daemon. Feeds additional randomness to /dev/random. Less needed on modern kernels with good entropy sources."),
-        "gpm" => Some("General Purpose Mouse. Provides mouse support in Linux virtual consoles (TTY). Not needed in graphical environments."),
-        "reflector" => Some("Arch Linux mirrorlist updater. Fetches latest mirror list and sorts by speed/country. Usually run via timer, not continuously."),
-
-        // Arch / CachyOS specific
-        "ananicy-cpp" => Some("Auto Nice Daemon (C++ rewrite). Automatically adjusts process priorities and I/O scheduling for better desktop responsiveness."),
-        "cachyos-rate-mirrors" => Some("CachyOS mirror rating. Tests and sorts pacman mirrors by speed for faster package downloads."),
-        "scx_loader" => Some("Sched-ext loader. Loads custom Linux CPU schedulers (BORE, Rusty, etc.) for CachyOS's optimized scheduling."),
-
-        // Session
-        "seatd" => Some("Minimal seat management daemon. Provides unprivileged access to input/display devices for Wayland compositors (Sway, etc.)."),
-
-        // VPN / Networking extras
-        "openvpn-client" | "openvpn-server" => Some("OpenVPN tunnel. Template unit — instantiate with config name (e.g., openvpn-client@myconfig)."),
-        "dnsmasq" => Some("Lightweight DNS forwarder and DHCP server. Often used for local DNS caching, network boot (PXE), or VM networking."),
-        "nextdns" => Some("NextDNS CLI client. Routes DNS queries through NextDNS for ad-blocking, tracking protection, and security filtering."),
+fn parse_memory_current(stdout: &str) -> u64 {
+    stdout
+        .lines()
+        .filter_map(|l| l.strip_prefix("MemoryCurrent="))
+        // "[not set]" when stopped; u64::MAX when accounting is off
+        .filter_map(|v| v.parse::<u64>().ok())
+        .filter(|&bytes| bytes != u64::MAX)
+        .sum()
+}
 
-        _ => None,
+/// "412 MiB", "1.3 GiB"
+pub fn format_bytes(bytes: u64) -> String {
+    const MIB: u64 = 1024 * 1024;
+    if bytes >= 1024 * MIB {
+        format!("{:.1} GiB", bytes as f64 / (1024 * MIB) as f64)
+    } else {
+        format!("{} MiB", bytes.div_ceil(MIB))
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum ChangeAction {
-    Enable,
-    Disable,
+const GRAPHICAL_SESSION: &str = "graphical-session.target";
+
+/// Which of `names` only run inside a graphical session: they're tied to
+/// graphical-session.target, which a desktop login starts and SSH doesn't.
+pub fn graphical_session_services(names: &[String]) -> HashSet<String> {
+    let scope = ServiceScope::User;
+    let props = ["PartOf", "BindsTo", "Requisite", "WantedBy"];
+    unit_cache::show(&scope, names, &props, |names, props| {
+        show_properties(&scope, names, props)
+    })
+    .iter()
+    .map(|block| parse_show_block(block))
+    .filter(|(_, info)| info.graphical_session)
+    .filter_map(|(id, _)| id)
+    .collect()
 }
 
-#[derive(Debug, Clone)]
-pub struct PendingChange {
-    pub service: String,
-    pub scope: ServiceScope,
-    pub action: ChangeAction,
+pub fn graphical_session_active() -> bool {
+    let mut cmd = systemctl(&ServiceScope::User, None);
+    cmd.args(["is-active", "--quiet", GRAPHICAL_SESSION]);
+    trace::output(&mut cmd).is_ok_and(|output| output.status.success())
 }
 
-#[derive(Debug)]
-pub struct ChangeResult {
-    pub service: String,
-    pub success: bool,
-    pub message: String,
+/// One `show` block: the unit's Id plus whatever properties were asked for.
+fn parse_show_block(block: &str) -> (Option<String>, ServiceInfo) {
+    let id = block
+        .lines()
+        .find_map(|l| l.strip_prefix("Id="))
+        .map(String::from);
+    (id, parse_show(block))
 }
 
-/// Apply changes using async commands with a timeout per command.
-/// Separates enable/disable from start/stop so the enable always succeeds
-/// even if the service is slow to start.
-pub async fn apply_changes(changes: Vec<PendingChange>) -> Vec<ChangeResult> {
-    let mut results = Vec::new();
+/// Loaded services whose unit files changed on disk since the last
+/// daemon-reload (`NeedDaemonReload=yes`).
+pub fn units_needing_reload(scope: &ServiceScope) -> Vec<String> {
+    let mut cmd = systemctl(&scope.manager(), None);
+    cmd.args([
+        "show",
+        "--property=Id,NeedDaemonReload",
+        "--no-pager",
+        "*.service",
+    ]);
+    match trace::output(&mut cmd) {
+        Ok(output) => parse_need_reload(&String::from_utf8_lossy(&output.stdout)),
+        Err(_) => Vec::new(),
+    }
+}
 
-    for change in &changes {
-        let (enable_action, start_action) = match change.action {
-            ChangeAction::Enable => ("enable", "start"),
-            ChangeAction::Disable => ("disable", "stop"),
-        };
+/// `show` prints one property block per unit, separated by blank lines.
+fn parse_need_reload(stdout: &str) -> Vec<String> {
+    stdout
+        .split("\n\n")
+        .filter(|block| block.lines().any(|l| l == "NeedDaemonReload=yes"))
+        .filter_map(|block| block.lines().find_map(|l| l.strip_prefix("Id=")))
+        .map(String::from)
+        .collect()
+}
 
-        // Step 1: enable/disable (should be instant)
-        let enable_result = run_systemctl(&change.scope, enable_action, &change.service).await;
-        match enable_result {
-            Ok(output) if output.status.success() => {
-                // Step 2: start/stop (might be slow, use timeout)
-                let start_result =
-                    run_systemctl(&change.scope, start_action, &change.service).await;
-                match start_result {
-                    Ok(output) if output.status.success() => {
-                        results.push(ChangeResult {
-                            service: change.service.clone(),
-                            success: true,
-                            message: format!("{}d and {}ed", enable_action, start_action),
-                        });
-                    }
-                    Ok(output) => {
-                        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-                        results.push(ChangeResult {
-                            service: change.service.clone(),
-                            success: false,
-                            message: format!(
-                                "{}d but {} failed: {}",
-                                enable_action, start_action, stderr
-                            ),
-                        });
-                    }
-                    Err(e) => {
-                        results.push(ChangeResult {
-                            service: change.service.clone(),
-                            success: false,
-                            message: format!(
-                                "{}d but {} timed out: {}",
-                                enable_action, start_action, e
-                            ),
-                        });
-                    }
-                }
-            }
-            Ok(output) => {
-                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-                results.push(ChangeResult {
-                    service: change.service.clone(),
-                    success: false,
-                    message: format!("{} failed: {}", enable_action, stderr),
-                });
+/// Reverse systemd-escape's `\xNN` encoding for display, e.g. the
+/// instance of `systemd-fsck@dev-disk-by\x2duuid-1a2b.service`.
+/// Unit names passed to systemctl always stay escaped.
+pub fn unescape(name: &str) -> String {
+    let bytes = name.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 2..i + 4)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], bytes.get(i + 1), hex) {
+            (b'\\', Some(b'x'), Some(byte)) => {
+                out.push(byte);
+                i += 4;
             }
-            Err(e) => {
-                results.push(ChangeResult {
-                    service: change.service.clone(),
-                    success: false,
-                    message: format!("{} timed out: {}", enable_action, e),
-                });
+            (byte, _, _) => {
+                out.push(byte);
+                i += 1;
             }
         }
     }
+    String::from_utf8_lossy(&out).into_owned()
+}
 
-    results
+/// The unescaped instance of a templated unit (`getty@tty1.service` → `tty1`).
+pub fn instance_name(service: &str) -> Option<String> {
+    let (_, rest) = service.split_once('@')?;
+    let instance = rest.rsplit_once('.').map_or(rest, |(inst, _)| inst);
+    (!instance.is_empty()).then(|| unescape(instance))
 }
 
-async fn run_systemctl(
-    scope: &ServiceScope,
-    action: &str,
-    service: &str,
-) -> Result<std::process::Output, String> {
-    let mut cmd = match scope {
-        ServiceScope::User => {
-            let mut c = AsyncCommand::new("systemctl");
-            c.args(["--user", action, service]);
-            c
-        }
-        ServiceScope::System => {
-            let mut c = AsyncCommand::new("pkexec");
-            c.args(["systemctl", action, service]);
+/// Where locally created or copied unit files go.
+pub fn unit_dir(scope: &ServiceScope) -> Option<PathBuf> {
+    match scope {
+        ServiceScope::System => Some(PathBuf::from("/etc/systemd/system")),
+        ServiceScope::User => user_unit_dir(),
+        ServiceScope::Global => Some(PathBuf::from("/etc/systemd/user")),
+    }
+}
+
+/// Check the name for a copy of `original`, adding the unit type suffix
+/// if it was left off.
+pub fn clone_name(original: &str, new: &str) -> Result<String, String> {
+    let new = new.trim();
+    let suffix = original.rfind('.').map_or("", |dot| &original[dot..]);
+    let name = if !suffix.is_empty() && !new.ends_with(suffix) {
+        format!("{new}{suffix}")
+    } else {
+        new.to_string()
+    };
+
+    if new.is_empty() || name == original {
+        return Err("Choose a new name".to_string());
+    }
+    // Backslashes are fine: systemd-escape writes `\x2d` and friends
+    if name.starts_with('.') || name.contains('/') || name.contains(char::is_whitespace) {
+        return Err(format!("Invalid unit name: {name}"));
+    }
+    Ok(name)
+}
+
+/// Copy a unit file to `name` in the scope's unit directory, never
+/// overwriting. Copies outside the home directory go through pkexec.
+pub fn clone_unit(scope: &ServiceScope, source: &Path, name: &str) -> Result<PathBuf> {
+    if !valid_unit_name(name) {
+        anyhow::bail!("Not a valid unit name: {name:?}");
+    }
+    let dir = unit_dir(scope).context("No config directory (HOME unset)")?;
+    let dest = dir.join(name);
+    if dest.exists() {
+        anyhow::bail!("{} already exists", dest.display());
+    }
+
+    match scope {
+        ServiceScope::User => {
+            std::fs::create_dir_all(&dir)?;
+            std::fs::copy(source, &dest)
+                .with_context(|| format!("Failed to copy {}", source.display()))?;
+        }
+        ServiceScope::System | ServiceScope::Global => {
+            let mut cmd = Command::new("pkexec");
+            cmd.args(["cp", "--no-clobber", "--"])
+                .arg(source)
+                .arg(&dest);
+            let output = trace::output(&mut cmd).context("Failed to run pkexec cp")?;
+            if !output.status.success() {
+                anyhow::bail!(
+                    "Copy failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+        }
+    }
+    Ok(dest)
+}
+
+/// Write a root-owned file: stage it in the state directory, then
+/// `pkexec install` it into place (creating parent directories).
+pub fn write_root_file(dest: &Path, body: &str) -> Result<()> {
+    let dir = state_dir().context("No state directory (HOME unset)")?;
+    std::fs::create_dir_all(&dir)?;
+    let staged = dir.join(dest.file_name().unwrap_or_default());
+    std::fs::write(&staged, body)
+        .with_context(|| format!("Failed to write {}", staged.display()))?;
+
+    let mut cmd = Command::new("pkexec");
+    cmd.args(["install", "-D", "-m", "0644", "--"])
+        .arg(&staged)
+        .arg(dest);
+    let result = trace::output(&mut cmd).context("Failed to run pkexec install");
+    let _ = std::fs::remove_file(&staged);
+    let output = result?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Install failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Drop-in overriding `[Service]` settings of `unit`, then daemon-reload.
+/// Returns the file written.
+pub fn write_drop_in(scope: &ServiceScope, unit: &str, settings: &[String]) -> Result<PathBuf> {
+    if !valid_unit_name(unit) {
+        anyhow::bail!("Not a valid unit name: {unit:?}");
+    }
+    let dir = unit_dir(scope).context("No config directory (HOME unset)")?;
+    let path = dir
+        .join(format!("{unit}.d"))
+        .join("50-comma-services-stop.conf");
+    let body = format!(
+        "# Written by comma-services\n[Service]\n{}\n",
+        settings.join("\n")
+    );
+    match scope {
+        ServiceScope::User => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, body)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        ServiceScope::System | ServiceScope::Global => write_root_file(&path, &body)?,
+    }
+    daemon_reload(scope)?;
+    Ok(path)
+}
+
+/// Turn what the user typed into drop-in lines: a bare span ("15s") means
+/// TimeoutStopSec; `TimeoutStopSec=` and `KillMode=` pass through.
+pub fn stop_settings(input: &str) -> Result<Vec<String>, String> {
+    let mut settings = Vec::new();
+    for part in input.split_whitespace() {
+        let setting = match part.split_once('=') {
+            Some(("TimeoutStopSec" | "KillMode", value)) if !value.is_empty() => part.to_string(),
+            Some(_) => return Err(format!("Only TimeoutStopSec= and KillMode= here: {part}")),
+            None if part.chars().all(|c| c.is_ascii_alphanumeric()) => {
+                format!("TimeoutStopSec={part}")
+            }
+            None => return Err(format!("Not a time span: {part}")),
+        };
+        settings.push(setting);
+    }
+    if settings.is_empty() {
+        return Err("Enter a timeout like 15s".to_string());
+    }
+    Ok(settings)
+}
+
+/// Interactive `systemctl edit` (with `full`, of the whole unit file
+/// rather than an override drop-in), which opens $EDITOR (or
+/// $SYSTEMD_EDITOR) and reloads the manager afterwards. Needs the terminal.
+pub fn edit_command(scope: &ServiceScope, name: &str, full: bool) -> Command {
+    let args: &[&str] = if full { &["edit", "--full"] } else { &["edit"] };
+    if !scope.needs_root() {
+        let mut cmd = systemctl(scope, None);
+        cmd.args(args).args(["--", name]);
+        return cmd;
+    }
+
+    // pkexec clears the environment, so pass the editor through
+    let mut cmd = Command::new("pkexec");
+    cmd.arg("env");
+    if let Some(editor) = ["SYSTEMD_EDITOR", "VISUAL", "EDITOR"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+    {
+        cmd.arg(format!("SYSTEMD_EDITOR={editor}"));
+    }
+    cmd.arg("systemctl");
+    if *scope == ServiceScope::Global {
+        cmd.arg("--global");
+    }
+    cmd.args(args).args(["--", name]);
+    cmd
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ServiceInfo {
+    pub description: String,
+    pub active_state: String,
+    pub sub_state: String,
+    pub fragment_path: String,
+    pub triggered_by: String,
+    pub documentation: String,
+    pub extra_info: String,
+    pub main_pid: u32,
+    pub confinement: String, // AppArmor profile or SELinux context of MainPID
+    pub network_facing: bool,
+    pub bus_name: String,
+    pub dbus_activatable: bool, // a D-Bus .service file can start this unit on demand
+    pub after: Vec<String>,
+    pub enablement_links: Vec<(String, String)>, // symlink path, target
+    pub instance: Option<String>,                // unescaped template instance
+    pub source_path: String,                     // what a generator built this from
+    pub snap: Option<String>,                    // owning snap for snap.*.service
+    pub user: String,                            // User=, empty means root
+    pub group: String,
+    pub dynamic_user: bool,
+    pub capabilities: Vec<String>, // CapabilityBoundingSet, e.g. "cap_net_raw"
+    pub syscall_filter: bool,      // SystemCallFilter= set
+    pub service_type: String,      // Type=: simple, exec, forking, notify, oneshot, dbus, idle
+    pub graphical_session: bool,   // bound to or wanted by graphical-session.target
+    pub next_run: Option<u64>,     // unix time the triggering timer next fires
+    pub display_manager: Option<String>, // what display-manager.service points at (DMs only)
+    pub watched_paths: Vec<String>, // e.g. "PathChanged=/etc/cups", from triggering .path units
+    pub path_triggered: Option<u64>, // unix time a triggering .path unit last changed state
+    pub socket_listens: Vec<String>, // e.g. "/run/cups/cups.sock (Stream)", this or its socket's
+    pub before: Vec<String>,
+}
+
+impl ServiceInfo {
+    pub fn is_unconfined(&self) -> bool {
+        let label = self.confinement.as_str();
+        // AppArmor reports "unconfined"; SELinux uses unconfined_t / unconfined_service_t
+        label == "unconfined" || label.contains(":unconfined_")
+    }
+
+    /// Who the service process runs as, e.g. "root", "dynamic user",
+    /// "nobody:nogroup".
+    pub fn runs_as(&self) -> String {
+        if self.dynamic_user {
+            return "dynamic user (allocated at start)".to_string();
+        }
+        let user = if self.user.is_empty() {
+            "root"
+        } else {
+            &self.user
+        };
+        match self.group.as_str() {
+            "" => user.to_string(),
+            group => format!("{user}:{group}"),
+        }
+    }
+
+    pub fn runs_as_root(&self) -> bool {
+        !self.dynamic_user && matches!(self.user.as_str(), "" | "root" | "0")
+    }
+
+    /// An unrestricted bounding set lists every capability the kernel
+    /// knows (40+); hardened units keep a handful.
+    pub fn has_all_capabilities(&self) -> bool {
+        self.capabilities.len() >= 38
+    }
+
+    /// Plain-language summary of the capability bounding set.
+    pub fn capability_summary(&self) -> String {
+        if self.has_all_capabilities() {
+            return "unrestricted (every capability)".to_string();
+        }
+        if self.capabilities.is_empty() {
+            return "none".to_string();
+        }
+
+        let mut phrases: Vec<&str> = Vec::new();
+        for cap in &self.capabilities {
+            let phrase = match cap.as_str() {
+                "cap_sys_admin" => "broad admin powers (sys_admin)",
+                "cap_net_bind_service" => "can bind low ports",
+                "cap_net_admin" => "can reconfigure networking",
+                "cap_net_raw" => "can use raw sockets",
+                "cap_sys_ptrace" => "can ptrace",
+                "cap_dac_override" | "cap_dac_read_search" => "can bypass file permissions",
+                "cap_chown" | "cap_fowner" => "can change file ownership",
+                "cap_setuid" | "cap_setgid" => "can switch user/group",
+                "cap_sys_module" => "can load kernel modules",
+                "cap_sys_rawio" => "can do raw device I/O",
+                "cap_sys_time" => "can set the clock",
+                "cap_sys_boot" => "can reboot",
+                "cap_kill" => "can signal any process",
+                "cap_bpf" => "can load BPF programs",
+                _ => continue,
+            };
+            if !phrases.contains(&phrase) {
+                phrases.push(phrase);
+            }
+        }
+        if phrases.is_empty() {
+            return format!("{} minor capabilities", self.capabilities.len());
+        }
+        phrases.join(", ")
+    }
+
+    /// Generators write their units to /run/systemd/generator{,.early,.late}
+    /// (or /run/user/UID/systemd/generator* for the user manager).
+    pub fn is_generated(&self) -> bool {
+        self.fragment_path.contains("/systemd/generator")
+    }
+
+    /// Wrapper that systemd-sysv-generator made for an init script.
+    pub fn is_sysv(&self) -> bool {
+        ["/etc/init.d/", "/etc/rc.d/init.d/"]
+            .iter()
+            .any(|dir| self.source_path.starts_with(dir))
+    }
+}
+
+pub fn get_service_info(scope: &ServiceScope, service: &str) -> ServiceInfo {
+    let is_template = service.contains('@');
+
+    // For template units, try instantiated form or fall back to systemctl cat
+    let manager = scope.manager();
+    let mut info = if is_template {
+        get_info_from_cat(&manager, service)
+    } else {
+        get_info_from_show(&manager, service)
+    };
+
+    // Enrich with curated descriptions when systemd's own description is generic
+    if let Some(extra) = descriptions::curated_description(service) {
+        info.extra_info = extra.to_string();
+    }
+
+    // PIDs and unit directories below are the host's, not a container's
+    if info.main_pid != 0 && machine().is_none() {
+        info.confinement = read_confinement(info.main_pid);
+    }
+    info.network_facing = is_network_facing(service);
+    if machine().is_none() {
+        info.dbus_activatable = is_dbus_activatable(&scope.manager(), service, &info.bus_name);
+        info.enablement_links = enablement_links(&enablement_dirs(scope), service);
+    }
+    info.instance = instance_name(service);
+    info.snap = snap_name(service).map(String::from);
+    for path_unit in info
+        .triggered_by
+        .split_whitespace()
+        .filter(|unit| unit.ends_with(".path"))
+    {
+        let (paths, changed) = path_watch(&manager, path_unit);
+        info.watched_paths.extend(paths);
+        info.path_triggered = info.path_triggered.max(changed);
+    }
+    let sockets: Vec<&str> = if service.ends_with(".socket") {
+        vec![service]
+    } else {
+        info.triggered_by
+            .split_whitespace()
+            .filter(|unit| unit.ends_with(".socket"))
+            .collect()
+    };
+    info.socket_listens = sockets
+        .into_iter()
+        .flat_map(|socket| socket_listens(&manager, socket))
+        .collect();
+    if *scope == ServiceScope::System && is_display_manager(service) {
+        info.display_manager = display_manager();
+    }
+
+    info
+}
+
+/// What a .path unit watches (from its unit file) and when it last
+/// changed state, which happens each time it fires.
+fn path_watch(scope: &ServiceScope, path_unit: &str) -> (Vec<String>, Option<u64>) {
+    let mut cat = systemctl(scope, None);
+    cat.args(["cat", "--no-pager", "--", path_unit]);
+    let paths = trace::output(&mut cat)
+        .map(|o| parse_path_directives(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default();
+
+    let mut show = systemctl(scope, None);
+    show.args([
+        "show",
+        "--timestamp=unix",
+        "--property=StateChangeTimestamp",
+        "--value",
+        "--",
+        path_unit,
+    ]);
+    let changed = trace::output(&mut show).ok().and_then(|o| {
+        String::from_utf8_lossy(&o.stdout)
+            .trim()
+            .strip_prefix('@')?
+            .parse()
+            .ok()
+    });
+    (paths, changed)
+}
+
+/// A socket's `Listen=` addresses with their type.
+fn socket_listens(scope: &ServiceScope, socket: &str) -> Vec<String> {
+    let mut cmd = systemctl(scope, None);
+    cmd.args(["show", "--property=Listen", "--no-pager", "--", socket]);
+    let Ok(output) = trace::output(&mut cmd) else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_prefix("Listen="))
+        .filter(|listen| !listen.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn parse_path_directives(unit_file: &str) -> Vec<String> {
+    const WATCHES: &[&str] = &[
+        "PathExists",
+        "PathExistsGlob",
+        "PathChanged",
+        "PathModified",
+        "DirectoryNotEmpty",
+    ];
+    unit_file
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            line.split_once('=')
+                .is_some_and(|(key, _)| WATCHES.contains(&key.trim()))
+        })
+        .map(String::from)
+        .collect()
+}
+
+const DISPLAY_MANAGER_ALIAS: &str = "/etc/systemd/system/display-manager.service";
+
+/// The unit display-manager.service is aliased to. Enabling a display
+/// manager creates that link, and only one can hold it.
+pub fn display_manager() -> Option<String> {
+    let target = std::fs::read_link(DISPLAY_MANAGER_ALIAS).ok()?;
+    Some(target.file_name()?.to_string_lossy().into_owned())
+}
+
+/// Directories where `enable` creates its symlinks.
+fn enablement_dirs(scope: &ServiceScope) -> Vec<PathBuf> {
+    match scope {
+        ServiceScope::System => vec![PathBuf::from("/etc/systemd/system")],
+        ServiceScope::User => user_unit_dir()
+            .into_iter()
+            .chain([PathBuf::from("/etc/systemd/user")])
+            .collect(),
+        ServiceScope::Global => vec![PathBuf::from("/etc/systemd/user")],
+    }
+}
+
+/// The symlinks that make a unit enabled: `*.wants/` / `*.requires/` /
+/// `*.upholds/` entries, plus `Alias=` links at the top level.
+/// Returns (link path, target) pairs.
+fn enablement_links(dirs: &[PathBuf], service: &str) -> Vec<(String, String)> {
+    let mut links = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let candidate = if [".wants", ".requires", ".upholds"]
+                .iter()
+                .any(|ext| file_name.ends_with(ext))
+            {
+                path.join(service)
+            } else {
+                path
+            };
+            let Ok(target) = std::fs::read_link(&candidate) else {
+                continue;
+            };
+            let is_alias = candidate.parent() == Some(dir.as_path())
+                && target.file_name().is_some_and(|t| t == service)
+                && file_name != service;
+            if candidate.parent() != Some(dir.as_path()) || is_alias {
+                links.push((
+                    candidate.display().to_string(),
+                    target.display().to_string(),
+                ));
+            }
+        }
+    }
+    links.sort();
+    links
+}
+
+/// Search the bus's activation directory for a file that names this unit
+/// (SystemdService=) or its bus name (Name=).
+fn is_dbus_activatable(scope: &ServiceScope, service: &str, bus_name: &str) -> bool {
+    let dirs: Vec<PathBuf> = match scope {
+        ServiceScope::System => vec![
+            PathBuf::from("/usr/share/dbus-1/system-services"),
+            PathBuf::from("/etc/dbus-1/system-services"),
+        ],
+        ServiceScope::User | ServiceScope::Global => {
+            let mut dirs = vec![PathBuf::from("/usr/share/dbus-1/services")];
+            if let Some(home) = std::env::var_os("HOME") {
+                dirs.push(PathBuf::from(home).join(".local/share/dbus-1/services"));
+            }
+            dirs
+        }
+    };
+
+    dirs.iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| std::fs::read_to_string(entry.ok()?.path()).ok())
+        .any(|contents| dbus_file_activates(&contents, service, bus_name))
+}
+
+fn dbus_file_activates(contents: &str, service: &str, bus_name: &str) -> bool {
+    contents
+        .lines()
+        .any(|line| match line.trim().split_once('=') {
+            Some(("SystemdService", unit)) => unit == service,
+            Some(("Name", name)) => !bus_name.is_empty() && name == bus_name,
+            _ => false,
+        })
+}
+
+/// Security label of a running process. AppArmor-enabled kernels expose it
+/// under attr/apparmor/, otherwise attr/current holds the active LSM's label.
+fn read_confinement(pid: u32) -> String {
+    [
+        format!("/proc/{pid}/attr/apparmor/current"),
+        format!("/proc/{pid}/attr/current"),
+    ]
+    .iter()
+    .find_map(|path| std::fs::read_to_string(path).ok())
+    .map(|label| label.trim_end_matches(['\0', '\n']).to_string())
+    .unwrap_or_default()
+}
+
+/// What the info modal reads from the unit file (and its drop-ins), so it
+/// can be cached until they change.
+const INFO_PROPERTIES: &[&str] = &[
+    "Description",
+    "FragmentPath",
+    "SourcePath",
+    "Documentation",
+    "BusName",
+    "User",
+    "Group",
+    "DynamicUser",
+    "CapabilityBoundingSet",
+    "SystemCallFilter",
+    "Type",
+    "PartOf",
+    "BindsTo",
+    "Requisite",
+];
+
+/// What the info modal asks for every time: whether it runs, and what
+/// other units say about it. Editing or enabling those changes ordering,
+/// triggers and WantedBy= without touching this unit's files.
+const LIVE_INFO_PROPERTIES: &str = "ActiveState,SubState,MainPID,TriggeredBy,After,Before,WantedBy";
+
+fn get_info_from_show(scope: &ServiceScope, service: &str) -> ServiceInfo {
+    let names = [service.to_string()];
+    let mut text = unit_cache::show(scope, &names, INFO_PROPERTIES, |names, props| {
+        show_properties(scope, names, props)
+    })
+    .concat();
+    text.push_str(&show_properties(scope, &names, LIVE_INFO_PROPERTIES));
+    parse_show(&text)
+}
+
+fn parse_show(stdout: &str) -> ServiceInfo {
+    let mut info = ServiceInfo::default();
+
+    for line in stdout.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            match key {
+                "Description" => info.description = value.to_string(),
+                "ActiveState" => info.active_state = value.to_string(),
+                "SubState" => info.sub_state = value.to_string(),
+                "FragmentPath" => info.fragment_path = value.to_string(),
+                "SourcePath" => info.source_path = value.to_string(),
+                "TriggeredBy" => info.triggered_by = value.to_string(),
+                "Documentation" => info.documentation = value.to_string(),
+                "MainPID" => info.main_pid = value.parse().unwrap_or(0),
+                "BusName" => info.bus_name = value.to_string(),
+                "User" => info.user = value.to_string(),
+                "Group" => info.group = value.to_string(),
+                "DynamicUser" => info.dynamic_user = value == "yes",
+                "CapabilityBoundingSet" => {
+                    info.capabilities = value.split_whitespace().map(String::from).collect()
+                }
+                "SystemCallFilter" => info.syscall_filter = !value.is_empty(),
+                "Type" => info.service_type = value.to_string(),
+                "PartOf" | "BindsTo" | "Requisite" | "WantedBy" => {
+                    info.graphical_session |= value
+                        .split_whitespace()
+                        .any(|unit| unit == GRAPHICAL_SESSION)
+                }
+                "After" => info.after = value.split_whitespace().map(String::from).collect(),
+                "Before" => info.before = value.split_whitespace().map(String::from).collect(),
+                _ => {}
+            }
+        }
+    }
+
+    info
+}
+
+/// The unit file and its drop-ins as `systemctl cat` prints them.
+pub fn unit_file_text(scope: &ServiceScope, unit: &str) -> Option<String> {
+    let mut cmd = systemctl(scope, None);
+    cmd.args(["cat", "--no-pager", "--", unit]);
+    match trace::output(&mut cmd) {
+        Ok(o) if o.status.success() => Some(String::from_utf8_lossy(&o.stdout).into_owned()),
+        _ => None,
+    }
+}
+
+/// `systemctl list-dependencies` for `unit`, tree glyphs and all. Only
+/// a running manager knows the graph, so all-users units ask the user one.
+pub fn list_dependencies(scope: &ServiceScope, unit: &str) -> Result<String> {
+    let mut cmd = systemctl(&scope.manager(), None);
+    cmd.args(["list-dependencies", "--no-pager", "--", unit]);
+    let output = trace::output(&mut cmd).context("Failed to run systemctl")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "list-dependencies: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn get_info_from_cat(scope: &ServiceScope, service: &str) -> ServiceInfo {
+    let mut cmd = systemctl(scope, None);
+    cmd.args(["cat", "--no-pager", "--", service]);
+
+    let output = match trace::output(&mut cmd) {
+        Ok(o) if o.status.success() => o,
+        _ => return ServiceInfo::default(),
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut info = ServiceInfo::default();
+
+    for line in stdout.lines() {
+        let trimmed = line.trim();
+        if let Some(val) = trimmed.strip_prefix("Description=") {
+            info.description = val.to_string();
+        } else if let Some(val) = trimmed.strip_prefix("Documentation=") {
+            info.documentation = val.to_string();
+        } else if trimmed.starts_with("# /") {
+            info.fragment_path = trimmed.trim_start_matches("# ").to_string();
+        }
+    }
+
+    // Template units aren't running instances, so state isn't meaningful
+    info.active_state = "template".to_string();
+    info.sub_state = "n/a".to_string();
+
+    info
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeAction {
+    Enable,      // and start
+    Disable,     // and stop
+    EnableOnly,  // at boot, not now
+    DisableOnly, // at boot; keeps running
+    Start,       // now; boot state unchanged
+    Stop,
+    Restart,
+    Mask,   // link to /dev/null and stop: nothing can start it
+    Unmask, // back to plain disabled
+}
+
+impl ChangeAction {
+    /// The boot-state verb and the start/stop that runs after it. Either
+    /// can be missing: unmasking starts nothing, a plain stop links nothing.
+    pub fn verbs(&self) -> (Option<&'static str>, Option<&'static str>) {
+        match self {
+            ChangeAction::Enable => (Some("enable"), Some("start")),
+            ChangeAction::Disable => (Some("disable"), Some("stop")),
+            ChangeAction::EnableOnly => (Some("enable"), None),
+            ChangeAction::DisableOnly => (Some("disable"), None),
+            ChangeAction::Start => (None, Some("start")),
+            ChangeAction::Stop => (None, Some("stop")),
+            ChangeAction::Restart => (None, Some("restart")),
+            ChangeAction::Mask => (Some("mask"), Some("stop")),
+            ChangeAction::Unmask => (Some("unmask"), None),
+        }
+    }
+
+    /// Whether the unit ends up stopped.
+    pub fn stops(&self) -> bool {
+        self.verbs().1 == Some("stop")
+    }
+}
+
+/// "stop" -> "stopped", "disable" -> "disabled", "mask" -> "masked".
+fn past(verb: &str) -> String {
+    match verb {
+        "stop" => "stopped".to_string(),
+        _ if verb.ends_with('e') => format!("{verb}d"),
+        _ => format!("{verb}ed"),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PendingChange {
+    pub service: String,
+    pub scope: ServiceScope,
+    pub action: ChangeAction,
+    pub runtime: bool, // --runtime: links live in /run and vanish at reboot
+}
+
+#[derive(Debug)]
+pub struct ChangeResult {
+    pub service: String,
+    pub success: bool,
+    pub message: String,
+    pub error: Option<SystemctlError>, // why the failing step failed
+    pub timings: Vec<(&'static str, Duration)>, // wall-clock time per systemctl step
+    pub retries: u32,                  // steps tried again after timing out
+}
+
+/// Why one systemctl step of an apply failed, sorted out from the exit
+/// code and stderr so the UI can say what to do about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SystemctlError {
+    /// Not a unit name we'd ever hand to pkexec.
+    InvalidName(String),
+    /// systemctl (or pkexec) couldn't be run at all.
+    Spawn(String),
+    /// Took longer than this many seconds, every try.
+    Timeout(u64),
+    /// The polkit password dialog was closed without authenticating.
+    PolkitDismissed,
+    /// polkit said no (wrong password, or no agent to ask with).
+    NotAuthorized,
+    /// The manager refused the call itself.
+    PermissionDenied(String),
+    NoSuchUnit(String),
+    Masked(String),
+    Failed(String),
+    /// The apply was canceled before this change's turn came.
+    Canceled,
+}
+
+impl SystemctlError {
+    /// Sort a failed run into a class. pkexec exits 126 when its dialog is
+    /// dismissed and 127 when authorization fails; everything else is
+    /// systemctl's own stderr.
+    fn classify(code: Option<i32>, stderr: &str, via_pkexec: bool) -> Self {
+        let stderr = stderr.trim().to_string();
+        let lower = stderr.to_lowercase();
+        match code {
+            Some(126) if via_pkexec => return Self::PolkitDismissed,
+            Some(127) if via_pkexec => return Self::NotAuthorized,
+            _ => {}
+        }
+        if lower.contains("access denied")
+            || lower.contains("interactive authentication required")
+            || lower.contains("permission denied")
+        {
+            Self::PermissionDenied(stderr)
+        } else if lower.contains("is masked") {
+            Self::Masked(stderr)
+        } else if lower.contains("not found")
+            || lower.contains("does not exist")
+            || lower.contains("not loaded")
+        {
+            Self::NoSuchUnit(stderr)
+        } else {
+            Self::Failed(stderr)
+        }
+    }
+
+    /// What to try next, for the classes where there's something to say.
+    pub fn fix(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::Timeout(_) => {
+                "Still running in the background; refresh with r to see where it got, or raise [timeouts] in the config"
+            }
+            Self::PolkitDismissed => "Still staged; press Enter to apply again",
+            Self::Canceled => "Still staged; press Enter to apply it",
+            Self::NotAuthorized => {
+                "Check the password, or install the polkit rule from --polkit-rule"
+            }
+            Self::PermissionDenied(_) => "Run from a session with a polkit agent, or as root",
+            Self::NoSuchUnit(_) => {
+                "The unit file is gone; refresh with r (a daemon-reload may be needed)"
+            }
+            Self::Masked(_) => "Unmask it with m first",
+            Self::InvalidName(_) | Self::Spawn(_) | Self::Failed(_) => return None,
+        })
+    }
+}
+
+impl std::fmt::Display for SystemctlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidName(name) => write!(f, "refusing to run systemctl on {name:?}"),
+            Self::Spawn(e) => write!(f, "command failed: {e}"),
+            Self::Timeout(secs) => write!(f, "timed out after {secs}s"),
+            Self::PolkitDismissed => write!(f, "authentication canceled — change not applied"),
+            Self::NotAuthorized => write!(f, "not authorized by polkit"),
+            Self::Canceled => write!(f, "skipped — apply canceled"),
+            Self::PermissionDenied(stderr)
+            | Self::NoSuchUnit(stderr)
+            | Self::Masked(stderr)
+            | Self::Failed(stderr) => write!(f, "{stderr}"),
+        }
+    }
+}
+
+impl std::error::Error for SystemctlError {}
+
+/// "enable failed: ..." or "stop timed out after 30s", with whatever
+/// timeout was configured. A dismissed dialog or a canceled apply isn't a
+/// failure of the verb, so it speaks for itself.
+fn step_message(verb: &str, error: &SystemctlError) -> String {
+    match error {
+        SystemctlError::Timeout(_) => format!("{verb} {error}"),
+        SystemctlError::PolkitDismissed | SystemctlError::Canceled => error.to_string(),
+        _ => format!("{verb} failed: {error}"),
+    }
+}
+
+/// Steps at least this slow are worth a look (half the default timeout;
+/// `TimeoutPolicy` may allow more).
+pub const SLOW_STEP: Duration = Duration::from_secs(5);
+
+impl ChangeResult {
+    /// "retried 2× after timing out", when the timeout policy kicked in.
+    pub fn retry_note(&self) -> Option<String> {
+        (self.retries > 0).then(|| format!("retried {}× after timing out", self.retries))
+    }
+
+    /// The stop step was slow or timed out.
+    pub fn slow_stop(&self) -> bool {
+        self.timings
+            .iter()
+            .any(|(step, elapsed)| *step == "stop" && *elapsed >= SLOW_STEP)
+    }
+}
+
+/// One systemctl step of a change: its verb, how it went, how long it took
+/// and how many times it was tried again after timing out.
+type Step = (&'static str, Result<(), SystemctlError>, Duration, u32);
+
+/// The boot-state verb and the start/stop after it, as applied. Nothing
+/// runs for all users, so a global change has only the first.
+fn steps(change: &PendingChange) -> (Option<&'static str>, Option<&'static str>) {
+    let (boot, now) = change.action.verbs();
+    match change.scope {
+        ServiceScope::Global => (boot, None),
+        _ => (boot, now),
+    }
+}
+
+/// Apply changes, separating enable/disable from start/stop so the enable
+/// always succeeds even if the service is slow to start. Changes that need
+/// root go through a single pkexec, so there's at most one password prompt;
+/// user changes run one command at a time with a timeout each. Once
+/// `cancel` is set, the change in flight finishes and the rest are skipped.
+pub async fn apply_changes(
+    changes: Vec<PendingChange>,
+    events: mpsc::UnboundedSender<ApplyEvent>,
+    cancel: Arc<AtomicBool>,
+) {
+    if changes.iter().all(|change| change.scope.needs_root()) {
+        apply_batch(&changes, &events, &cancel).await;
+    } else {
+        apply_each(&changes, &events, &cancel).await;
+    }
+    let _ = events.send(ApplyEvent::Finished);
+}
+
+/// How an apply is getting on, sent as it happens.
+#[derive(Debug)]
+pub enum ApplyEvent {
+    /// `done` changes have finished and `service` is being worked on.
+    Working {
+        done: usize,
+        service: String,
+    },
+    Done(ChangeResult),
+    /// Every change has a Done; nothing else follows.
+    Finished,
+}
+
+async fn apply_each(
+    changes: &[PendingChange],
+    events: &mpsc::UnboundedSender<ApplyEvent>,
+    cancel: &AtomicBool,
+) {
+    for (done, change) in changes.iter().enumerate() {
+        let (boot_verb, now_verb) = steps(change);
+        if cancel.load(Ordering::Relaxed) {
+            let verb = boot_verb.or(now_verb).unwrap_or_default();
+            let step = (verb, Err(SystemctlError::Canceled), Duration::ZERO, 0);
+            let _ = events.send(ApplyEvent::Done(
+                change_result(change, Some(step), None).await,
+            ));
+            continue;
+        }
+        let _ = events.send(ApplyEvent::Working {
+            done,
+            service: change.service.clone(),
+        });
+        let boot = match boot_verb {
+            Some(verb) => Some(run_step(change, verb).await),
+            None => None,
+        };
+        let now = match now_verb {
+            Some(verb) if boot.as_ref().is_none_or(|(_, result, ..)| result.is_ok()) => {
+                Some(run_step(change, verb).await)
+            }
+            _ => None,
+        };
+        let _ = events.send(ApplyEvent::Done(change_result(change, boot, now).await));
+    }
+}
+
+async fn run_step(change: &PendingChange, verb: &'static str) -> Step {
+    let mut args = vec![verb];
+    if change.runtime && steps(change).0 == Some(verb) {
+        args.push("--runtime");
+    }
+    let started = Instant::now();
+    let mut retries = 0;
+    loop {
+        let result = run_systemctl(&change.scope, &args, &change.service).await;
+        if matches!(result, Err(SystemctlError::Timeout(_))) && retries < timeouts().retries {
+            retries += 1;
+            continue;
+        }
+        return (verb, result, started.elapsed(), retries);
+    }
+}
+
+/// Run every step in one `pkexec /bin/sh -c` of `batch_script`, then read
+/// each step's outcome back from its output line. Progress is reported as
+/// the lines come in; the results follow once the batch is over, since
+/// wording a failure may take another systemctl call. Each step waits for
+/// a go-ahead on stdin, so a cancel stops the script between changes.
+async fn apply_batch(
+    changes: &[PendingChange],
+    events: &mpsc::UnboundedSender<ApplyEvent>,
+    cancel: &AtomicBool,
+) {
+    let (valid, invalid): (Vec<&PendingChange>, Vec<&PendingChange>) = changes
+        .iter()
+        .partition(|change| valid_unit_name(&change.service));
+    // Every step's timeout for every try, plus one for the password prompt
+    let limit = valid
+        .iter()
+        .map(|change| {
+            let (boot, now) = steps(change);
+            let count = u32::from(boot.is_some()) + u32::from(now.is_some());
+            timeouts().for_unit(&change.service) * count * (timeouts().retries + 1)
+        })
+        .sum::<Duration>()
+        + CMD_TIMEOUT;
+
+    let mut stdout = String::new();
+    let mut stopped = None; // why the steps without an output line didn't run
+    if !valid.is_empty() {
+        let mut cmd = AsyncCommand::new("pkexec");
+        cmd.args(["/bin/sh", "-c", &batch_script(&valid)]);
+        let working = |done: usize| {
+            if let Some(change) = valid.get(done) {
+                let _ = events.send(ApplyEvent::Working {
+                    done,
+                    service: change.service.clone(),
+                });
+            }
+        };
+        working(0);
+        let mut codes = Vec::new();
+        let mut done = 0;
+        let mut between = true;
+        let progress = |line: &str| {
+            if let Some((code, ..)) = parse_step_line(line) {
+                codes.push(code);
+                let now_done;
+                (now_done, between) = batch_finished(&valid, &codes);
+                if now_done != done {
+                    done = now_done;
+                    working(done);
+                }
+            }
+            // Half a change (enabled, not yet started) is worse than none
+            !(between && cancel.load(Ordering::Relaxed))
+        };
+        match timeout(limit, trace::output_lines(&mut cmd, progress)).await {
+            Ok(Ok(output)) => {
+                stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+                if cancel.load(Ordering::Relaxed) {
+                    stopped = Some(SystemctlError::Canceled);
+                }
+                if !output.status.success() {
+                    stopped = Some(SystemctlError::classify(
+                        output.status.code(),
+                        &String::from_utf8_lossy(&output.stderr),
+                        true,
+                    ));
+                }
+            }
+            Ok(Err(e)) => stopped = Some(SystemctlError::Spawn(e.to_string())),
+            Err(_) => stopped = Some(SystemctlError::Timeout(limit.as_secs())),
+        }
+    }
+
+    let outcomes = batch_outcomes(&valid, &stdout, stopped);
+    for (change, (boot, now)) in valid.into_iter().zip(outcomes) {
+        let _ = events.send(ApplyEvent::Done(change_result(change, boot, now).await));
+    }
+    for change in invalid {
+        let (boot, now) = steps(change);
+        let error = SystemctlError::InvalidName(change.service.clone());
+        let step = (
+            boot.or(now).unwrap_or_default(),
+            Err(error),
+            Duration::ZERO,
+            0,
+        );
+        let _ = events.send(ApplyEvent::Done(
+            change_result(change, Some(step), None).await,
+        ));
+    }
+}
+
+/// How many changes from the front have all their steps in `codes`, the
+/// exit codes of the batch's output lines so far, and whether `codes`
+/// ends right after one of them rather than partway into the next.
+fn batch_finished(changes: &[&PendingChange], codes: &[i32]) -> (usize, bool) {
+    let mut used = 0;
+    let mut finished = 0;
+    for change in changes {
+        let needed = match steps(change) {
+            (Some(_), Some(_)) => match codes.get(used) {
+                Some(0) => 2,
+                Some(_) => 1, // the start/stop after a failure never runs
+                None => break,
+            },
+            (Some(_), None) | (None, Some(_)) => 1,
+            (None, None) => 0,
+        };
+        if used + needed > codes.len() {
+            break;
+        }
+        used += needed;
+        finished += 1;
+    }
+    (finished, used == codes.len())
+}
+
+/// Shell function each batched step goes through: a go-ahead line read
+/// from stdin (end of input stops the script), then systemctl under a
+/// timeout of `$1` seconds, tried again up to `$2` times if it runs out,
+/// printing `exit-code<TAB>milliseconds<TAB>retries<TAB>stderr`.
+pub fn batch_prelude() -> String {
+    "r() { read -r g || exit 0; t=$1; n=$2; shift 2; a=0; s=$(date +%s%N); \
+     while e=$(timeout \"$t\" systemctl \"$@\" 2>&1 >/dev/null); c=$?; \
+     [ \"$c\" = 124 ] && [ \"$a\" -lt \"$n\" ]; do a=$((a + 1)); done; \
+     printf '%s\\t%s\\t%s\\t%s\\n' \"$c\" \"$(( ($(date +%s%N) - s) / 1000000 ))\" \"$a\" \
+     \"$(printf %s \"$e\" | tr '\\t\\n' ' \\036')\"; return $c; }; "
+        .to_string()
+}
+
+/// The whole apply as one line of shell for root: each change's start/stop
+/// runs only if its enable/disable went through. Ends with `exit 0`, so a
+/// failing exit status is pkexec's (dismissed, not authorized).
+fn batch_script(changes: &[&PendingChange]) -> String {
+    let mut script = batch_prelude();
+    for change in changes {
+        let mut flags = String::new();
+        match (&change.scope, machine()) {
+            (ServiceScope::Global, _) => flags.push_str("--global "),
+            (ServiceScope::System, Some(name)) => {
+                flags.push_str(&format!("-M {} ", shell_quote(name)))
+            }
+            _ => {}
+        }
+        let unit = shell_quote(&change.service);
+        let limits = format!(
+            "{} {}",
+            timeouts().for_unit(&change.service).as_secs().max(1),
+            timeouts().retries
+        );
+        let (boot, now) = steps(change);
+        let boot = boot.map(|verb| {
+            let runtime = if change.runtime { " --runtime" } else { "" };
+            format!("r {limits} {flags}{verb}{runtime} -- {unit}")
+        });
+        let now = now.map(|verb| format!("r {limits} {flags}{verb} -- {unit}"));
+        script.push_str(&boot.into_iter().chain(now).collect::<Vec<_>>().join(" && "));
+        script.push_str("; ");
+    }
+    script.push_str("exit 0");
+    script
+}
+
+/// Match the batch's output lines back to the steps, in order. A step
+/// after a failed one never ran and has no line; steps past the end of
+/// the output get `stopped`, the reason the batch ended early.
+fn batch_outcomes(
+    changes: &[&PendingChange],
+    stdout: &str,
+    stopped: Option<SystemctlError>,
+) -> Vec<(Option<Step>, Option<Step>)> {
+    let mut lines = stdout.lines().filter_map(parse_step_line);
+    let stopped = stopped.unwrap_or(SystemctlError::Failed("step didn't run".to_string()));
+    let mut next = |change: &PendingChange, verb: &'static str| -> Step {
+        match lines.next() {
+            Some((code, elapsed, retries, stderr)) => {
+                let result = match code {
+                    0 => Ok(()),
+                    // timeout(1) killed it
+                    124 => Err(SystemctlError::Timeout(
+                        timeouts().for_unit(&change.service).as_secs(),
+                    )),
+                    code => Err(SystemctlError::classify(Some(code), &stderr, false)),
+                };
+                (verb, result, elapsed, retries)
+            }
+            None => (verb, Err(stopped.clone()), Duration::ZERO, 0),
+        }
+    };
+    changes
+        .iter()
+        .map(|change| {
+            let (boot_verb, now_verb) = steps(change);
+            let boot = boot_verb.map(|verb| next(change, verb));
+            let now = match now_verb {
+                Some(verb) if boot.as_ref().is_none_or(|(_, result, ..)| result.is_ok()) => {
+                    Some(next(change, verb))
+                }
+                _ => None,
+            };
+            (boot, now)
+        })
+        .collect()
+}
+
+/// `1<TAB>37<TAB>0<TAB>Failed to start...` -> (1, 37 ms, 0 retries,
+/// "Failed to start..."). The script sends stderr's newlines as RS
+/// (`\x1e`) to keep each step on one line.
+fn parse_step_line(line: &str) -> Option<(i32, Duration, u32, String)> {
+    let mut fields = line.splitn(4, '\t');
+    let code = fields.next()?.parse().ok()?;
+    let millis = fields.next()?.parse().ok()?;
+    let retries = fields.next()?.parse().ok()?;
+    let stderr = fields.next().unwrap_or("").replace('\x1e', "\n");
+    let stderr = stderr.trim().to_string();
+    Some((code, Duration::from_millis(millis), retries, stderr))
+}
+
+/// Word one change's outcome from its steps.
+async fn change_result(
+    change: &PendingChange,
+    boot: Option<Step>,
+    now: Option<Step>,
+) -> ChangeResult {
+    let mut timings = Vec::new();
+    let mut retries = 0;
+    let done = match boot {
+        None => Ok(None),
+        Some((verb, result, elapsed, tries)) => {
+            timings.push((verb, elapsed));
+            retries += tries;
+            result
+                .map(|()| Some(past(verb)))
+                .map_err(|e| (step_message(verb, &e), e))
+        }
+    };
+    let (message, error) = match (done, now) {
+        (Err((message, e)), _) => (message, Some(e)),
+        // Takes effect as each user's manager next starts
+        (Ok(done), _) if change.scope == ServiceScope::Global => {
+            (format!("{} for all users", done.unwrap_or_default()), None)
+        }
+        (Ok(done), None) => (done.unwrap_or_default(), None),
+        (Ok(done), Some((verb, result, elapsed, tries))) => {
+            timings.push((verb, elapsed));
+            retries += tries;
+            match result {
+                Ok(()) => (
+                    match done {
+                        Some(done) => format!("{done} and {}", past(verb)),
+                        None => past(verb),
+                    },
+                    None,
+                ),
+                Err(e) => {
+                    let hint = match e {
+                        SystemctlError::Canceled => String::new(),
+                        _ => start_diagnosis(change, verb).await,
+                    };
+                    let done = done.map(|d| format!("{d} but ")).unwrap_or_default();
+                    (format!("{done}{}{hint}", step_message(verb, &e)), Some(e))
+                }
+            }
+        }
+    };
+    ChangeResult {
+        service: change.service.clone(),
+        success: error.is_none(),
+        message,
+        error,
+        timings,
+        retries,
+    }
+}
+
+/// After a failed or slow start, ask systemd why and phrase it in terms
+/// of the unit's Type=. Returns "" for stops or when there's nothing to add.
+async fn start_diagnosis(change: &PendingChange, start_action: &str) -> String {
+    if start_action != "start" {
+        return String::new();
+    }
+    let mut cmd = AsyncCommand::new("systemctl");
+    cmd.args(machine_args());
+    if change.scope.manager() == ServiceScope::User {
+        cmd.arg("--user");
+    }
+    cmd.args([
+        "show",
+        "--property=Type,Result,ActiveState",
+        "--no-pager",
+        "--",
+        &change.service,
+    ]);
+    let Ok(Ok(output)) = timeout(CMD_TIMEOUT, trace::output_async(&mut cmd)).await else {
+        return String::new();
+    };
+
+    let props = String::from_utf8_lossy(&output.stdout);
+    let prop = |key: &str| {
+        props
+            .lines()
+            .find_map(|l| l.strip_prefix(key)?.strip_prefix('='))
+            .unwrap_or("")
+    };
+    match start_hint(prop("Type"), prop("Result"), prop("ActiveState")) {
+        Some(hint) => format!(" — {hint}"),
+        None => String::new(),
+    }
+}
+
+fn start_hint(service_type: &str, result: &str, active_state: &str) -> Option<&'static str> {
+    let hint = match (service_type, result, active_state) {
+        ("notify" | "notify-reload", _, "activating") => {
+            "Type=notify service hasn't signaled readiness yet"
+        }
+        ("notify" | "notify-reload", "timeout", _) => {
+            "Type=notify service never signaled readiness"
+        }
+        ("forking", "timeout", _) | ("forking", _, "activating") => {
+            "Type=forking service's parent process never exited"
+        }
+        ("dbus", "timeout", _) | ("dbus", _, "activating") => {
+            "Type=dbus service never claimed its bus name"
+        }
+        ("oneshot", _, "activating") => "Type=oneshot task is still running",
+        ("oneshot", "exit-code", _) => "oneshot command exited with an error",
+        (_, "timeout", _) => "start exceeded TimeoutStartSec",
+        (_, "protocol", _) => "PID file missing or readiness protocol violated",
+        (_, "start-limit-hit", _) => {
+            "restarted too often; run systemctl reset-failed before retrying"
+        }
+        (_, "resources", _) => "couldn't set up resources (missing user, directory, or file)",
+        (_, "exit-code" | "signal" | "core-dump", _) => "main process died; check journalctl -u",
+        _ => return None,
+    };
+    Some(hint)
+}
+
+/// Unit names as systemd itself allows them (alphanumerics plus `:-_.\`,
+/// one `@` for instances) with a unit-type suffix. Anything else never
+/// goes near pkexec, even though we don't pass through a shell there.
+pub fn valid_unit_name(name: &str) -> bool {
+    let Some((stem, suffix)) = name.rsplit_once('.') else {
+        return false;
+    };
+    let known = matches!(
+        suffix,
+        "service" | "socket" | "timer" | "path" | "mount" | "automount" | "target" | "swap"
+    );
+    known
+        && !stem.is_empty()
+        && !name.starts_with('-')
+        && name.len() <= 255
+        && name.matches('@').count() <= 1
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ":-_.\\@".contains(c))
+}
+
+async fn run_systemctl(
+    scope: &ServiceScope,
+    args: &[&str],
+    service: &str,
+) -> Result<(), SystemctlError> {
+    if !valid_unit_name(service) {
+        return Err(SystemctlError::InvalidName(service.to_string()));
+    }
+    let mut cmd = match scope {
+        ServiceScope::User => {
+            let mut c = AsyncCommand::new("systemctl");
+            c.arg("--user");
+            c
+        }
+        ServiceScope::System => {
+            let mut c = AsyncCommand::new("pkexec");
+            c.arg("systemctl").args(machine_args());
+            c
+        }
+        ServiceScope::Global => {
+            let mut c = AsyncCommand::new("pkexec");
+            c.args(["systemctl", "--global"]);
             c
         }
     };
+    // `--` so an instance name starting with "-" isn't read as an option
+    cmd.args(args).arg("--").arg(service);
+
+    let limit = timeouts().for_unit(service);
+    match timeout(limit, trace::output_async(&mut cmd)).await {
+        Ok(Ok(output)) if output.status.success() => Ok(()),
+        Ok(Ok(output)) => Err(SystemctlError::classify(
+            output.status.code(),
+            &String::from_utf8_lossy(&output.stderr),
+            *scope != ServiceScope::User,
+        )),
+        Ok(Err(e)) => Err(SystemctlError::Spawn(e.to_string())),
+        Err(_) => Err(SystemctlError::Timeout(limit.as_secs())),
+    }
+}
+
+/// Defer a batch to `when` by creating a transient timer with `systemd-run`.
+/// `when` is an OnCalendar expression ("02:00", "tomorrow 03:00") or, with a
+/// leading `+`, a delay from now ("+2h"). Returns a summary for the status bar.
+pub async fn schedule_changes(changes: Vec<PendingChange>, when: String) -> Result<String, String> {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let timer_arg = match when.strip_prefix('+') {
+        Some(delay) => format!("--on-active={}", delay.trim()),
+        None => format!("--on-calendar={}", when.trim()),
+    };
+
+    // The batch runs as a shell script; quoting covers it, but don't rely on that
+    if let Some(change) = changes.iter().find(|c| !valid_unit_name(&c.service)) {
+        return Err(format!("refusing to schedule {:?}", change.service));
+    }
+
+    let mut units = Vec::new();
+    for (root, batch) in schedule_batches(&changes) {
+        let unit = if root {
+            format!("comma-services-apply-{stamp}")
+        } else {
+            format!("comma-services-apply-user-{stamp}")
+        };
+        let mut cmd = if root {
+            let mut c = AsyncCommand::new("pkexec");
+            c.arg("systemd-run");
+            c
+        } else {
+            let mut c = AsyncCommand::new("systemd-run");
+            c.arg("--user");
+            c
+        };
+        cmd.args([
+            format!("--unit={unit}"),
+            "--description=comma-services scheduled apply".to_string(),
+            timer_arg.clone(),
+            "--timer-property=AccuracySec=1s".to_string(),
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            changes_script(&batch),
+        ]);
+
+        // As in apply_batch: the root timer waits on a password prompt first
+        let limit = if root {
+            timeouts().timeout + CMD_TIMEOUT
+        } else {
+            timeouts().timeout
+        };
+        // A timer created after we gave up would fire unannounced
+        cmd.kill_on_drop(true);
+        match timeout(limit, trace::output_async(&mut cmd)).await {
+            Ok(Ok(output)) if output.status.success() => units.push(format!("{unit}.timer")),
+            Ok(Ok(output)) => {
+                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                return Err(format!("scheduling failed: {stderr}"));
+            }
+            Ok(Err(e)) => return Err(format!("command failed: {e}")),
+            Err(_) => return Err(format!("timed out after {}s", limit.as_secs())),
+        }
+    }
+
+    Ok(format!(
+        "{} change{} scheduled for {} ({})",
+        changes.len(),
+        if changes.len() == 1 { "" } else { "s" },
+        when.trim(),
+        units.join(", ")
+    ))
+}
+
+/// The scheduled timers' batches, the user's first: (needs root, changes).
+/// Global changes write /etc/systemd/user, so they ride in the root timer.
+fn schedule_batches(changes: &[PendingChange]) -> Vec<(bool, Vec<PendingChange>)> {
+    [false, true]
+        .into_iter()
+        .map(|root| {
+            let batch: Vec<PendingChange> = changes
+                .iter()
+                .filter(|c| c.scope.needs_root() == root)
+                .cloned()
+                .collect();
+            (root, batch)
+        })
+        .filter(|(_, batch)| !batch.is_empty())
+        .collect()
+}
 
-    match timeout(CMD_TIMEOUT, cmd.output()).await {
-        Ok(Ok(output)) => Ok(output),
-        Ok(Err(e)) => Err(format!("command failed: {}", e)),
-        Err(_) => {
-            // Timeout — try to kill the child if possible
-            Err("timed out after 10s".to_string())
+/// One-line shell script that applies `changes` with plain systemctl.
+/// Used by scheduled timers (already root or the user) and rescue scripts,
+/// so no pkexec is needed inside.
+pub fn changes_script(changes: &[PendingChange]) -> String {
+    changes
+        .iter()
+        .map(|change| {
+            let scope_flag = match (change.scope.clone(), machine()) {
+                (ServiceScope::System, Some(name)) => format!(" -M {}", shell_quote(name)),
+                (ServiceScope::System, None) => String::new(),
+                (ServiceScope::User, _) => " --user".to_string(),
+                (ServiceScope::Global, _) => " --global".to_string(),
+            };
+            let unit = shell_quote(&change.service);
+            let (boot, now) = change.action.verbs();
+            let Some(action) = boot else {
+                return format!(
+                    "systemctl{scope_flag} {} -- {unit}",
+                    now.unwrap_or_default()
+                );
+            };
+            let runtime_flag = if change.runtime { " --runtime" } else { "" };
+            // Nothing runs globally, so there's nothing to start or stop
+            let now_flag = match change.scope {
+                ServiceScope::Global => "",
+                _ if now.is_none() => "",
+                _ => " --now",
+            };
+            format!("systemctl{scope_flag} {action}{runtime_flag}{now_flag} -- {unit}")
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_machine_name() {
+        assert!(valid_machine_name("devbox"));
+        assert!(valid_machine_name("arch-nspawn.1"));
+        assert!(!valid_machine_name("-H evil"));
+        assert!(!valid_machine_name("a;b"));
+        assert!(!valid_machine_name(""));
+    }
+
+    #[test]
+    fn test_valid_unit_name() {
+        assert!(valid_unit_name("cups.service"));
+        assert!(valid_unit_name("getty@tty1.service"));
+        assert!(valid_unit_name(
+            "systemd-fsck@dev-disk-by\\x2duuid-1234.service"
+        ));
+        assert!(valid_unit_name("fstrim.timer"));
+        assert!(!valid_unit_name("cups.service; reboot"));
+        assert!(!valid_unit_name("$(id).service"));
+        assert!(!valid_unit_name("-rf.service"));
+        assert!(!valid_unit_name("cups"));
+        assert!(!valid_unit_name(".service"));
+        assert!(!valid_unit_name("a@b@c.service"));
+    }
+
+    #[test]
+    fn test_parse_list_timers() {
+        let json = r#"[{"next":1760486400000000,"left":1760400000000000,"last":null,"passed":null,"unit":"fstrim.timer","activates":"fstrim.service"},{"next":null,"left":null,"last":1760390000123456,"passed":1760390000123456,"unit":"old.timer","activates":"old.service"}]"#;
+        let times = parse_list_timers(json);
+        assert_eq!(
+            times["fstrim.timer"],
+            TimerTimes {
+                next: Some(1_760_486_400),
+                last: None
+            }
+        );
+        assert_eq!(times["old.timer"].last, Some(1_760_390_000));
+        assert!(parse_list_timers("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_unit_files_skips_untoggleable_states() {
+        let stdout = "\
+sshd.service          enabled  disabled
+cups.service          disabled enabled
+dbus.service          static   -
+bad.service           masked   enabled
+";
+        let active: HashSet<String> = ["cups.service".to_string()].into();
+        let services = parse_unit_files(stdout, &active);
+
+        let names: Vec<&str> = services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["sshd.service", "cups.service", "bad.service"]);
+        assert!(services[2].masked && !services[2].enabled);
+        assert!(!services[2].differs_from_preset());
+        assert!(services[0].enabled && !services[0].active);
+        assert!(!services[1].enabled && services[1].active);
+        assert_eq!(services[0].preset, Some(false));
+        assert_eq!(services[1].preset, Some(true));
+    }
+
+    #[test]
+    fn test_parse_requirements() {
+        let stdout = "Id=cups.service\nRequires=cups.socket sysinit.target\nRequisite=\nBindsTo=\nWants=\nConflicts=shutdown.target\n\nId=sshd.service\nWants=sshdgenkeys.service\nConflicts=\n";
+        let reqs = parse_requirements(stdout);
+        assert_eq!(reqs.len(), 2);
+        assert_eq!(reqs[0].requires, ["cups.socket", "sysinit.target"]);
+        assert!(reqs[0].conflicts.is_empty());
+        assert_eq!(reqs[1].wants, ["sshdgenkeys.service"]);
+    }
+
+    #[test]
+    fn test_parse_show_properties() {
+        let info = parse_show(
+            "Description=OpenSSH Daemon\nActiveState=active\nSubState=running\nMainPID=812\nAfter=network.target sshdgenkeys.service\nBefore=\n",
+        );
+        assert_eq!(info.description, "OpenSSH Daemon");
+        assert_eq!(info.sub_state, "running");
+        assert_eq!(info.main_pid, 812);
+        assert_eq!(info.after, ["network.target", "sshdgenkeys.service"]);
+        assert!(info.before.is_empty());
+    }
+
+    #[test]
+    fn test_is_unconfined_apparmor_and_selinux() {
+        let mut info = ServiceInfo {
+            confinement: "unconfined".into(),
+            ..Default::default()
+        };
+        assert!(info.is_unconfined());
+        info.confinement = "system_u:system_r:unconfined_service_t:s0".into();
+        assert!(info.is_unconfined());
+        info.confinement = "system_u:system_r:sshd_t:s0-s0:c0.c1023".into();
+        assert!(!info.is_unconfined());
+        info.confinement = "/usr/sbin/cupsd (enforce)".into();
+        assert!(!info.is_unconfined());
+    }
+
+    #[test]
+    fn test_dbus_file_activates() {
+        let contents = "[D-BUS Service]\nName=org.bluez\nExec=/bin/false\nUser=root\nSystemdService=dbus-org.bluez.service\n";
+        assert!(dbus_file_activates(contents, "dbus-org.bluez.service", ""));
+        assert!(dbus_file_activates(
+            contents,
+            "bluetooth.service",
+            "org.bluez"
+        ));
+        assert!(!dbus_file_activates(contents, "cups.service", ""));
+    }
+
+    #[test]
+    fn test_changes_script_orders_and_quotes() {
+        let changes = vec![
+            PendingChange {
+                service: "docker.service".into(),
+                scope: ServiceScope::System,
+                action: ChangeAction::Disable,
+                runtime: false,
+            },
+            PendingChange {
+                service: "it's.service".into(),
+                scope: ServiceScope::User,
+                action: ChangeAction::Enable,
+                runtime: true,
+            },
+            PendingChange {
+                service: "syncthing.service".into(),
+                scope: ServiceScope::Global,
+                action: ChangeAction::Enable,
+                runtime: false,
+            },
+            PendingChange {
+                service: "cups.service".into(),
+                scope: ServiceScope::System,
+                action: ChangeAction::Restart,
+                runtime: false,
+            },
+            PendingChange {
+                service: "sshd.service".into(),
+                scope: ServiceScope::System,
+                action: ChangeAction::EnableOnly,
+                runtime: false,
+            },
+        ];
+        assert_eq!(
+            changes_script(&changes),
+            "systemctl disable --now -- 'docker.service'; systemctl --user enable --runtime --now -- 'it'\\''s.service'; systemctl --global enable -- 'syncthing.service'; systemctl restart -- 'cups.service'; systemctl enable -- 'sshd.service'"
+        );
+    }
+
+    #[test]
+    fn test_schedule_batches_put_global_with_root() {
+        let change = |service: &str, scope| PendingChange {
+            service: service.into(),
+            scope,
+            action: ChangeAction::Enable,
+            runtime: false,
+        };
+        let changes = vec![
+            change("syncthing.service", ServiceScope::Global),
+            change("pipewire.service", ServiceScope::User),
+            change("sshd.service", ServiceScope::System),
+        ];
+        let batches: Vec<(bool, Vec<String>)> = schedule_batches(&changes)
+            .into_iter()
+            .map(|(root, batch)| (root, batch.into_iter().map(|c| c.service).collect()))
+            .collect();
+        assert_eq!(
+            batches,
+            [
+                (false, vec!["pipewire.service".to_string()]),
+                (
+                    true,
+                    vec!["syncthing.service".to_string(), "sshd.service".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clone_name_adds_suffix() {
+        assert_eq!(
+            clone_name("syncthing.service", "syncthing-work"),
+            Ok("syncthing-work.service".to_string())
+        );
+        assert_eq!(
+            clone_name("getty@.service", "mygetty@.service"),
+            Ok("mygetty@.service".to_string())
+        );
+    }
+
+    #[test]
+    fn test_clone_name_rejects_bad_names() {
+        assert!(clone_name("sshd.service", "sshd").is_err());
+        assert!(clone_name("sshd.service", "  ").is_err());
+        assert!(clone_name("sshd.service", "../sshd2").is_err());
+        assert!(clone_name("sshd.service", "my sshd").is_err());
+    }
+
+    #[test]
+    fn test_enablement_links() {
+        let dir = std::env::temp_dir().join(format!("comma-links-{}", std::process::id()));
+        let wants = dir.join("multi-user.target.wants");
+        std::fs::create_dir_all(&wants).unwrap();
+        let target = "/usr/lib/systemd/system/sshd.service";
+        std::os::unix::fs::symlink(target, wants.join("sshd.service")).unwrap();
+        std::os::unix::fs::symlink(target, dir.join("ssh.service")).unwrap();
+        std::os::unix::fs::symlink("/dev/null", dir.join("other.service")).unwrap();
+
+        let links = enablement_links(std::slice::from_ref(&dir), "sshd.service");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let paths: Vec<String> = links.iter().map(|(link, _)| link.clone()).collect();
+        assert_eq!(
+            paths,
+            [
+                dir.join("multi-user.target.wants/sshd.service")
+                    .display()
+                    .to_string(),
+                dir.join("ssh.service").display().to_string(),
+            ]
+        );
+        assert!(links.iter().all(|(_, t)| t == target));
+    }
+
+    #[test]
+    fn test_parse_need_reload() {
+        let output = "Id=a.service\nNeedDaemonReload=no\n\nNeedDaemonReload=yes\nId=b.service\n\nId=c.service\nNeedDaemonReload=yes\n";
+        assert_eq!(parse_need_reload(output), ["b.service", "c.service"]);
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(
+            unescape("dev-disk-by\\x2duuid-1a2b"),
+            "dev-disk-by-uuid-1a2b"
+        );
+        assert_eq!(unescape("caf\\xc3\\xa9"), "café");
+        assert_eq!(unescape("plain\\xzz"), "plain\\xzz");
+    }
+
+    #[test]
+    fn test_instance_name() {
+        assert_eq!(
+            instance_name("getty@tty1.service"),
+            Some("tty1".to_string())
+        );
+        assert_eq!(
+            instance_name("systemd-fsck@dev-disk-by\\x2dlabel-home.service"),
+            Some("dev-disk-by-label-home".to_string())
+        );
+        assert_eq!(instance_name("getty@.service"), None);
+        assert_eq!(instance_name("sshd.service"), None);
+    }
+
+    #[test]
+    fn test_parse_unit_files_keeps_escaped_names() {
+        let output = "systemd-fsck@dev-disk-by\\x2duuid-1a2b.service enabled enabled\n";
+        let services = parse_unit_files(output, &HashSet::new());
+        assert_eq!(
+            services[0].name,
+            "systemd-fsck@dev-disk-by\\x2duuid-1a2b.service"
+        );
+    }
+
+    #[test]
+    fn test_is_generated() {
+        let info = |path: &str| ServiceInfo {
+            fragment_path: path.to_string(),
+            ..Default::default()
+        };
+        assert!(info("/run/systemd/generator/home.mount").is_generated());
+        assert!(info("/run/systemd/generator.late/apache2.service").is_generated());
+        assert!(!info("/usr/lib/systemd/system/sshd.service").is_generated());
+    }
+
+    #[test]
+    fn test_parse_show_sysv_source() {
+        let info = parse_show(
+            "FragmentPath=/run/systemd/generator.late/apache-htcacheclean.service\nSourcePath=/etc/init.d/apache-htcacheclean\n",
+        );
+        assert!(info.is_generated());
+        assert!(info.is_sysv());
+    }
+
+    #[test]
+    fn test_parse_show_graphical_session() {
+        let waybar = parse_show("PartOf=graphical-session.target\nBindsTo=\nWantedBy=\n");
+        assert!(waybar.graphical_session);
+        let wanted = parse_show("PartOf=\nWantedBy=default.target graphical-session.target\n");
+        assert!(wanted.graphical_session);
+        let pipewire = parse_show("PartOf=\nWantedBy=default.target\n");
+        assert!(!pipewire.graphical_session);
+        // Only the exact target, not lookalikes
+        let pre =
+            parse_show("After=graphical-session-pre.target\nPartOf=graphical-session-pre.target\n");
+        assert!(!pre.graphical_session);
+    }
+
+    #[test]
+    fn test_parse_timer_runs() {
+        let stdout = "Triggers=reflector.service\nNextElapseUSecRealtime=@1760400000\n\n\
+Triggers=fstrim.service\nNextElapseUSecRealtime=\n\n\
+Triggers=reflector.service\nNextElapseUSecRealtime=@1760300000\n";
+        let runs = parse_timer_runs(stdout);
+        assert_eq!(runs.get("reflector.service"), Some(&1_760_300_000));
+        assert!(!runs.contains_key("fstrim.service"));
+    }
+
+    #[test]
+    fn test_format_until() {
+        assert_eq!(format_until(100, 130), "now");
+        assert_eq!(format_until(1_000 + 12 * 60, 1_000), "in 12min");
+        assert_eq!(format_until(3 * 3_600 + 20 * 60, 0), "in 3h 20min");
+        assert_eq!(format_until(2 * 86_400 + 4 * 3_600 + 59, 0), "in 2d 4h");
+        assert_eq!(format_ago(0, 3 * 3_600), "3h ago");
+        assert_eq!(format_ago(100, 130), "just now");
+    }
+
+    #[test]
+    fn test_paired_trigger() {
+        let triggers: HashMap<String, bool> = [
+            ("cups.socket".to_string(), true),
+            ("reflector.timer".to_string(), false),
+            ("systemd-journald-audit.socket".to_string(), true),
+        ]
+        .into();
+        assert_eq!(
+            paired_trigger("cups.service", &triggers).as_deref(),
+            Some("cups.socket")
+        );
+        assert_eq!(
+            paired_trigger("reflector.service", &triggers).as_deref(),
+            Some("reflector.timer")
+        );
+        assert_eq!(paired_trigger("systemd-journald.service", &triggers), None);
+        assert_eq!(paired_trigger("cups.socket", &triggers), None);
+    }
+
+    #[test]
+    fn test_parse_memory_current() {
+        let stdout = "MemoryCurrent=104857600\n\nMemoryCurrent=[not set]\n\nMemoryCurrent=18446744073709551615\n\nMemoryCurrent=4194304\n";
+        assert_eq!(parse_memory_current(stdout), 104_857_600 + 4_194_304);
+        assert_eq!(format_bytes(104_857_600 + 4_194_304), "104 MiB");
+        assert_eq!(format_bytes(1_395_864_371), "1.3 GiB");
+    }
+
+    #[test]
+    fn test_parse_memory_by_unit() {
+        let stdout = "Id=cups.service\nMemoryCurrent=4194304\n\nId=idle.service\nMemoryCurrent=[not set]\n\nMemoryCurrent=18446744073709551615\nId=off.service\n";
+        assert_eq!(
+            parse_memory_by_unit(stdout),
+            [("cups.service".to_string(), 4_194_304)]
+        );
+    }
+
+    #[test]
+    fn test_stop_settings() {
+        assert_eq!(stop_settings("15s").unwrap(), ["TimeoutStopSec=15s"]);
+        assert_eq!(
+            stop_settings("10s KillMode=mixed").unwrap(),
+            ["TimeoutStopSec=10s", "KillMode=mixed"]
+        );
+        assert!(stop_settings("ExecStop=/bin/true").is_err());
+        assert!(stop_settings("5s\nExecStart=x").is_err());
+        assert!(stop_settings("").is_err());
+    }
+
+    #[test]
+    fn test_parse_path_directives() {
+        let unit = "# /usr/lib/systemd/system/cups.path\n[Unit]\nDescription=CUPS Scheduler\n\n[Path]\nPathExists=/var/cache/cups/org.cups.cupsd\n# PathChanged=/commented\n\n[Install]\nWantedBy=multi-user.target\n";
+        assert_eq!(
+            parse_path_directives(unit),
+            ["PathExists=/var/cache/cups/org.cups.cupsd"]
+        );
+    }
+
+    #[test]
+    fn test_parse_root_services() {
+        let output = "Id=sshd.service\nUser=\nDynamicUser=no\n\nId=chronyd.service\nUser=chrony\nDynamicUser=no\n\nId=systemd-resolved.service\nUser=\nDynamicUser=yes\n";
+        let root = parse_root_services(output);
+        assert_eq!(root.len(), 1);
+        assert!(root.contains("sshd.service"));
+    }
+
+    #[test]
+    fn test_runs_as() {
+        let info = ServiceInfo {
+            user: "nobody".into(),
+            group: "nogroup".into(),
+            ..Default::default()
+        };
+        assert_eq!(info.runs_as(), "nobody:nogroup");
+        assert_eq!(ServiceInfo::default().runs_as(), "root");
+    }
+
+    #[test]
+    fn test_capability_summary() {
+        let info = parse_show(
+            "CapabilityBoundingSet=cap_net_bind_service cap_setuid cap_setgid cap_audit_write\n",
+        );
+        assert_eq!(
+            info.capability_summary(),
+            "can bind low ports, can switch user/group"
+        );
+        assert_eq!(
+            parse_show("CapabilityBoundingSet=\n").capability_summary(),
+            "none"
+        );
+
+        let all = (0..41)
+            .map(|i| format!("cap_{i}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let info = parse_show(&format!("CapabilityBoundingSet={all}\n"));
+        assert_eq!(info.capability_summary(), "unrestricted (every capability)");
+    }
+
+    #[test]
+    fn test_start_hint() {
+        assert_eq!(
+            start_hint("notify", "timeout", "failed"),
+            Some("Type=notify service never signaled readiness")
+        );
+        assert_eq!(
+            start_hint("notify", "success", "activating"),
+            Some("Type=notify service hasn't signaled readiness yet")
+        );
+        assert_eq!(
+            start_hint("simple", "timeout", "failed"),
+            Some("start exceeded TimeoutStartSec")
+        );
+        assert_eq!(start_hint("simple", "success", "active"), None);
+    }
+
+    #[test]
+    fn test_classify_systemctl_errors() {
+        let classify = SystemctlError::classify;
+        assert_eq!(
+            classify(Some(126), "", true),
+            SystemctlError::PolkitDismissed
+        );
+        assert_eq!(
+            classify(
+                Some(127),
+                "Error executing command as another user: Not authorized",
+                true
+            ),
+            SystemctlError::NotAuthorized
+        );
+        assert!(matches!(
+            classify(
+                Some(5),
+                "Failed to enable unit: Unit file foo.service does not exist.\n",
+                true
+            ),
+            SystemctlError::NoSuchUnit(_)
+        ));
+        assert_eq!(
+            classify(
+                Some(1),
+                "Failed to start unit: Unit cups.service is masked.",
+                false
+            ),
+            SystemctlError::Masked("Failed to start unit: Unit cups.service is masked.".into())
+        );
+        assert!(matches!(
+            classify(Some(1), "Failed to stop x.service: Access denied", false),
+            SystemctlError::PermissionDenied(_)
+        ));
+        // 126 only means a dismissed dialog when pkexec ran
+        assert!(matches!(
+            classify(Some(126), "oops", false),
+            SystemctlError::Failed(_)
+        ));
+    }
+
+    fn change(service: &str, action: ChangeAction) -> PendingChange {
+        PendingChange {
+            service: service.into(),
+            scope: ServiceScope::System,
+            action,
+            runtime: false,
+        }
+    }
+
+    #[test]
+    fn test_batch_script() {
+        let enable = change("cups.service", ChangeAction::Enable);
+        let stop = PendingChange {
+            runtime: true,
+            ..change("sshd.service", ChangeAction::DisableOnly)
+        };
+        let script = batch_script(&[&enable, &stop]);
+        assert!(script.starts_with(&batch_prelude()));
+        assert!(script.ends_with(
+            "r 10 0 enable -- 'cups.service' && r 10 0 start -- 'cups.service'; \
+             r 10 0 disable --runtime -- 'sshd.service'; exit 0"
+        ));
+    }
+
+    #[test]
+    fn test_batch_finished_counts_whole_changes() {
+        let a = change("a.service", ChangeAction::Enable);
+        let b = change("b.service", ChangeAction::Stop);
+        let changes = [&a, &b];
+        assert_eq!(batch_finished(&changes, &[]), (0, true));
+        assert_eq!(
+            batch_finished(&changes, &[0]),
+            (0, false),
+            "start still running"
+        );
+        assert_eq!(
+            batch_finished(&changes, &[1]),
+            (1, true),
+            "failed enable skips start"
+        );
+        assert_eq!(batch_finished(&changes, &[0, 0, 0]), (2, true));
+    }
+
+    #[test]
+    fn test_batch_outcomes_skip_after_failure() {
+        let a = change("a.service", ChangeAction::Enable);
+        let b = change("b.service", ChangeAction::Disable);
+        let c = change("c.service", ChangeAction::Stop);
+        let stdout = "1\t12\t0\tFailed to enable unit: Unit file a.service does not exist.\x1eRun daemon-reload.\n0\t3\t1\t\n";
+        let outcomes = batch_outcomes(&[&a, &b, &c], stdout, Some(SystemctlError::Timeout(30)));
+
+        let (boot, now) = &outcomes[0];
+        match boot {
+            Some(("enable", Err(SystemctlError::NoSuchUnit(stderr)), ..)) => {
+                assert!(stderr.ends_with("does not exist.\nRun daemon-reload."));
+            }
+            other => panic!("unexpected {other:?}"),
         }
+        assert!(now.is_none(), "start never ran after the failed enable");
+        let (boot, now) = &outcomes[1];
+        assert_eq!(boot.as_ref().map(|s| &s.1), Some(&Ok(())));
+        assert_eq!(boot.as_ref().map(|s| s.3), Some(1), "retried once");
+        // Output ended: the rest get why the batch stopped
+        assert_eq!(
+            now.as_ref().map(|s| &s.1),
+            Some(&Err(SystemctlError::Timeout(30)))
+        );
+        assert_eq!(
+            outcomes[2].1.as_ref().map(|s| &s.1),
+            Some(&Err(SystemctlError::Timeout(30)))
+        );
     }
 }
@@ -0,0 +1,254 @@
+use anyhow::{bail, Context, Result};
+
+use crate::config::user_unit_dir;
+use crate::systemd::valid_unit_name;
+
+pub struct Prompt {
+    pub key: &'static str,
+    pub question: &'static str,
+    pub default: &'static str,
+}
+
+/// A ready-made user service. `{key}` placeholders in file names and
+/// bodies are filled from the prompt answers.
+pub struct Template {
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub prompts: &'static [Prompt],
+    pub files: &'static [(&'static str, &'static str)],
+}
+
+pub const TEMPLATES: &[Template] = &[
+    Template {
+        name: "Syncthing instance",
+        summary: "Extra Syncthing instance with its own config directory",
+        prompts: &[
+            Prompt {
+                key: "name",
+                question: "Instance name",
+                default: "work",
+            },
+            Prompt {
+                key: "home",
+                question: "Config directory",
+                default: "%h/.local/state/syncthing-work",
+            },
+        ],
+        files: &[(
+            "syncthing-{name}.service",
+            "[Unit]
+Description=Syncthing ({name})
+After=network-online.target
+Wants=network-online.target
+
+[Service]
+ExecStart=/usr/bin/syncthing serve --no-browser --no-restart --home={home}
+Restart=on-failure
+SuccessExitStatus=3 4
+RestartForceExitStatus=3 4
+
+[Install]
+WantedBy=default.target
+",
+        )],
+    },
+    Template {
+        name: "Restic backup + timer",
+        summary: "Scheduled restic snapshot of chosen paths",
+        prompts: &[
+            Prompt {
+                key: "name",
+                question: "Backup name",
+                default: "home",
+            },
+            Prompt {
+                key: "repository",
+                question: "Restic repository",
+                default: "sftp:backup@nas:/srv/restic",
+            },
+            Prompt {
+                key: "password_file",
+                question: "Password file",
+                default: "%h/.config/restic/password",
+            },
+            Prompt {
+                key: "paths",
+                question: "Paths to back up",
+                default: "%h/Documents %h/Pictures",
+            },
+            Prompt {
+                key: "schedule",
+                question: "When (OnCalendar)",
+                default: "daily",
+            },
+        ],
+        files: &[
+            (
+                "restic-{name}.service",
+                "[Unit]
+Description=Restic backup ({name})
+After=network-online.target
+Wants=network-online.target
+
+[Service]
+Type=oneshot
+Environment=RESTIC_REPOSITORY={repository}
+Environment=RESTIC_PASSWORD_FILE={password_file}
+ExecStart=/usr/bin/restic backup {paths}
+Nice=10
+IOSchedulingClass=idle
+",
+            ),
+            (
+                "restic-{name}.timer",
+                "[Unit]
+Description=Run restic backup ({name}) {schedule}
+
+[Timer]
+OnCalendar={schedule}
+Persistent=true
+RandomizedDelaySec=10min
+
+[Install]
+WantedBy=timers.target
+",
+            ),
+        ],
+    },
+    Template {
+        name: "Simple web app",
+        summary: "Long-running app restarted on failure",
+        prompts: &[
+            Prompt {
+                key: "name",
+                question: "Service name",
+                default: "webapp",
+            },
+            Prompt {
+                key: "directory",
+                question: "Working directory",
+                default: "%h/src/webapp",
+            },
+            Prompt {
+                key: "command",
+                question: "Start command (absolute path)",
+                default: "/usr/bin/python3 -m http.server",
+            },
+            Prompt {
+                key: "port",
+                question: "Port (exported as PORT)",
+                default: "8080",
+            },
+        ],
+        files: &[(
+            "{name}.service",
+            "[Unit]
+Description={name} web app
+After=network.target
+
+[Service]
+WorkingDirectory={directory}
+Environment=PORT={port}
+ExecStart={command}
+Restart=on-failure
+RestartSec=2
+
+[Install]
+WantedBy=default.target
+",
+        )],
+    },
+];
+
+/// Fill placeholders; answers are matched to prompts by position.
+pub fn render(template: &Template, answers: &[String]) -> Result<Vec<(String, String)>> {
+    if let Some(pos) = answers.iter().position(|a| a.contains('\n')) {
+        bail!("{} can't contain a newline", template.prompts[pos].question);
+    }
+    // What systemd would load, and no instance name: `@` only belongs in
+    // a template unit, which these aren't
+    let name_ok = |name: &str| valid_unit_name(name) && !name.contains('@');
+
+    template
+        .files
+        .iter()
+        .map(|(file_name, body)| {
+            let name = fill(file_name, template, answers);
+            if !name_ok(&name) {
+                bail!("Invalid unit file name: {name:?}");
+            }
+            Ok((name, fill(body, template, answers)))
+        })
+        .collect()
+}
+
+fn fill(text: &str, template: &Template, answers: &[String]) -> String {
+    template
+        .prompts
+        .iter()
+        .zip(answers)
+        .fold(text.to_string(), |acc, (prompt, answer)| {
+            acc.replace(&format!("{{{}}}", prompt.key), answer)
+        })
+}
+
+/// Write rendered files into the user unit directory, refusing to
+/// overwrite anything. Returns the created file names.
+pub fn install(files: &[(String, String)]) -> Result<Vec<String>> {
+    let dir = user_unit_dir().context("No config directory (HOME unset)")?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    if let Some((name, _)) = files.iter().find(|(name, _)| dir.join(name).exists()) {
+        bail!("{name} already exists in {}", dir.display());
+    }
+    for (name, body) in files {
+        let path = dir.join(name);
+        std::fs::write(&path, body)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(files.iter().map(|(name, _)| name.clone()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defaults(template: &Template) -> Vec<String> {
+        template
+            .prompts
+            .iter()
+            .map(|p| p.default.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_render_fills_every_placeholder() {
+        for template in TEMPLATES {
+            for (name, body) in render(template, &defaults(template)).unwrap() {
+                assert!(!name.contains('{'), "{name}");
+                assert!(!body.contains('{'), "{}: {body}", template.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_restic_pair() {
+        let restic = &TEMPLATES[1];
+        let files = render(restic, &defaults(restic)).unwrap();
+        let names: Vec<&str> = files.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["restic-home.service", "restic-home.timer"]);
+        assert!(files[1].1.contains("OnCalendar=daily"));
+    }
+
+    #[test]
+    fn test_render_rejects_path_in_name() {
+        let webapp = &TEMPLATES[2];
+        let mut answers = defaults(webapp);
+        answers[0] = "../evil".to_string();
+        assert!(render(webapp, &answers).is_err());
+        for name in ["my app", "tab\there", "web@app"] {
+            answers[0] = name.to_string();
+            assert!(render(webapp, &answers).is_err(), "{name}");
+        }
+    }
+}
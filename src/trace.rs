@@ -0,0 +1,325 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::os::unix::process::ExitStatusExt;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::{Context, Result};
+use crossterm::event::Event;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader as AsyncBufReader};
+use tokio::process::{ChildStdin, Command as AsyncCommand};
+
+use crate::demo::World;
+
+/// One line of a session trace file (JSON lines).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Entry {
+    Event {
+        event: Event,
+    },
+    Command {
+        argv: Vec<String>,
+        status: Option<i32>,
+        stdout: String,
+        stderr: String,
+    },
+}
+
+enum Trace {
+    Recording(Mutex<BufWriter<File>>),
+    Replaying(Mutex<Replay>),
+    Demo(Mutex<World>), // `--tutorial`: a pretend machine answers instead
+}
+
+struct Replay {
+    events: VecDeque<Event>,
+    commands: Vec<Option<Entry>>, // consumed entries become None
+}
+
+static TRACE: OnceLock<Trace> = OnceLock::new();
+
+/// `--record FILE`: log every input event and backend call, sanitized.
+pub fn start_recording(path: &Path) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let _ = TRACE.set(Trace::Recording(Mutex::new(BufWriter::new(file))));
+    Ok(())
+}
+
+/// `--replay FILE`: feed recorded events back in and answer backend calls
+/// from the recording instead of running them.
+pub fn start_replay(path: &Path) -> Result<()> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut replay = Replay {
+        events: VecDeque::new(),
+        commands: Vec::new(),
+    };
+    for (idx, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        let entry: Entry = serde_json::from_str(&line)
+            .with_context(|| format!("{}:{}: invalid trace entry", path.display(), idx + 1))?;
+        match entry {
+            Entry::Event { event } => replay.events.push_back(event),
+            command => replay.commands.push(Some(command)),
+        }
+    }
+    let _ = TRACE.set(Trace::Replaying(Mutex::new(replay)));
+    Ok(())
+}
+
+/// `--tutorial`: answer backend calls from the demo machine.
+pub fn start_demo() {
+    let _ = TRACE.set(Trace::Demo(Mutex::new(World::new())));
+}
+
+pub fn is_replaying() -> bool {
+    matches!(TRACE.get(), Some(Trace::Replaying(_)))
+}
+
+/// Nothing real is behind the backend (a replay or the tutorial), so
+/// don't start editors, monitors or anything else that bypasses it.
+pub fn is_offline() -> bool {
+    matches!(TRACE.get(), Some(Trace::Replaying(_) | Trace::Demo(_)))
+}
+
+pub fn is_demo() -> bool {
+    matches!(TRACE.get(), Some(Trace::Demo(_)))
+}
+
+/// Recording or replaying: every backend call should happen as it did.
+pub fn is_active() -> bool {
+    TRACE.get().is_some()
+}
+
+pub fn record_event(event: &Event) {
+    if let Some(Trace::Recording(_)) = TRACE.get() {
+        write(&Entry::Event {
+            event: event.clone(),
+        });
+    }
+}
+
+pub fn next_replay_event() -> Option<Event> {
+    match TRACE.get() {
+        Some(Trace::Replaying(replay)) => replay.lock().ok()?.events.pop_front(),
+        _ => None,
+    }
+}
+
+/// Drop-in for `Command::output()` that records or replays the call.
+pub fn output(cmd: &mut Command) -> io::Result<Output> {
+    let argv = argv(cmd);
+    if let Some(output) = replayed(&argv) {
+        return output;
+    }
+    let result = cmd.output();
+    record_command(argv, &result);
+    result
+}
+
+/// Async counterpart of [`output`] for tokio commands.
+pub async fn output_async(cmd: &mut AsyncCommand) -> io::Result<Output> {
+    let argv = argv(cmd.as_std());
+    if let Some(output) = replayed(&argv) {
+        return output;
+    }
+    let result = cmd.output().await;
+    record_command(argv, &result);
+    result
+}
+
+/// Like [`output_async`], but hands each stdout line to `line` as it's
+/// printed, for commands that report progress as they go. Stdin gets one
+/// newline up front and another after each line `line` returns true for;
+/// false closes it, for commands that wait for a go-ahead between steps.
+/// Replayed output arrives all at once.
+pub async fn output_lines(
+    cmd: &mut AsyncCommand,
+    mut line: impl FnMut(&str) -> bool,
+) -> io::Result<Output> {
+    let argv = argv(cmd.as_std());
+    if let Some(output) = replayed(&argv) {
+        if let Ok(output) = &output {
+            for text in String::from_utf8_lossy(&output.stdout).lines() {
+                line(text);
+            }
+        }
+        return output;
+    }
+    let result = stream_lines(cmd, line).await;
+    record_command(argv, &result);
+    result
+}
+
+async fn stream_lines(
+    cmd: &mut AsyncCommand,
+    mut line: impl FnMut(&str) -> bool,
+) -> io::Result<Output> {
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    let mut stderr_pipe = child.stderr.take();
+    let stderr = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        if let Some(pipe) = &mut stderr_pipe {
+            let _ = pipe.read_to_end(&mut buf).await;
+        }
+        buf
+    });
+    let mut stdin = child.stdin.take();
+    go_ahead(&mut stdin).await;
+    let mut stdout = String::new();
+    if let Some(pipe) = child.stdout.take() {
+        let mut lines = AsyncBufReader::new(pipe).lines();
+        while let Some(text) = lines.next_line().await? {
+            if line(&text) {
+                go_ahead(&mut stdin).await;
+            } else {
+                stdin = None;
+            }
+            stdout.push_str(&text);
+            stdout.push('\n');
+        }
+    }
+    let status = child.wait().await?;
+    Ok(Output {
+        status,
+        stdout: stdout.into_bytes(),
+        stderr: stderr.await.unwrap_or_default(),
+    })
+}
+
+async fn go_ahead(stdin: &mut Option<ChildStdin>) {
+    if let Some(pipe) = stdin {
+        // Gone already if the command doesn't read it
+        if pipe.write_all(b"\n").await.is_err() {
+            *stdin = None;
+        }
+    }
+}
+
+fn argv(cmd: &Command) -> Vec<String> {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|arg| sanitize(&arg.to_string_lossy()))
+        .collect()
+}
+
+fn replayed(argv: &[String]) -> Option<io::Result<Output>> {
+    let replay = match TRACE.get() {
+        Some(Trace::Replaying(replay)) => replay,
+        Some(Trace::Demo(world)) => return Some(world.lock().ok()?.answer(argv)),
+        _ => return None,
+    };
+    let mut replay = replay.lock().ok()?;
+    // First unconsumed recording of the same invocation
+    let slot = replay.commands.iter_mut().find(
+        |slot| matches!(slot, Some(Entry::Command { argv: recorded, .. }) if recorded == argv),
+    );
+    match slot.and_then(Option::take) {
+        Some(Entry::Command {
+            status,
+            stdout,
+            stderr,
+            ..
+        }) => Some(Ok(Output {
+            // Wait status encoding: exit code in the high byte, else a signal
+            status: ExitStatus::from_raw(status.map(|code| code << 8).unwrap_or(9)),
+            stdout: stdout.into_bytes(),
+            stderr: stderr.into_bytes(),
+        })),
+        _ => Some(Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("not in replay trace: {}", argv.join(" ")),
+        ))),
+    }
+}
+
+fn record_command(argv: Vec<String>, result: &io::Result<Output>) {
+    if !matches!(TRACE.get(), Some(Trace::Recording(_))) {
+        return;
+    }
+    let entry = match result {
+        Ok(output) => Entry::Command {
+            argv,
+            status: output.status.code(),
+            stdout: sanitize(&String::from_utf8_lossy(&output.stdout)),
+            stderr: sanitize(&String::from_utf8_lossy(&output.stderr)),
+        },
+        Err(e) => Entry::Command {
+            argv,
+            status: None,
+            stdout: String::new(),
+            stderr: e.to_string(),
+        },
+    };
+    write(&entry);
+}
+
+fn write(entry: &Entry) {
+    if let Some(Trace::Recording(file)) = TRACE.get() {
+        if let (Ok(mut file), Ok(line)) = (file.lock(), serde_json::to_string(entry)) {
+            let _ = writeln!(file, "{line}");
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Strip the home directory, user name, and host name so traces can be
+/// attached to public bug reports.
+fn sanitize(text: &str) -> String {
+    let mut out = text.to_string();
+    if let Some(home) = std::env::var_os("HOME") {
+        let home = home.to_string_lossy();
+        if home.len() > 1 {
+            out = out.replace(home.as_ref(), "~");
+        }
+    }
+    if let Ok(host) = std::fs::read_to_string("/etc/hostname") {
+        out = redact(&out, host.trim(), "<host>");
+    }
+    if let Some(user) = std::env::var_os("USER") {
+        out = redact(&out, &user.to_string_lossy(), "<user>");
+    }
+    out
+}
+
+fn redact(text: &str, secret: &str, placeholder: &str) -> String {
+    // Very short names (e.g. "pi") would mangle unrelated words
+    if secret.len() < 3 {
+        return text.to_string();
+    }
+    text.replace(secret, placeholder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn test_entry_roundtrip() {
+        let entry = Entry::Event {
+            event: Event::Key(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE)),
+        };
+        let line = serde_json::to_string(&entry).unwrap();
+        assert!(line.starts_with("{\"kind\":\"event\""));
+        assert!(matches!(
+            serde_json::from_str::<Entry>(&line).unwrap(),
+            Entry::Event { .. }
+        ));
+    }
+
+    #[test]
+    fn test_redact_skips_short_names() {
+        assert_eq!(redact("leo@laptop", "laptop", "<host>"), "leo@<host>");
+        assert_eq!(redact("pipewire", "pi", "<user>"), "pipewire");
+    }
+}
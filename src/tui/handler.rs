@@ -1,4 +1,4 @@
-use crossterm::event::{Event, KeyCode, KeyEventKind};
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
 
 use crate::app::{App, Mode};
 
@@ -6,51 +6,187 @@ use crate::app::{App, Mode};
 pub enum Action {
     None,
     ApplyChanges,
+    ScheduleChanges(String),
+    UpdateDescriptions,
+    EditUnit { name: String, full: bool }, // full: the whole file, not a drop-in
+    FollowLogs,                            // (re)start journalctl -f for app.follow
+    Page(String),                          // hand this text to $PAGER
 }
 
 pub fn handle_event(app: &mut App, event: Event) -> Action {
+    if let Event::Resize(..) = event {
+        // Nothing to do until the next frame lays out at the new size and
+        // feeds back the re-clamped list and modal scroll (fit_viewport)
+        return Action::None;
+    }
+    if let Event::Paste(text) = &event {
+        // Only the filter takes free text; elsewhere a paste is dropped
+        // rather than replayed as key presses
+        if app.mode == Mode::Filter {
+            app.paste_filter(text);
+        }
+        return Action::None;
+    }
     if let Event::Key(key) = event {
         if key.kind != KeyEventKind::Press {
             return Action::None;
         }
 
-        return match app.mode {
+        if app.mode == Mode::Normal {
+            // Undo and redo move through the history rather than add to it
+            match key.code {
+                KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    app.redo();
+                    return Action::None;
+                }
+                KeyCode::Char('u') => {
+                    app.undo();
+                    return Action::None;
+                }
+                _ => {}
+            }
+            if let Some((n, fold)) = category_key(key.code, key.modifiers) {
+                app.jump_to_category(n, fold);
+                return Action::None;
+            }
+        }
+
+        let before = app.snapshot();
+        let action = match app.mode {
             Mode::Normal => handle_normal(app, key.code),
             Mode::Filter => handle_filter(app, key.code),
             Mode::Confirm => handle_confirm(app, key.code),
-            Mode::Applying => Action::None, // ignore input while applying
+            Mode::Applying => {
+                let ctrl_c =
+                    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.code == KeyCode::Esc || ctrl_c {
+                    app.cancel_apply();
+                }
+                Action::None
+            }
             Mode::Info => handle_info(app, key.code),
+            Mode::Compare | Mode::Drift => handle_compare(app, key.code),
+            Mode::Schedule => handle_schedule(app, key.code),
+            Mode::StrictConfirm => handle_strict_confirm(app, key.code),
+            Mode::TemplatePick => handle_template_pick(app, key.code),
+            Mode::Wizard => handle_wizard(app, key.code),
+            Mode::Clone => handle_clone(app, key.code),
+            Mode::Jobs | Mode::NextBoot => handle_compare(app, key.code),
+            Mode::Pair => handle_pair(app, key.code),
+            Mode::Scheduler => handle_scheduler(app, key.code),
+            Mode::Hook => handle_hook(app, key.code),
+            Mode::Results => handle_results(app, key.code),
+            Mode::StopTimeout => handle_stop_timeout(app, key.code),
+            Mode::Logs => handle_logs(app, key.code),
+            Mode::QuitConfirm => handle_quit_confirm(app, key.code),
+            Mode::Follow => handle_follow(app, key.code),
+            Mode::Advisor => handle_advisor(app, key.code),
+            Mode::Profiles => handle_profiles(app, key.code),
+            Mode::ProfileName => handle_profile_name(app, key.code),
+            Mode::Blame => handle_blame(app, key.code),
+            Mode::Deps => handle_deps(app, key.code),
+            Mode::UnitFile => handle_unit_file(app, key.code),
+            Mode::Visual => handle_visual(app, key.code),
+            Mode::CategoryBulk => handle_category_bulk(app, key.code),
         };
+        app.record_undo(before);
+        return action;
     }
     Action::None
 }
 
+/// 1-9 jump to a category header; Shift folds it as well. Shift+digit
+/// arrives as the digit with SHIFT, or as the US layout's `!`..`(`.
+fn category_key(code: KeyCode, modifiers: KeyModifiers) -> Option<(usize, bool)> {
+    let KeyCode::Char(c) = code else {
+        return None;
+    };
+    match c {
+        '1'..='9' => Some((
+            c as usize - '0' as usize,
+            modifiers.contains(KeyModifiers::SHIFT),
+        )),
+        _ => "!@#$%^&*(".find(c).map(|idx| (idx + 1, true)),
+    }
+}
+
 fn handle_normal(app: &mut App, code: KeyCode) -> Action {
     match code {
-        KeyCode::Char('q') => {
-            app.should_quit = true;
-        }
+        KeyCode::Char('q') => app.request_quit(),
         KeyCode::Up | KeyCode::Char('k') => app.move_cursor(-1),
         KeyCode::Down | KeyCode::Char('j') => app.move_cursor(1),
-        KeyCode::Char(' ') => app.toggle_current(),
-        KeyCode::Enter => {
-            if app.has_pending_changes() {
-                app.mode = Mode::Confirm;
-            }
+        KeyCode::Char(' ') => {
+            app.toggle_current();
+            return instant(app);
         }
+        KeyCode::Enter if app.has_pending_changes() => return review(app),
         KeyCode::Tab => {
             let _ = app.switch_tab();
         }
         KeyCode::Left | KeyCode::Char('h') => app.toggle_collapse(),
         KeyCode::Right | KeyCode::Char('l') => app.toggle_collapse(),
-        KeyCode::Esc => {
-            if !app.filter.is_empty() {
-                app.filter.clear();
-                app.rebuild_visible();
-                app.cursor = 0;
-            }
+        KeyCode::Esc if !app.filter.is_empty() => {
+            app.filter.clear();
+            app.rebuild_visible();
+            app.cursor = 0;
         }
         KeyCode::Char('i') => app.show_info(),
+        KeyCode::Char('C') => app.show_compare(),
+        KeyCode::Char('m') => {
+            app.toggle_mask();
+            return instant(app);
+        }
+        KeyCode::Char('s') => {
+            app.cycle_run_change();
+            return instant(app);
+        }
+        KeyCode::Char('I') => app.toggle_instant(),
+        KeyCode::Char('V') => app.begin_visual(),
+        KeyCode::Char('x') => app.toggle_failure_lines(),
+        KeyCode::Char('M') => app.toggle_maintenance(),
+        KeyCode::Char('A') => app.apply_presets(),
+        KeyCode::Char('a') => app.open_advisor(),
+        KeyCode::Char('p') => app.open_profiles(),
+        KeyCode::Char('B') => app.record_boot(),
+        KeyCode::Char('b') => app.show_blame(),
+        KeyCode::Char('d') => app.show_deps(),
+        KeyCode::Char('c') => app.show_unit_file(),
+        KeyCode::Char('e') => {
+            if let Some(name) = app.begin_edit() {
+                return Action::EditUnit { name, full: false };
+            }
+        }
+        KeyCode::Char('E') => app.export_state(),
+        KeyCode::Char('D') => {
+            app.show_drift();
+            return open_paged(app);
+        }
+        KeyCode::Char('N') => app.open_templates(),
+        KeyCode::Char('y') => app.begin_clone(),
+        KeyCode::Char('r') => app.toggle_runtime(),
+        KeyCode::Char('R') => app.reload_daemon(),
+        KeyCode::Char('P') => app.toggle_root_filter(),
+        KeyCode::Char('J') => app.show_jobs(),
+        KeyCode::Char('n') => app.show_next_boot(),
+        KeyCode::Char('S') => app.open_schedulers(),
+        KeyCode::Char('H') => app.suggest_hook(),
+        KeyCode::Char('F') => app.retry_failed(),
+        KeyCode::Char('L') => {
+            app.show_results();
+            return open_paged(app);
+        }
+        KeyCode::Char('o') => {
+            app.show_logs();
+            return open_paged(app);
+        }
+        KeyCode::Char('f') if app.begin_follow() => return Action::FollowLogs,
+        KeyCode::Char('?') => app.show_legend = !app.show_legend,
+        KeyCode::Char('U') => {
+            if app.config.descriptions_url.is_some() {
+                return Action::UpdateDescriptions;
+            }
+            app.notice = Some("Set descriptions_url in config.toml to enable updates".to_string());
+        }
         KeyCode::Char('/') => {
             app.mode = Mode::Filter;
             app.filter.clear();
@@ -60,6 +196,31 @@ fn handle_normal(app: &mut App, code: KeyCode) -> Action {
     Action::None
 }
 
+/// With `pager = true` a view opens straight in $PAGER. Views with nothing
+/// to page (a journal permission problem, say) stay as modals.
+fn open_paged(app: &mut App) -> Action {
+    if !app.config.pager {
+        return Action::None;
+    }
+    let Some(text) = app.pager_text() else {
+        return Action::None;
+    };
+    app.mode = Mode::Normal;
+    app.logs = None;
+    Action::Page(text)
+}
+
+/// `|` in a text view.
+fn page(app: &mut App) -> Action {
+    match app.pager_text() {
+        Some(text) => Action::Page(text),
+        None => {
+            app.notice = Some("Nothing to show in a pager here".to_string());
+            Action::None
+        }
+    }
+}
+
 fn handle_filter(app: &mut App, code: KeyCode) -> Action {
     match code {
         KeyCode::Esc => {
@@ -94,21 +255,418 @@ fn handle_info(app: &mut App, code: KeyCode) -> Action {
             app.mode = Mode::Normal;
             app.info = None;
         }
+        KeyCode::Char('|') => return page(app),
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.modal_scroll = app.modal_scroll.saturating_sub(1);
+        }
+        KeyCode::Down | KeyCode::Char('j') => app.modal_scroll += 1,
         _ => {}
     }
     Action::None
 }
 
+fn handle_unit_file(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Esc | KeyCode::Char('c') | KeyCode::Char('q') => {
+            app.mode = Mode::Normal;
+            app.unit_file = None;
+        }
+        KeyCode::Char('|') => return page(app),
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.modal_scroll = app.modal_scroll.saturating_sub(1);
+        }
+        KeyCode::Down | KeyCode::Char('j') => app.modal_scroll += 1,
+        KeyCode::PageUp => app.modal_scroll = app.modal_scroll.saturating_sub(10),
+        KeyCode::PageDown => app.modal_scroll += 10,
+        KeyCode::Home | KeyCode::Char('g') => app.modal_scroll = 0,
+        // Clamped to the last page by the next frame
+        KeyCode::End | KeyCode::Char('G') => app.modal_scroll = usize::MAX,
+        _ => {}
+    }
+    Action::None
+}
+
+/// Scrollable read-only list modals (comparison, drift).
+fn handle_compare(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Esc
+        | KeyCode::Char('C')
+        | KeyCode::Char('D')
+        | KeyCode::Char('J')
+        | KeyCode::Char('L')
+        | KeyCode::Char('n')
+        | KeyCode::Char('q') => {
+            app.mode = Mode::Normal;
+        }
+        KeyCode::Char('|') => return page(app),
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.modal_scroll = app.modal_scroll.saturating_sub(1);
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.modal_scroll += 1;
+        }
+        _ => {}
+    }
+    Action::None
+}
+
+/// In instant mode a toggle is applied as soon as it's made, without the
+/// review; a pairing offer is answered first, and `strict_confirm` still
+/// asks for the name. Nothing happens while a modal or prompt is open.
+fn instant(app: &mut App) -> Action {
+    if !app.config.instant || app.mode != Mode::Normal || !app.has_pending_changes() {
+        return Action::None;
+    }
+    if app.begin_strict_confirm(false) {
+        return Action::None;
+    }
+    Action::ApplyChanges
+}
+
+/// Enter on staged changes: the review modal, or straight to the apply
+/// when the confirmation policy waives it. Typing names for
+/// `strict_confirm` is never waived.
+fn review(app: &mut App) -> Action {
+    if app.needs_review() {
+        app.review_changes();
+        return Action::None;
+    }
+    if app.begin_strict_confirm(false) {
+        return Action::None;
+    }
+    app.mode = Mode::Normal;
+    Action::ApplyChanges
+}
+
 fn handle_confirm(app: &mut App, code: KeyCode) -> Action {
     match code {
         KeyCode::Enter => {
+            if app.begin_strict_confirm(false) {
+                return Action::None;
+            }
             app.mode = Mode::Normal;
             return Action::ApplyChanges;
         }
+        KeyCode::Char('t') => {
+            if app.begin_strict_confirm(true) {
+                return Action::None;
+            }
+            app.prompt_input.clear();
+            app.mode = Mode::Schedule;
+        }
         KeyCode::Esc => {
+            app.quit_after_apply = false;
             app.mode = Mode::Normal;
         }
         _ => {}
     }
     Action::None
 }
+
+fn handle_schedule(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Enter if !app.prompt_input.trim().is_empty() => {
+            app.mode = Mode::Normal;
+            return Action::ScheduleChanges(app.prompt_input.trim().to_string());
+        }
+        KeyCode::Esc => {
+            app.mode = Mode::Confirm;
+        }
+        KeyCode::Backspace => {
+            app.prompt_input.pop();
+        }
+        KeyCode::Char(c) => app.prompt_input.push(c),
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_strict_confirm(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Enter => {
+            if !app.submit_strict_confirm() {
+                return Action::None;
+            }
+            if app.strict_then_schedule {
+                app.mode = Mode::Schedule;
+            } else {
+                app.mode = Mode::Normal;
+                return Action::ApplyChanges;
+            }
+        }
+        KeyCode::Esc => {
+            app.strict_queue.clear();
+            app.mode = Mode::Confirm;
+        }
+        KeyCode::Backspace => {
+            app.prompt_input.pop();
+        }
+        KeyCode::Char(c) => app.prompt_input.push(c),
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_template_pick(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => app.move_template_cursor(-1),
+        KeyCode::Down | KeyCode::Char('j') => app.move_template_cursor(1),
+        KeyCode::Enter => app.choose_template(),
+        KeyCode::Esc | KeyCode::Char('q') => app.mode = Mode::Normal,
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_scheduler(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => app.move_sched_cursor(-1),
+        KeyCode::Down | KeyCode::Char('j') => app.move_sched_cursor(1),
+        KeyCode::Enter => app.choose_scheduler(),
+        KeyCode::Esc | KeyCode::Char('q') => app.mode = Mode::Normal,
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_advisor(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => app.move_advisor_cursor(-1),
+        KeyCode::Down | KeyCode::Char('j') => app.move_advisor_cursor(1),
+        KeyCode::Char(' ') => app.toggle_advisor_candidate(),
+        KeyCode::Enter if app.has_pending_changes() => return review(app),
+        KeyCode::Esc | KeyCode::Char('a') | KeyCode::Char('q') => app.mode = Mode::Normal,
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_deps(app: &mut App, code: KeyCode) -> Action {
+    let Some(tree) = app.deps.as_mut() else {
+        app.mode = Mode::Normal;
+        return Action::None;
+    };
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => tree.move_cursor(-1),
+        KeyCode::Down | KeyCode::Char('j') => tree.move_cursor(1),
+        KeyCode::Enter | KeyCode::Char(' ') => tree.toggle(None),
+        KeyCode::Right | KeyCode::Char('l') => tree.toggle(Some(true)),
+        KeyCode::Left | KeyCode::Char('h') => tree.toggle(Some(false)),
+        KeyCode::Esc | KeyCode::Char('d') | KeyCode::Char('q') => {
+            app.deps = None;
+            app.mode = Mode::Normal;
+        }
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_blame(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => app.move_blame_cursor(-1),
+        KeyCode::Down | KeyCode::Char('j') => app.move_blame_cursor(1),
+        KeyCode::Char(' ') => app.toggle_blame_entry(),
+        KeyCode::Enter if app.has_pending_changes() => return review(app),
+        KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('q') => app.mode = Mode::Normal,
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_profiles(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => app.move_profile_cursor(-1),
+        KeyCode::Down | KeyCode::Char('j') => app.move_profile_cursor(1),
+        KeyCode::Enter => app.restore_profile(),
+        KeyCode::Char('s') => app.begin_save_profile(),
+        KeyCode::Char('d') => app.delete_profile(),
+        KeyCode::Esc | KeyCode::Char('p') | KeyCode::Char('q') => app.mode = Mode::Normal,
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_profile_name(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Enter => app.submit_profile_name(),
+        KeyCode::Esc => app.mode = Mode::Profiles,
+        KeyCode::Backspace => {
+            app.prompt_input.pop();
+        }
+        KeyCode::Char(c) => app.prompt_input.push(c),
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_hook(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Enter => app.install_hook(),
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.hook = None;
+            app.mode = Mode::Normal;
+        }
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_results(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Char('T') => app.begin_stop_timeout(),
+        KeyCode::Char('R') => app.retry_failed(),
+        KeyCode::PageUp => app.modal_scroll = app.modal_scroll.saturating_sub(10),
+        KeyCode::PageDown => app.modal_scroll += 10,
+        _ => return handle_compare(app, code),
+    }
+    Action::None
+}
+
+fn handle_logs(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Char('F') => app.fix_journal(),
+        KeyCode::Char('f') if app.begin_follow() => return Action::FollowLogs,
+        KeyCode::Char('|') => return page(app),
+        KeyCode::Esc | KeyCode::Char('o') | KeyCode::Char('q') => {
+            app.logs = None;
+            app.mode = Mode::Normal;
+        }
+        // Scrolled from the bottom, so up moves further back
+        KeyCode::Up | KeyCode::Char('k') => app.modal_scroll += 1,
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.modal_scroll = app.modal_scroll.saturating_sub(1);
+        }
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_stop_timeout(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Enter => app.submit_stop_timeout(),
+        KeyCode::Esc => {
+            app.stop_timeout_unit = None;
+            app.mode = Mode::Results;
+        }
+        KeyCode::Backspace => {
+            app.prompt_input.pop();
+        }
+        KeyCode::Char(c) => app.prompt_input.push(c),
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_wizard(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Enter => app.submit_wizard_answer(),
+        KeyCode::Esc => app.cancel_wizard(),
+        KeyCode::Backspace => {
+            app.prompt_input.pop();
+        }
+        KeyCode::Char(c) => app.prompt_input.push(c),
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_clone(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Enter => {
+            if let Some(name) = app.submit_clone() {
+                return Action::EditUnit { name, full: true };
+            }
+        }
+        KeyCode::Esc => {
+            app.clone_source = None;
+            app.mode = Mode::Normal;
+        }
+        KeyCode::Backspace => {
+            app.prompt_input.pop();
+        }
+        KeyCode::Char(c) => app.prompt_input.push(c),
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_follow(app: &mut App, code: KeyCode) -> Action {
+    let paused = app.follow.as_ref().is_some_and(|f| f.paused);
+    match code {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('f') => {
+            app.follow = None;
+            app.mode = Mode::Normal;
+        }
+        KeyCode::Char(' ') => app.set_follow_paused(!paused),
+        KeyCode::Char('p') => {
+            app.cycle_follow_priority();
+            return Action::FollowLogs;
+        }
+        // Scrolling back pauses, so the view doesn't jump away
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.set_follow_paused(true);
+            app.modal_scroll += 1;
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.modal_scroll = app.modal_scroll.saturating_sub(1);
+        }
+        KeyCode::End | KeyCode::Char('G') => app.set_follow_paused(false),
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_quit_confirm(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Char('q') | KeyCode::Char('y') => app.should_quit = true,
+        KeyCode::Char('a') | KeyCode::Enter => {
+            app.mode = Mode::Normal;
+            app.quit_after_apply = true;
+            return review(app);
+        }
+        KeyCode::Char('n') | KeyCode::Esc => app.mode = Mode::Normal,
+        _ => {}
+    }
+    Action::None
+}
+
+fn handle_visual(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => app.move_cursor(-1),
+        KeyCode::Down | KeyCode::Char('j') => app.move_cursor(1),
+        KeyCode::Char(' ') => app.stage_selection(None),
+        KeyCode::Char('e') => app.stage_selection(Some(true)),
+        KeyCode::Char('d') => app.stage_selection(Some(false)),
+        // Nothing staged, so nothing for instant mode to apply
+        KeyCode::Esc | KeyCode::Char('V') => {
+            app.mode = Mode::Normal;
+            return Action::None;
+        }
+        _ => return Action::None,
+    }
+    instant(app)
+}
+
+fn handle_category_bulk(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Char('e') => app.stage_category(true),
+        KeyCode::Char('d') => app.stage_category(false),
+        // Nothing staged, so nothing for instant mode to apply
+        KeyCode::Esc | KeyCode::Char('n') => {
+            app.bulk_category = None;
+            app.mode = Mode::Normal;
+            return Action::None;
+        }
+        _ => return Action::None,
+    }
+    instant(app)
+}
+
+fn handle_pair(app: &mut App, code: KeyCode) -> Action {
+    match code {
+        KeyCode::Char('y') | KeyCode::Enter => app.answer_pair(true),
+        KeyCode::Char('n') | KeyCode::Esc => app.answer_pair(false),
+        _ => return Action::None,
+    }
+    instant(app)
+}
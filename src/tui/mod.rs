@@ -1,2 +1,22 @@
 pub mod handler;
+pub mod text;
 pub mod ui;
+
+use std::io::stdout;
+
+use crossterm::event::{DisableBracketedPaste, EnableBracketedPaste};
+use crossterm::execute;
+use ratatui::DefaultTerminal;
+
+/// ratatui's terminal setup plus bracketed paste, so pasted text arrives
+/// as one `Event::Paste` instead of a burst of key presses.
+pub fn init() -> DefaultTerminal {
+    let terminal = ratatui::init();
+    let _ = execute!(stdout(), EnableBracketedPaste);
+    terminal
+}
+
+pub fn restore() {
+    let _ = execute!(stdout(), DisableBracketedPaste);
+    ratatui::restore();
+}
@@ -0,0 +1,93 @@
+use std::borrow::Cow;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Cut `text` to at most `width` terminal columns, ending in "…" when
+/// anything was dropped. Counts display width, so wide (CJK, emoji) and
+/// multi-byte characters neither overflow nor get split.
+pub fn truncate(text: &str, width: usize) -> Cow<'_, str> {
+    if text.width() <= width {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    if width > 0 {
+        out.push('…');
+    }
+    Cow::Owned(out)
+}
+
+/// `truncate`, then pad with spaces to exactly `width` columns, for
+/// aligned columns where `{:<30}` would count chars instead.
+pub fn pad(text: &str, width: usize) -> String {
+    let text = truncate(text, width);
+    let fill = width.saturating_sub(text.width());
+    format!("{text}{}", " ".repeat(fill))
+}
+
+/// Greedy word wrap to `width` columns. A word longer than a whole line
+/// is broken between characters.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let sep = usize::from(!line.is_empty());
+        if line.width() + sep + word.width() <= width {
+            if sep == 1 {
+                line.push(' ');
+            }
+            line.push_str(word);
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for c in word.chars() {
+            if line.width() + c.width().unwrap_or(0) > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.push(c);
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_by_display_width() {
+        assert_eq!(truncate("cups.service", 20), "cups.service");
+        assert_eq!(
+            truncate("systemd-networkd-wait-online.service", 12),
+            "systemd-net…"
+        );
+        // Each CJK character is two columns wide
+        assert_eq!(truncate("日本語サービス.service", 7), "日本語…");
+        assert_eq!(truncate("ab", 0), "");
+        assert_eq!(pad("é", 3), "é  ");
+    }
+
+    #[test]
+    fn test_wrap_multibyte() {
+        assert_eq!(
+            wrap("Prints — and scans — documents", 13),
+            ["Prints — and", "scans —", "documents"]
+        );
+        assert_eq!(wrap("abcdef", 4), ["abcd", "ef"]);
+        assert!(wrap("", 10).is_empty());
+    }
+}
@@ -1,77 +1,301 @@
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::widgets::{Block, Borders, Clear, Padding, Paragraph, Wrap};
 use ratatui::Frame;
 
-use crate::app::{App, Mode, Tab, VisibleItem};
-use crate::systemd::ChangeAction;
+use crate::app::{scroll_window, App, Mode, RunChange, Tab, VisibleItem};
+use crate::boot;
+use crate::deps::DepState;
+use crate::drift;
+use crate::journal;
+use crate::sched_ext;
+use crate::systemd;
+use crate::systemd::{
+    format_ago, format_bytes, format_until, paired_trigger, ChangeAction, Service, ServiceScope,
+    SLOW_STEP,
+};
+use crate::templates::TEMPLATES;
+use crate::tui::text;
+use crate::tutorial::{self, Step};
 
-pub fn render(frame: &mut Frame, app: &App) {
-    let [header_area, list_area, status_area] = Layout::vertical([
+/// What the frame ended up showing, fed back so the next key press
+/// starts from what's on screen rather than from before a resize.
+pub struct Viewport {
+    /// First service-list row drawn.
+    pub list_offset: usize,
+    /// How far the open modal can scroll at this size, if it scrolls.
+    pub modal_scroll_max: Option<usize>,
+}
+
+pub fn render(frame: &mut Frame, app: &App) -> Viewport {
+    let [header_area, health_area, list_area, legend_area, status_area] = Layout::vertical([
+        Constraint::Length(1),
         Constraint::Length(1),
         Constraint::Fill(1),
+        Constraint::Length(u16::from(app.show_legend)),
         Constraint::Length(1),
     ])
     .areas(frame.area());
 
     render_header(frame, app, header_area);
-    render_service_list(frame, app, list_area);
+    render_health(frame, app, health_area);
+    let list_offset = render_service_list(frame, app, list_area);
+    if app.show_legend {
+        render_legend(frame, legend_area);
+    }
     render_status_bar(frame, app, status_area);
 
-    match app.mode {
-        Mode::Confirm => render_confirm_modal(frame, app),
-        Mode::Applying => render_applying_overlay(frame),
-        Mode::Info => render_info_modal(frame, app),
-        _ => {}
+    let modal_scroll_max = match app.mode {
+        Mode::Confirm | Mode::Schedule | Mode::StrictConfirm => {
+            render_confirm_modal(frame, app);
+            None
+        }
+        Mode::Applying => {
+            render_applying_overlay(frame, app);
+            None
+        }
+        Mode::Info => Some(render_info_modal(frame, app)),
+        Mode::Compare => Some(render_compare_modal(frame, app)),
+        Mode::Drift => Some(render_drift_modal(frame, app)),
+        Mode::Jobs => Some(render_jobs_modal(frame, app)),
+        Mode::NextBoot => Some(render_next_boot_modal(frame, app)),
+        Mode::Advisor => {
+            render_advisor_modal(frame, app);
+            None
+        }
+        Mode::TemplatePick | Mode::Wizard => {
+            render_template_modal(frame, app);
+            None
+        }
+        Mode::Scheduler => {
+            render_scheduler_modal(frame, app);
+            None
+        }
+        Mode::Hook => {
+            render_hook_modal(frame, app);
+            None
+        }
+        Mode::Blame => {
+            render_blame_modal(frame, app);
+            None
+        }
+        Mode::Deps => {
+            render_deps_modal(frame, app);
+            None
+        }
+        Mode::Profiles | Mode::ProfileName => {
+            render_profiles_modal(frame, app);
+            None
+        }
+        Mode::Results | Mode::StopTimeout => Some(render_results_modal(frame, app)),
+        Mode::Logs => Some(render_logs_modal(frame, app)),
+        Mode::UnitFile => Some(render_unit_file_modal(frame, app)),
+        Mode::Follow => {
+            render_follow(frame, app);
+            None
+        }
+        _ => None,
+    };
+    if let Some(step) = app.tutorial {
+        render_tutorial(frame, step, status_area);
+    }
+    Viewport {
+        list_offset,
+        modal_scroll_max: modal_scroll_max.map(usize::from),
     }
 }
 
-fn render_header(frame: &mut Frame, app: &App, area: Rect) {
-    let system_style = if app.tab == Tab::System {
-        Style::default().fg(Color::Black).bg(Color::Cyan)
-    } else {
-        Style::default().fg(Color::DarkGray)
+/// `--tutorial` hint, bottom right above the status bar so it stays clear
+/// of the list's left edge and of centered modals.
+fn render_tutorial(frame: &mut Frame, step: Step, status_area: Rect) {
+    let area = frame.area();
+    let width = 46u16.min(area.width);
+    let lines = vec![Line::raw(step.hint())];
+    let height = (wrapped_rows(&lines, width.saturating_sub(4)) + 2).min(status_area.y);
+    let hint_area = Rect {
+        x: area.width.saturating_sub(width),
+        y: status_area.y.saturating_sub(height),
+        width,
+        height,
     };
-    let user_style = if app.tab == Tab::User {
-        Style::default().fg(Color::Black).bg(Color::Cyan)
-    } else {
-        Style::default().fg(Color::DarkGray)
+    let title = match step {
+        Step::Done => " Tutorial: done ".to_string(),
+        _ => format!(" Tutorial {}/{} ", step.number(), tutorial::STEPS),
+    };
+    frame.render_widget(Clear, hint_area);
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .padding(Padding::horizontal(1))
+        .border_style(Style::default().fg(Color::Green));
+    frame.render_widget(
+        Paragraph::new(lines).block(block).wrap(Wrap { trim: true }),
+        hint_area,
+    );
+}
+
+fn render_header(frame: &mut Frame, app: &App, area: Rect) {
+    let tab_style = |tab: Tab| {
+        if app.tab == tab {
+            Style::default().fg(Color::Black).bg(Color::Cyan)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        }
     };
 
-    let header = Line::from(vec![
+    let mut spans = vec![
         Span::raw(" "),
-        Span::styled(" System ", system_style),
+        Span::styled(" System ", tab_style(Tab::System)),
         Span::raw("  "),
-        Span::styled(" User ", user_style),
-        Span::raw("          Tab: switch  /: search  q: quit"),
-    ]);
+        Span::styled(" User ", tab_style(Tab::User)),
+        Span::raw("  "),
+        Span::styled(" All users ", tab_style(Tab::Global)),
+        Span::raw("  "),
+        Span::styled(" Timers ", tab_style(Tab::Timers)),
+    ];
+    if let Some(total) = app.boot_total {
+        spans.push(Span::styled(
+            format!("   ⏱ boot {}", boot::format_secs(total)),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    if app.config.instant {
+        spans.push(Span::styled(
+            "   ⚡ instant",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    if let Some(name) = systemd::machine() {
+        spans.push(Span::styled(
+            format!("   ⬡ container {name}"),
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    // Stale manager state is easy to miss, so it takes over the hint area
+    match app.needs_reload.len() {
+        0 if app.queued_jobs.is_empty() => spans.push(Span::raw(
+            "          Tab: switch  /: search  ?: legend  q: quit",
+        )),
+        0 => {}
+        count => spans.push(Span::styled(
+            format!(
+                "    ⟳ {count} unit file{} changed on disk — R: daemon-reload",
+                if count == 1 { "" } else { "s" }
+            ),
+            Style::default().fg(Color::Yellow),
+        )),
+    }
+    if !app.queued_jobs.is_empty() {
+        let count = app.queued_jobs.len();
+        spans.push(Span::styled(
+            format!(
+                "    ⧗ {count} job{} queued — J: view",
+                if count == 1 { "" } else { "s" }
+            ),
+            Style::default().fg(Color::Magenta),
+        ));
+    }
+    let header = Line::from(spans);
 
     frame.render_widget(Paragraph::new(header), area);
 }
 
-fn render_service_list(frame: &mut Frame, app: &App, area: Rect) {
+/// A chip per category: running/total, plus failed and staged counts when
+/// there are any. The chip is red with a failure, yellow with a pending change.
+fn render_health(frame: &mut Frame, app: &App, area: Rect) {
+    let mut spans = vec![Span::raw(" ")];
+    for health in app.category_health() {
+        let mut chip = format!(" {}", health.name);
+        if app.tab != Tab::Global {
+            chip.push_str(&format!(" ●{}/{}", health.active, health.total));
+        } else {
+            chip.push_str(&format!(" {}", health.total));
+        }
+        if health.failed > 0 {
+            chip.push_str(&format!(" ✗{}", health.failed));
+        }
+        if health.dirty > 0 {
+            chip.push_str(&format!(" ✎{}", health.dirty));
+        }
+        chip.push(' ');
+        let style = if health.failed > 0 {
+            Style::default().fg(Color::Black).bg(Color::Red)
+        } else if health.dirty > 0 {
+            Style::default().fg(Color::Black).bg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::Gray).bg(Color::DarkGray)
+        };
+        spans.push(Span::styled(chip, style));
+        spans.push(Span::raw(" "));
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// One-line key to the list markers, toggled with `?`.
+fn render_legend(frame: &mut Frame, area: Rect) {
+    let legend = Line::from(vec![
+        Span::raw(" boot: [✓] enabled  [ ] disabled  "),
+        Span::styled(
+            "[·] generated  [⊘] masked",
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::raw("   now: "),
+        Span::styled("●", Style::default().fg(Color::Green)),
+        Span::raw(" running  "),
+        Span::styled("○", Style::default().fg(Color::DarkGray)),
+        Span::raw(" stopped  "),
+        Span::styled("✗", Style::default().fg(Color::Red)),
+        Span::raw(" failed   "),
+        Span::styled("yellow", Style::default().fg(Color::Yellow)),
+        Span::raw(" = pending ("),
+        Span::styled("+", Style::default().fg(Color::Green)),
+        Span::raw(" enable "),
+        Span::styled("−", Style::default().fg(Color::Red)),
+        Span::raw(" disable)"),
+    ]);
+    frame.render_widget(
+        Paragraph::new(legend).style(Style::default().bg(Color::Black)),
+        area,
+    );
+}
+
+/// Narrowest a unit name gets cut to, to make room for its hints.
+const NAME_MIN: usize = 24;
+
+/// Draws the list and returns the row it starts at.
+fn render_service_list(frame: &mut Frame, app: &App, area: Rect) -> usize {
     let block = Block::default().borders(Borders::TOP);
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    // Calculate scroll offset to keep cursor visible
     let max_visible = inner.height as usize;
-    let scroll_offset = if app.cursor >= max_visible {
-        app.cursor - max_visible + 1
-    } else {
-        0
-    };
-
-    let mut lines: Vec<Line> = Vec::new();
+    let scroll_offset = scroll_window(
+        app.cursor,
+        app.list_offset,
+        max_visible,
+        app.visible_items.len(),
+    );
 
-    for (idx, item) in app
+    let now = drift::now_secs();
+    // Only the rows on screen become Lines, however many units there are
+    let end = (scroll_offset + max_visible).min(app.visible_items.len());
+    let window = app
         .visible_items
+        .get(scroll_offset..end)
+        .unwrap_or_default();
+    let mut lines: Vec<Line> = Vec::with_capacity(window.len());
+    // Headers are numbered for the 1-9 jump keys
+    let mut header_number = app.visible_items[..scroll_offset]
         .iter()
-        .enumerate()
-        .skip(scroll_offset)
-        .take(max_visible)
-    {
+        .filter(|item| matches!(item, VisibleItem::Category(_)))
+        .count();
+
+    for (idx, item) in (scroll_offset..).zip(window) {
         let is_cursor = idx == app.cursor;
 
         let line = match item {
@@ -83,47 +307,225 @@ fn render_service_list(frame: &mut Frame, app: &App, area: Rect) {
                     .fg(Color::Cyan)
                     .add_modifier(Modifier::BOLD);
                 let cursor_indicator = if is_cursor { ">" } else { " " };
+                header_number += 1;
+                let key = if header_number <= 9 {
+                    format!("  [{header_number}]")
+                } else {
+                    String::new()
+                };
                 Line::from(vec![
                     Span::styled(format!("{cursor_indicator} {arrow} {}", cat.name), style),
-                    Span::styled(format!(" ({count})"), Style::default().fg(Color::DarkGray)),
+                    Span::styled(
+                        format!(" ({count}){key}"),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ])
+            }
+            VisibleItem::Journal(svc_idx, line) => {
+                let name = &app.services[*svc_idx].name;
+                let lines = app
+                    .failure_logs
+                    .get(name)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                let last = *line + 1 == lines.len();
+                let branch = if last { "└" } else { "│" };
+                let more = match lines.len() {
+                    n if last && n > 1 && !app.expanded_failures.contains(name) => {
+                        format!("  (x: {} more)", n - 1)
+                    }
+                    _ => String::new(),
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!(
+                            "         {branch} {}",
+                            lines.get(*line).map(String::as_str).unwrap_or("")
+                        ),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(
+                        more,
+                        Style::default()
+                            .fg(Color::DarkGray)
+                            .add_modifier(Modifier::DIM),
+                    ),
+                ])
+            }
+            VisibleItem::Role(role_idx) => {
+                let role = &app.roles[*role_idx];
+                let on = role
+                    .services
+                    .iter()
+                    .filter(|&&idx| app.services[idx].enabled)
+                    .count();
+                let checkbox = match on {
+                    0 => "[ ]",
+                    n if n == role.services.len() => "[✓]",
+                    _ => "[~]",
+                };
+                let dirty = role
+                    .services
+                    .iter()
+                    .any(|&idx| app.is_service_dirty(&app.services[idx]));
+                let mut style = Style::default().add_modifier(Modifier::BOLD);
+                if dirty {
+                    style = style.fg(Color::Yellow);
+                }
+                if is_cursor {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                let members: Vec<&str> = role
+                    .services
+                    .iter()
+                    .map(|&idx| app.services[idx].name.trim_end_matches(".service"))
+                    .collect();
+                let cursor_indicator = if is_cursor { ">" } else { " " };
+                Line::from(vec![
+                    Span::styled(
+                        format!("{cursor_indicator} ◆ {checkbox} {}", role.name),
+                        style,
+                    ),
+                    Span::styled(
+                        format!(" role: {}", members.join(", ")),
+                        Style::default().fg(Color::DarkGray),
+                    ),
                 ])
             }
             VisibleItem::Service(svc_idx) => {
                 let svc = &app.services[*svc_idx];
-                let checkbox = if svc.enabled {
+                // Boot column: enablement (as staged); now column: whether
+                // it's running. Enabled-but-dead and running-but-disabled
+                // (socket/dependency-started) both read at a glance.
+                let masked = app.is_masked(svc);
+                let checkbox = if svc.generated {
+                    "[·]" // read-only, generator-produced
+                } else if masked {
+                    "[⊘]"
+                } else if svc.enabled {
                     "[✓]"
-                } else if svc.active {
-                    "[●]" // running via socket/dependency but not enabled
                 } else {
                     "[ ]"
                 };
+                let running = match app.tab {
+                    Tab::Global => Span::raw("  "), // no manager to be running under
+                    _ if svc.active => Span::styled("● ", Style::default().fg(Color::Green)),
+                    _ if app.failed.contains(&svc.name) => {
+                        Span::styled("✗ ", Style::default().fg(Color::Red))
+                    }
+                    _ => Span::styled("○ ", Style::default().fg(Color::DarkGray)),
+                };
                 let dirty = app.is_service_dirty(svc);
+                // A visual selection is drawn like the cursor row
+                let highlighted = is_cursor || app.in_selection(idx);
 
-                let style = if is_cursor && dirty {
+                let style = if highlighted && dirty {
                     Style::default()
                         .fg(Color::Yellow)
                         .add_modifier(Modifier::BOLD | Modifier::REVERSED)
-                } else if is_cursor {
+                } else if highlighted {
                     Style::default().add_modifier(Modifier::REVERSED)
                 } else if dirty {
                     Style::default().fg(Color::Yellow)
+                } else if svc.generated || masked {
+                    Style::default().fg(Color::DarkGray)
                 } else {
                     Style::default()
                 };
 
-                let active_hint = if svc.active && !svc.enabled {
-                    " (running)"
+                let global_hint = if svc.enabled_globally && app.tab == Tab::User {
+                    " (all users)"
+                } else {
+                    ""
+                };
+                let runtime_hint = if dirty && app.runtime.contains(&svc.name) {
+                    " (until reboot)"
+                } else {
+                    ""
+                };
+                let retry_hint = if dirty && app.retry.contains(&svc.name) {
+                    " (retry)"
+                } else {
+                    ""
+                };
+                let boot_only_hint = match app.run_changes.get(&svc.name) {
+                    Some(RunChange::Keep) if svc.enabled => " (boot only, not started now)",
+                    Some(RunChange::Keep) => " (boot only, keeps running)",
+                    _ => "",
+                };
+                let session_hint = if app.session_bound.contains(&svc.name) {
+                    " (desktop)"
+                } else {
+                    ""
+                };
+                // Timer-driven services sit inactive between runs
+                let timer_hint = match app.next_runs.get(&svc.name) {
+                    Some(&at) if !svc.active => format!(" (timer: {})", format_until(at, now)),
+                    _ => String::new(),
+                };
+                let schedule_hint = match app.timer_times.get(&svc.name) {
+                    Some(times) => {
+                        let next = times
+                            .next
+                            .map(|at| format!("next {}", format_until(at, now)));
+                        let last = times.last.map(|at| format!("last {}", format_ago(at, now)));
+                        match (next, last) {
+                            (Some(next), Some(last)) => format!(" ({next}, {last})"),
+                            (Some(one), None) | (None, Some(one)) => format!(" ({one})"),
+                            (None, None) => String::new(),
+                        }
+                    }
+                    None => String::new(),
+                };
+                let sched_hint = match &app.sched_ext {
+                    Some(current) if svc.name == sched_ext::LOADER_SERVICE => {
+                        format!(" ({current})")
+                    }
+                    _ => String::new(),
+                };
+                let preset_hint = if svc.differs_from_preset() {
+                    " ≠ preset"
                 } else {
                     ""
                 };
+                let pair_hint = match paired_trigger(&svc.name, &app.triggers) {
+                    Some(trigger) if app.paired.contains(&svc.name) => {
+                        format!(" (+ {trigger})")
+                    }
+                    _ => String::new(),
+                };
                 let cursor_indicator = if is_cursor { ">" } else { " " };
-                Line::from(vec![
-                    Span::styled(
-                        format!("{cursor_indicator}   {checkbox} {}", svc.name),
-                        style,
-                    ),
-                    Span::styled(active_hint, Style::default().fg(Color::Green)),
-                ])
+                let (mark, mark_color) = gutter(app, svc);
+                // Keep the cursor bar unbroken
+                let mark_style = if highlighted { style } else { Style::default() };
+                let prefix = vec![
+                    Span::styled(cursor_indicator, style),
+                    Span::styled(mark, mark_style.fg(mark_color)),
+                    Span::styled(format!("  {checkbox} "), style),
+                    running,
+                ];
+                let hints = vec![
+                    Span::styled(global_hint, Style::default().fg(Color::Blue)),
+                    Span::styled(session_hint, Style::default().fg(Color::Cyan)),
+                    Span::styled(timer_hint, Style::default().fg(Color::Blue)),
+                    Span::styled(schedule_hint, Style::default().fg(Color::Blue)),
+                    Span::styled(sched_hint, Style::default().fg(Color::Cyan)),
+                    Span::styled(pair_hint, Style::default().fg(Color::Yellow)),
+                    Span::styled(retry_hint, Style::default().fg(Color::Red)),
+                    Span::styled(runtime_hint, Style::default().fg(Color::Magenta)),
+                    Span::styled(boot_only_hint, Style::default().fg(Color::Magenta)),
+                    Span::styled(preset_hint, Style::default().fg(Color::DarkGray)),
+                ];
+                // Shorten the name so the hints stay visible, but not below
+                // NAME_MIN; past that the hints are the ones clipped
+                let used: usize = prefix.iter().map(Span::width).sum();
+                let room = (inner.width as usize).saturating_sub(used);
+                let hints_width: usize = hints.iter().map(Span::width).sum();
+                let name_room = room.saturating_sub(hints_width).max(NAME_MIN.min(room));
+                let mut spans = prefix;
+                spans.push(Span::styled(text::truncate(&svc.name, name_room), style));
+                spans.extend(hints);
+                Line::from(spans)
             }
         };
 
@@ -131,6 +533,28 @@ fn render_service_list(frame: &mut Frame, app: &App, area: Rect) {
     }
 
     frame.render_widget(Paragraph::new(lines), inner);
+    scroll_offset
+}
+
+/// First-column mark for what a row has staged, so a mixed batch reads
+/// straight off the list.
+fn gutter(app: &App, svc: &Service) -> (&'static str, Color) {
+    match app.masks.get(&svc.name) {
+        Some(true) => return ("⊘", Color::Red),
+        Some(false) => return ("○", Color::Cyan),
+        None => {}
+    }
+    match app.run_changes.get(&svc.name) {
+        Some(RunChange::Start) => return ("▶", Color::Green),
+        Some(RunChange::Stop) => return ("■", Color::Red),
+        Some(RunChange::Restart) => return ("↻", Color::Yellow),
+        Some(RunChange::Keep) | None => {}
+    }
+    match (app.toggled.contains(&svc.name), svc.enabled) {
+        (true, true) => ("+", Color::Green),
+        (true, false) => ("−", Color::Red),
+        (false, _) => (" ", Color::Reset),
+    }
 }
 
 fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
@@ -144,8 +568,168 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             Span::raw("  "),
             Span::styled("[Esc] Clear", Style::default().fg(Color::DarkGray)),
         ]),
+        Mode::Schedule => Line::from(vec![
+            Span::styled(" apply at: ", Style::default().fg(Color::Cyan)),
+            Span::raw(&app.prompt_input),
+            Span::styled("▏", Style::default().fg(Color::Cyan)),
+            Span::styled(
+                "  e.g. 02:00, tomorrow 03:00, +2h",
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::raw("  "),
+            Span::styled("[Enter] Schedule", Style::default().fg(Color::Green)),
+            Span::raw("  "),
+            Span::styled("[Esc] Back", Style::default().fg(Color::DarkGray)),
+        ]),
+        Mode::StrictConfirm => Line::from(vec![
+            Span::styled(" type ", Style::default().fg(Color::Red)),
+            Span::styled(
+                app.strict_queue.first().map(String::as_str).unwrap_or(""),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" to disable it: ", Style::default().fg(Color::Red)),
+            Span::raw(&app.prompt_input),
+            Span::styled("▏", Style::default().fg(Color::Cyan)),
+            Span::raw("  "),
+            Span::styled("[Esc] Back", Style::default().fg(Color::DarkGray)),
+        ]),
+        Mode::CategoryBulk => {
+            let name = app.bulk_category.unwrap_or_default();
+            let count = app.shown_members(name).len();
+            Line::from(vec![
+                Span::styled(
+                    format!(
+                        " {name} ({count} service{}): ",
+                        if count == 1 { "" } else { "s" }
+                    ),
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("[e] Enable all", Style::default().fg(Color::Green)),
+                Span::raw("  "),
+                Span::styled("[d] Disable all", Style::default().fg(Color::Red)),
+                Span::raw("  "),
+                Span::styled("[Esc] Cancel", Style::default().fg(Color::DarkGray)),
+            ])
+        }
+        Mode::Visual => {
+            let rows = app.visual_anchor.abs_diff(app.cursor) + 1;
+            Line::from(vec![
+                Span::styled(
+                    format!(" VISUAL {rows} row{}  ", if rows == 1 { "" } else { "s" }),
+                    Style::default().fg(Color::Black).bg(Color::Cyan),
+                ),
+                Span::raw("  "),
+                Span::styled("[Space] Toggle each", Style::default().fg(Color::Green)),
+                Span::raw("  "),
+                Span::styled("[e] Enable all", Style::default().fg(Color::Green)),
+                Span::raw("  "),
+                Span::styled("[d] Disable all", Style::default().fg(Color::Red)),
+                Span::raw("  "),
+                Span::styled("[Esc] Cancel", Style::default().fg(Color::DarkGray)),
+            ])
+        }
+        Mode::Pair => {
+            let (service, trigger) = app.pair_offer.clone().unwrap_or_default();
+            let verb = match app.service_named(&service) {
+                Some(svc) if svc.enabled => "enable",
+                _ => "disable",
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!(" Also {verb} {trigger}? "),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::styled(
+                    format!("It starts {service}.  "),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled("[y] Both", Style::default().fg(Color::Green)),
+                Span::raw("  "),
+                Span::styled("[n] Service only", Style::default().fg(Color::DarkGray)),
+            ])
+        }
+        Mode::QuitConfirm => {
+            let count = app.pending_count();
+            Line::from(vec![
+                Span::styled(
+                    format!(
+                        " {count} pending change{} will be discarded — quit anyway? ",
+                        if count == 1 { "" } else { "s" }
+                    ),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::styled("[q] Discard & quit", Style::default().fg(Color::Red)),
+                Span::raw("  "),
+                Span::styled("[a] Apply & quit", Style::default().fg(Color::Green)),
+                Span::raw("  "),
+                Span::styled("[Esc] Cancel", Style::default().fg(Color::DarkGray)),
+            ])
+        }
+        Mode::StopTimeout => Line::from(vec![
+            Span::styled(
+                format!(
+                    " TimeoutStopSec for {}: ",
+                    app.stop_timeout_unit.as_deref().unwrap_or("")
+                ),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::raw(&app.prompt_input),
+            Span::styled("▏", Style::default().fg(Color::Cyan)),
+            Span::styled(
+                "  e.g. 15s, or 10s KillMode=mixed",
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::raw("  "),
+            Span::styled("[Enter] Write drop-in", Style::default().fg(Color::Green)),
+            Span::raw("  "),
+            Span::styled("[Esc] Back", Style::default().fg(Color::DarkGray)),
+        ]),
+        Mode::ProfileName => Line::from(vec![
+            Span::styled(" Save profile as: ", Style::default().fg(Color::Cyan)),
+            Span::raw(&app.prompt_input),
+            Span::styled("▏", Style::default().fg(Color::Cyan)),
+            Span::raw("  "),
+            Span::styled("[Enter] Save", Style::default().fg(Color::Green)),
+            Span::raw("  "),
+            Span::styled("[Esc] Back", Style::default().fg(Color::DarkGray)),
+        ]),
+        Mode::Clone => Line::from(vec![
+            Span::styled(" Copy as: ", Style::default().fg(Color::Cyan)),
+            Span::raw(&app.prompt_input),
+            Span::styled("▏", Style::default().fg(Color::Cyan)),
+            Span::raw("  "),
+            Span::styled("[Enter] Copy & edit", Style::default().fg(Color::Green)),
+            Span::raw("  "),
+            Span::styled("[Esc] Cancel", Style::default().fg(Color::DarkGray)),
+        ]),
+        Mode::Wizard => {
+            let question = app
+                .wizard
+                .as_ref()
+                .and_then(|w| w.prompts().get(w.answers.len()))
+                .map(|p| p.question)
+                .unwrap_or("");
+            Line::from(vec![
+                Span::styled(format!(" {question}: "), Style::default().fg(Color::Cyan)),
+                Span::raw(&app.prompt_input),
+                Span::styled("▏", Style::default().fg(Color::Cyan)),
+                Span::raw("  "),
+                Span::styled("[Enter] Next", Style::default().fg(Color::Green)),
+                Span::raw("  "),
+                Span::styled("[Esc] Cancel", Style::default().fg(Color::DarkGray)),
+            ])
+        }
         _ => {
             let mut spans = Vec::new();
+            if app.root_only.is_some() {
+                spans.push(Span::styled(
+                    " runs as root [P]",
+                    Style::default().fg(Color::Magenta),
+                ));
+                spans.push(Span::raw("  "));
+            }
             if !app.filter.is_empty() {
                 spans.push(Span::styled(
                     format!(" filter: {}", app.filter),
@@ -158,6 +742,13 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                 ));
                 spans.push(Span::raw("  "));
             }
+            if let Some(notice) = &app.notice {
+                spans.push(Span::styled(
+                    format!(" {notice}"),
+                    Style::default().fg(Color::Cyan),
+                ));
+                spans.push(Span::raw("  "));
+            }
             let count = app.pending_count();
             if count > 0 {
                 spans.push(Span::styled(
@@ -193,7 +784,11 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                         ));
                     }
                 }
-            } else {
+                spans.push(Span::styled(
+                    "  L: timings",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            } else if app.notice.is_none() {
                 spans.push(Span::styled(
                     " Space: toggle  Enter: apply  i: info  q: quit",
                     Style::default().fg(Color::DarkGray),
@@ -206,10 +801,83 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(Paragraph::new(line), area);
 }
 
-fn render_applying_overlay(frame: &mut Frame) {
+/// Finished changes listed under the apply's progress line.
+const RECENT_RESULTS: usize = 6;
+
+fn render_applying_overlay(frame: &mut Frame, app: &App) {
+    let title = if app.progress.total == 0 {
+        " Applying changes..." // a scheduled batch, handed off at once
+    } else if app.canceling() {
+        " Canceling after the current change..."
+    } else {
+        " Applying changes... (Esc to cancel)"
+    };
+    let mut lines = vec![Line::styled(
+        title,
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    )];
+
+    let progress = &app.progress;
+    if progress.total > 0 {
+        let mut text = format!(" {}/{} done", progress.done, progress.total);
+        if let Some(current) = progress
+            .current
+            .as_ref()
+            .filter(|_| progress.done < progress.total)
+        {
+            text.push_str(&format!(", currently: {current}"));
+        }
+        lines.push(Line::styled(text, Style::default().fg(Color::White)));
+    }
+    // The latest few; the results modal has them all afterwards
+    let shown = progress.results.len().saturating_sub(RECENT_RESULTS);
+    for result in &progress.results[shown..] {
+        let (icon, color) = if result.success {
+            ("✓", Color::Green)
+        } else {
+            ("✗", Color::Red)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("   {icon} "), Style::default().fg(color)),
+            Span::raw(&result.service),
+        ]));
+    }
+
+    // Jobs from JobNew/JobRemoved; others in the queue are someone else's
+    let changes = app.pending_changes();
+    let ours = |unit: &str| changes.iter().find(|c| c.service == unit);
+    for job in &app.jobs {
+        let Some(change) = ours(&job.unit) else {
+            continue;
+        };
+        let Some(verb) = change.action.verbs().1 else {
+            continue;
+        };
+        lines.push(Line::styled(
+            format!(" waiting for job {} ({verb} {})", job.id, job.unit),
+            Style::default().fg(Color::White),
+        ));
+        let ahead = app
+            .jobs
+            .iter()
+            .filter(|other| other.id < job.id && ours(&other.unit).is_none())
+            .count();
+        if ahead > 0 {
+            lines.push(Line::styled(
+                format!(
+                    "   queued behind {ahead} other job{}",
+                    if ahead == 1 { "" } else { "s" }
+                ),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+    }
+
     let area = frame.area();
-    let w = 30u16.min(area.width.saturating_sub(4));
-    let h = 3u16;
+    let w = if lines.len() > 1 { 60u16 } else { 30u16 }.min(area.width.saturating_sub(4));
+    let h = lines.len() as u16 + 2;
     let modal = Rect {
         x: (area.width.saturating_sub(w)) / 2,
         y: (area.height.saturating_sub(h)) / 2,
@@ -220,20 +888,13 @@ fn render_applying_overlay(frame: &mut Frame) {
     let block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan));
-    let text = Paragraph::new(Line::styled(
-        " Applying changes...",
-        Style::default()
-            .fg(Color::Yellow)
-            .add_modifier(Modifier::BOLD),
-    ))
-    .block(block);
-    frame.render_widget(text, modal);
+    frame.render_widget(Paragraph::new(lines).block(block), modal);
 }
 
-fn render_info_modal(frame: &mut Frame, app: &App) {
+fn render_info_modal(frame: &mut Frame, app: &App) -> u16 {
     let info = match &app.info {
         Some(info) => info,
-        None => return,
+        None => return 0,
     };
 
     let area = frame.area();
@@ -254,24 +915,35 @@ fn render_info_modal(frame: &mut Frame, app: &App) {
     if !info.extra_info.is_empty() {
         // Word-wrap the extra info manually to fit the modal
         let wrap_width = 56usize; // modal inner width minus padding
-        let mut remaining = info.extra_info.as_str();
-        while !remaining.is_empty() {
-            let (chunk, rest) = if remaining.len() <= wrap_width {
-                (remaining, "")
-            } else if let Some(pos) = remaining[..wrap_width].rfind(' ') {
-                (&remaining[..pos], remaining[pos + 1..].trim_start())
-            } else {
-                (&remaining[..wrap_width], &remaining[wrap_width..])
-            };
+        for chunk in text::wrap(&info.extra_info, wrap_width) {
             lines.push(Line::from(Span::styled(
                 format!("  {chunk}"),
                 Style::default().fg(Color::White),
             )));
-            remaining = rest;
         }
         lines.push(Line::raw(""));
     }
 
+    if let Some(snap) = &info.snap {
+        lines.push(Line::from(vec![
+            Span::styled("  Snap:        ", label_style),
+            Span::styled(snap, value_style),
+        ]));
+        lines.push(Line::styled(
+            format!("  Installed and managed by snapd; see `snap services {snap}`."),
+            Style::default().fg(Color::DarkGray),
+        ));
+        lines.push(Line::raw(""));
+    }
+
+    if let Some(instance) = &info.instance {
+        lines.push(Line::from(vec![
+            Span::styled("  Instance:    ", label_style),
+            Span::styled(instance, value_style),
+        ]));
+        lines.push(Line::raw(""));
+    }
+
     let state_color = match info.active_state.as_str() {
         "active" => Color::Green,
         "failed" => Color::Red,
@@ -284,59 +956,1447 @@ fn render_info_modal(frame: &mut Frame, app: &App) {
             Style::default().fg(state_color),
         ),
     ]));
+    if !info.service_type.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled("  Type:        ", label_style),
+            Span::styled(&info.service_type, value_style),
+        ]));
+    }
+    if info.fragment_path.ends_with(sched_ext::LOADER_SERVICE) {
+        lines.push(Line::from(vec![
+            Span::styled("  Scheduler:   ", label_style),
+            Span::styled(
+                app.sched_ext.as_deref().unwrap_or("none (kernel default)"),
+                value_style,
+            ),
+            Span::styled("  S: switch", Style::default().fg(Color::DarkGray)),
+        ]));
+    }
+    if let Some(current) = &info.display_manager {
+        lines.push(Line::from(vec![
+            Span::styled("  Alias:       ", label_style),
+            Span::styled(format!("display-manager.service → {current}"), value_style),
+        ]));
+        if !info.fragment_path.ends_with(current.as_str()) {
+            lines.push(Line::styled(
+                format!("  Only one display manager can hold the alias: disable {current}"),
+                Style::default().fg(Color::Yellow),
+            ));
+            lines.push(Line::styled(
+                "  in the same batch, or enabling this one fails.",
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+    }
+    if info.graphical_session {
+        lines.push(Line::from(vec![
+            Span::styled("  Session:     ", label_style),
+            Span::styled("graphical only (graphical-session.target)", value_style),
+        ]));
+        if !app.graphical_session {
+            lines.push(Line::styled(
+                "  No desktop session is running here (e.g. over SSH),",
+                Style::default().fg(Color::DarkGray),
+            ));
+            lines.push(Line::styled(
+                "  so it stays inactive until you log in graphically.",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+    }
     lines.push(Line::raw(""));
 
-    if !info.triggered_by.is_empty() {
+    // User units run as the user unless they say otherwise
+    if !info.active_state.is_empty()
+        && (matches!(app.tab, Tab::System | Tab::Timers) || !info.user.is_empty())
+    {
+        let color = if info.runs_as_root() {
+            Color::Yellow
+        } else {
+            Color::Green
+        };
         lines.push(Line::from(vec![
-            Span::styled("  Triggered by:", label_style),
-            Span::styled(format!(" {}", info.triggered_by), value_style),
+            Span::styled("  Runs as:     ", label_style),
+            Span::styled(info.runs_as(), Style::default().fg(color)),
         ]));
         lines.push(Line::raw(""));
     }
 
-    if !info.documentation.is_empty() {
+    if !info.active_state.is_empty() {
         lines.push(Line::from(vec![
-            Span::styled("  Docs:        ", label_style),
-            Span::styled(&info.documentation, Style::default().fg(Color::Blue)),
+            Span::styled("  Capabilities:", label_style),
+            Span::styled(
+                format!(" {}", info.capability_summary()),
+                Style::default().fg(if info.has_all_capabilities() {
+                    Color::Yellow
+                } else {
+                    Color::Green
+                }),
+            ),
+        ]));
+        let (filter_text, filter_color) = if info.syscall_filter {
+            ("filtered (SystemCallFilter=)", Color::Green)
+        } else {
+            ("any syscall allowed", Color::Yellow)
+        };
+        lines.push(Line::from(vec![
+            Span::styled("  Syscalls:    ", label_style),
+            Span::styled(filter_text, Style::default().fg(filter_color)),
         ]));
         lines.push(Line::raw(""));
     }
 
-    if !info.fragment_path.is_empty() {
+    if !info.confinement.is_empty() {
+        let color = if info.is_unconfined() {
+            Color::Yellow
+        } else {
+            Color::Green
+        };
         lines.push(Line::from(vec![
-            Span::styled("  Unit file:   ", label_style),
-            Span::styled(&info.fragment_path, Style::default().fg(Color::DarkGray)),
+            Span::styled("  Confinement: ", label_style),
+            Span::styled(&info.confinement, Style::default().fg(color)),
         ]));
+        if info.is_unconfined() && info.network_facing {
+            lines.push(Line::styled(
+                "  ⚠ Network-facing daemon running without an LSM profile",
+                Style::default().fg(Color::Red),
+            ));
+        }
         lines.push(Line::raw(""));
     }
 
-    lines.push(Line::from(Span::styled(
-        "  [Esc/i] Close",
-        Style::default().fg(Color::DarkGray),
-    )));
-
-    let modal_width = 64u16.min(area.width.saturating_sub(4));
-    let modal_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
-    let modal_area = Rect {
-        x: (area.width.saturating_sub(modal_width)) / 2,
-        y: (area.height.saturating_sub(modal_height)) / 2,
-        width: modal_width,
-        height: modal_height,
-    };
-
-    frame.render_widget(Clear, modal_area);
+    if info.dbus_activatable {
+        let name = if info.bus_name.is_empty() {
+            "a D-Bus service file".to_string()
+        } else {
+            info.bus_name.clone()
+        };
+        lines.push(Line::from(vec![
+            Span::styled("  D-Bus:       ", label_style),
+            Span::styled(
+                format!("activated on demand via {name}"),
+                Style::default().fg(Color::Yellow),
+            ),
+        ]));
+        lines.push(Line::styled(
+            "  Disabling won't stop bus activation; mask the unit to prevent it.",
+            Style::default().fg(Color::DarkGray),
+        ));
+        lines.push(Line::raw(""));
+    }
 
-    let block = Block::default()
-        .title(" Service Info ")
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+    // Ordering: what this waits for, and what waits for it
+    for (label, units) in [
+        ("  After:       ", &info.after),
+        ("  Before:      ", &info.before),
+    ] {
+        if !units.is_empty() {
+            lines.push(Line::from(vec![
+                Span::styled(label, label_style),
+                Span::styled(unit_list(units, 8), value_style),
+            ]));
+        }
+    }
+    if !info.after.is_empty() || !info.before.is_empty() {
+        lines.push(Line::raw(""));
+    }
 
-    let paragraph = Paragraph::new(lines)
-        .block(block)
-        .wrap(Wrap { trim: false });
+    if !info.triggered_by.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled("  Triggered by:", label_style),
+            Span::styled(format!(" {}", info.triggered_by), value_style),
+        ]));
+        if !info.watched_paths.is_empty() {
+            lines.push(Line::styled("  Watches:", label_style));
+            for watch in &info.watched_paths {
+                lines.push(Line::styled(
+                    format!("    {watch}"),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            if let Some(at) = info.path_triggered {
+                lines.push(Line::from(vec![
+                    Span::styled("  Last fired:  ", label_style),
+                    Span::styled(format_ago(at, drift::now_secs()), value_style),
+                ]));
+            }
+            lines.push(Line::styled(
+                "  A change to these paths starts the service even if it's disabled.",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        if let Some(at) = info.next_run {
+            lines.push(Line::from(vec![
+                Span::styled("  Next run:    ", label_style),
+                Span::styled(
+                    format!(
+                        "{} ({})",
+                        format_until(at, drift::now_secs()),
+                        drift::format_date(at)
+                    ),
+                    value_style,
+                ),
+            ]));
+            if info.active_state == "inactive" {
+                lines.push(Line::styled(
+                    "  Inactive between runs is normal for timer-driven services.",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+        }
+        lines.push(Line::raw(""));
+    }
+
+    if !info.socket_listens.is_empty() {
+        lines.push(Line::styled("  Listens on:", label_style));
+        for listen in &info.socket_listens {
+            lines.push(Line::styled(
+                format!("    {listen}"),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        lines.push(Line::styled(
+            "  A connection starts the service even if it's disabled; disable the socket too.",
+            Style::default().fg(Color::DarkGray),
+        ));
+        lines.push(Line::raw(""));
+    }
+
+    if !info.documentation.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled("  Docs:        ", label_style),
+            Span::styled(&info.documentation, Style::default().fg(Color::Blue)),
+        ]));
+        lines.push(Line::raw(""));
+    }
+
+    if info.is_sysv() {
+        lines.push(Line::from(vec![
+            Span::styled("  SysV script: ", label_style),
+            Span::styled(&info.source_path, Style::default().fg(Color::Yellow)),
+        ]));
+        for note in [
+            "  systemd-sysv-generator wraps this legacy init script, so the",
+            "  unit file is a stub: the real start/stop logic is in the script.",
+        ] {
+            lines.push(Line::styled(note, Style::default().fg(Color::DarkGray)));
+        }
+        lines.push(Line::raw(""));
+    } else if info.is_generated() {
+        let source = if info.source_path.is_empty() {
+            "a systemd generator".to_string()
+        } else {
+            format!("{} by a systemd generator", info.source_path)
+        };
+        lines.push(Line::from(vec![
+            Span::styled("  Generated:   ", label_style),
+            Span::styled(format!("from {source}"), Style::default().fg(Color::Yellow)),
+        ]));
+        lines.push(Line::styled(
+            "  Can't be enabled or disabled directly; change the source and reboot",
+            Style::default().fg(Color::DarkGray),
+        ));
+        lines.push(Line::styled(
+            "  (or run daemon-reload) to regenerate it.",
+            Style::default().fg(Color::DarkGray),
+        ));
+        lines.push(Line::raw(""));
+    }
+
+    if !info.fragment_path.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled("  Unit file:   ", label_style),
+            Span::styled(&info.fragment_path, Style::default().fg(Color::DarkGray)),
+        ]));
+        lines.push(Line::raw(""));
+    }
+
+    // What `enable` actually created on disk
+    if !info.enablement_links.is_empty() {
+        lines.push(Line::styled("  Enabled via:", label_style));
+        for (link, target) in &info.enablement_links {
+            lines.push(Line::styled(
+                format!("    {link}"),
+                Style::default().fg(Color::DarkGray),
+            ));
+            lines.push(Line::styled(
+                format!("      → {target}"),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        lines.push(Line::raw(""));
+    }
+
+    lines.push(Line::from(Span::styled(
+        "  [j/k] Scroll  [|] Unit file in pager  [Esc/i] Close",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    // Size by the wrapped height, which grows as the terminal narrows
+    let modal_width = 64u16.min(area.width.saturating_sub(4));
+    let rows = wrapped_rows(&lines, modal_width.saturating_sub(2));
+    let modal_height = (rows + 2).min(area.height.saturating_sub(4));
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(" Service Info ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let max_scroll = rows.saturating_sub(modal_height.saturating_sub(2));
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll(((app.modal_scroll as u16).min(max_scroll), 0));
+    frame.render_widget(paragraph, modal_area);
+    max_scroll
+}
+
+/// One line of `systemctl cat` output: the `# /path` headers it puts
+/// above the file and each drop-in, comments, `[Section]`s, and
+/// `Key=value` with the key picked out.
+fn unit_file_line(line: &str) -> Line<'_> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("# /") {
+        return Line::styled(
+            line,
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+    }
+    if trimmed.starts_with('#') || trimmed.starts_with(';') {
+        return Line::styled(line, Style::default().fg(Color::DarkGray));
+    }
+    if trimmed.starts_with('[') && trimmed.ends_with(']') {
+        return Line::styled(
+            line,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        );
+    }
+    match line.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() && !key.contains(' ') => Line::from(vec![
+            Span::styled(key, Style::default().fg(Color::Green)),
+            Span::styled("=", Style::default().fg(Color::DarkGray)),
+            Span::raw(value),
+        ]),
+        _ => Line::raw(line),
+    }
+}
+
+fn render_unit_file_modal(frame: &mut Frame, app: &App) -> u16 {
+    let Some((unit, text)) = &app.unit_file else {
+        return 0;
+    };
+    let mut lines: Vec<Line> = text.lines().map(unit_file_line).collect();
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        "[j/k] Scroll  [g/G] Top/end  [|] Pager  [Esc/c] Close",
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    let area = frame.area();
+    let modal_width = 100u16.min(area.width.saturating_sub(4));
+    let rows = wrapped_rows(&lines, modal_width.saturating_sub(2));
+    let modal_height = (rows + 2).min(area.height.saturating_sub(4));
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(format!(" {unit} "))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let max_scroll = rows.saturating_sub(modal_height.saturating_sub(2));
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((app.modal_scroll.min(usize::from(max_scroll)) as u16, 0));
+    frame.render_widget(paragraph, modal_area);
+    max_scroll
+}
+
+/// Rows `lines` take once wrapped to `width` columns. Counts whole
+/// characters per row, so word wrapping may need a row more now and then.
+fn wrapped_rows(lines: &[Line], width: u16) -> u16 {
+    let width = usize::from(width.max(1));
+    lines
+        .iter()
+        .map(|line| line.width().div_ceil(width).max(1) as u16)
+        .sum()
+}
+
+fn render_compare_modal(frame: &mut Frame, app: &App) -> u16 {
+    let host = match &app.remote {
+        Some(remote) => remote.host.as_str(),
+        None => return 0,
+    };
+
+    let state_text = |state: Option<bool>| match state {
+        Some(true) => ("enabled", Color::Green),
+        Some(false) => ("disabled", Color::Red),
+        None => ("not installed", Color::DarkGray),
+    };
+
+    let mut lines = vec![Line::raw("")];
+
+    if app.tab != Tab::System {
+        lines.push(Line::raw(
+            "  Remote comparison covers system services only.",
+        ));
+        lines.push(Line::raw("  Switch to the System tab to compare."));
+    } else {
+        let diffs = app.comparison();
+        if diffs.is_empty() {
+            lines.push(Line::styled(
+                "  No enablement differences.",
+                Style::default().fg(Color::Green),
+            ));
+        } else {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {:<30} {:<14} ", "Service", "Local"),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(host, Style::default().add_modifier(Modifier::BOLD)),
+            ]));
+            for diff in &diffs {
+                let (local, local_color) = state_text(diff.local);
+                let (remote, remote_color) = state_text(diff.remote);
+                lines.push(Line::from(vec![
+                    Span::raw(format!("  {} ", text::pad(&diff.name, 30))),
+                    Span::styled(format!("{local:<14} "), Style::default().fg(local_color)),
+                    Span::styled(remote, Style::default().fg(remote_color)),
+                ]));
+            }
+        }
+    }
+
+    lines.push(Line::raw(""));
+    lines.push(Line::from(Span::styled(
+        "  [j/k] Scroll  [Esc/C] Close",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let area = frame.area();
+    let modal_width = 72u16.min(area.width.saturating_sub(4));
+    let modal_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    let max_scroll = (lines.len() as u16).saturating_sub(modal_height.saturating_sub(2));
+    let scroll = (app.modal_scroll as u16).min(max_scroll);
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(format!(" Compare with {host} "))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll, 0));
+    frame.render_widget(paragraph, modal_area);
+    max_scroll
+}
+
+/// Space-separated unit names, capped at `max` with a "+N more" suffix.
+fn unit_list(units: &[String], max: usize) -> String {
+    let mut text = units
+        .iter()
+        .take(max)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if units.len() > max {
+        text.push_str(&format!(" +{} more", units.len() - max));
+    }
+    text
+}
+
+fn render_results_modal(frame: &mut Frame, app: &App) -> u16 {
+    let mut lines = vec![Line::raw("")];
+    let mut any_slow = false;
+
+    for result in &app.results {
+        let (icon, color) = if result.success {
+            ("✓", Color::Green)
+        } else {
+            ("✗", Color::Red)
+        };
+        let repeats = app.slow_stops.get(&result.service).copied().unwrap_or(0);
+        let repeat_note = if result.slow_stop() && repeats > 1 {
+            format!("  (slow stop {repeats}× this session)")
+        } else {
+            String::new()
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {icon} "), Style::default().fg(color)),
+            Span::raw(&result.service),
+            Span::styled(repeat_note, Style::default().fg(Color::Yellow)),
+        ]));
+
+        let mut spans = vec![Span::raw("      ")];
+        for (idx, (step, elapsed)) in result.timings.iter().enumerate() {
+            if idx > 0 {
+                spans.push(Span::styled(" · ", Style::default().fg(Color::DarkGray)));
+            }
+            let slow = *elapsed >= SLOW_STEP;
+            any_slow |= slow;
+            spans.push(Span::styled(
+                format!("{step} {:.1} s", elapsed.as_secs_f64()),
+                Style::default().fg(if slow { Color::Yellow } else { Color::DarkGray }),
+            ));
+        }
+        lines.push(Line::from(spans));
+        if !result.success {
+            // The whole of systemctl's stderr, one row per line it wrote
+            for line in result.message.lines() {
+                lines.push(Line::styled(
+                    format!("      {line}"),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+        }
+        if let Some(note) = result.retry_note() {
+            lines.push(Line::styled(
+                format!("      {note}"),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        if let Some(fix) = result.error.as_ref().and_then(|e| e.fix()) {
+            lines.push(Line::styled(
+                format!("      → {fix}"),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+    }
+
+    if any_slow {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            "  Slow stops usually mean the unit waits out TimeoutStopSec.",
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    lines.push(Line::raw(""));
+    let mut footer = vec![Span::styled(
+        "  [j/k] Scroll  [|] Pager  [Esc/L] Close",
+        Style::default().fg(Color::DarkGray),
+    )];
+    if app.has_failed_changes() {
+        footer.push(Span::styled(
+            "  [R] Retry failed",
+            Style::default().fg(Color::Green),
+        ));
+    }
+    if any_slow {
+        footer.push(Span::styled(
+            "  [T] Shorter stop timeout",
+            Style::default().fg(Color::Green),
+        ));
+    }
+    lines.push(Line::from(footer));
+
+    let area = frame.area();
+    let modal_width = 70u16.min(area.width.saturating_sub(4));
+    let rows = wrapped_rows(&lines, modal_width.saturating_sub(2));
+    let modal_height = (rows + 2).min(area.height.saturating_sub(4));
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    let max_scroll = rows.saturating_sub(modal_height.saturating_sub(2));
+    let scroll = (app.modal_scroll as u16).min(max_scroll);
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(" Last Apply ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0));
+    frame.render_widget(paragraph, modal_area);
+    max_scroll
+}
+
+fn render_next_boot_modal(frame: &mut Frame, app: &App) -> u16 {
+    let next = &app.next_boot;
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = vec![
+        Line::raw(""),
+        Line::styled(format!("  Starts at boot ({}):", next.at_boot.len()), bold),
+    ];
+    for name in &next.at_boot {
+        let (mark, color) = if next.gained.contains(name) {
+            ("+", Color::Green)
+        } else {
+            (" ", Color::White)
+        };
+        lines.push(Line::styled(
+            format!("  {mark} {name}"),
+            Style::default().fg(color),
+        ));
+    }
+    if !next.lost.is_empty() {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled("  No longer starts at boot:", bold));
+        for name in &next.lost {
+            lines.push(Line::styled(
+                format!("  - {name}"),
+                Style::default().fg(Color::Red),
+            ));
+        }
+    }
+    if !next.on_demand.is_empty() {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            "  Disabled, but still started on demand:",
+            bold,
+        ));
+        for (service, trigger) in &next.on_demand {
+            lines.push(Line::from(vec![
+                Span::styled(format!("    {service}"), Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    format!("  by {trigger}"),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]));
+        }
+    }
+
+    lines.push(Line::raw(""));
+    lines.push(Line::from(Span::styled(
+        "  Dependencies pulled in by these aren't listed.  [j/k] Scroll  [Esc/n] Close",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let area = frame.area();
+    let modal_width = 80u16.min(area.width.saturating_sub(4));
+    let modal_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    let max_scroll = (lines.len() as u16).saturating_sub(modal_height.saturating_sub(2));
+    let scroll = (app.modal_scroll as u16).min(max_scroll);
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(" If You Reboot Now ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll, 0));
+    frame.render_widget(paragraph, modal_area);
+    max_scroll
+}
+
+fn render_drift_modal(frame: &mut Frame, app: &App) -> u16 {
+    let mut lines = vec![Line::raw("")];
+
+    match app.drift.since {
+        None => lines.push(Line::raw(
+            "  First snapshot taken; changes will be reported next launch.",
+        )),
+        Some(_) if app.drift.changes.is_empty() => lines.push(Line::styled(
+            "  No changes outside comma-services.",
+            Style::default().fg(Color::Green),
+        )),
+        Some(since) => {
+            lines.push(Line::styled(
+                format!(
+                    "  Changed outside comma-services since {}:",
+                    drift::format_date(since)
+                ),
+                Style::default().add_modifier(Modifier::BOLD),
+            ));
+            lines.push(Line::raw(""));
+            for change in &app.drift.changes {
+                let (verb, color) = match (change.local, change.remote) {
+                    (_, Some(true)) => ("enabled", Color::Green),
+                    (Some(true), Some(false)) => ("disabled", Color::Red),
+                    (Some(_), None) => ("removed", Color::DarkGray),
+                    _ => ("changed", Color::Yellow),
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(format!("  {verb:<9} "), Style::default().fg(color)),
+                    Span::raw(&change.name),
+                ]));
+            }
+        }
+    }
+
+    lines.push(Line::raw(""));
+    lines.push(Line::from(Span::styled(
+        "  [j/k] Scroll  [|] Pager  [Esc/D] Close",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let area = frame.area();
+    let modal_width = 64u16.min(area.width.saturating_sub(4));
+    let modal_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    let max_scroll = (lines.len() as u16).saturating_sub(modal_height.saturating_sub(2));
+    let scroll = (app.modal_scroll as u16).min(max_scroll);
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(" Outside Changes ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll, 0));
+    frame.render_widget(paragraph, modal_area);
+    max_scroll
+}
+
+fn render_jobs_modal(frame: &mut Frame, app: &App) -> u16 {
+    let mut lines = vec![Line::raw("")];
+
+    if app.queued_jobs.is_empty() {
+        lines.push(Line::styled(
+            "  No jobs queued.",
+            Style::default().fg(Color::Green),
+        ));
+    } else {
+        lines.push(Line::styled(
+            format!("  {:>6}  {:<8} {:<8} UNIT", "JOB", "TYPE", "STATE"),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        for job in &app.queued_jobs {
+            let color = if job.state == "running" {
+                Color::Yellow
+            } else {
+                Color::DarkGray
+            };
+            lines.push(Line::styled(
+                format!(
+                    "  {:>6}  {:<8} {:<8} {}",
+                    job.id, job.job_type, job.state, job.unit
+                ),
+                Style::default().fg(color),
+            ));
+        }
+    }
+
+    lines.push(Line::raw(""));
+    lines.push(Line::from(Span::styled(
+        "  [j/k] Scroll  [Esc/J] Close",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let area = frame.area();
+    let modal_width = 70u16.min(area.width.saturating_sub(4));
+    let modal_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    let max_scroll = (lines.len() as u16).saturating_sub(modal_height.saturating_sub(2));
+    let scroll = (app.modal_scroll as u16).min(max_scroll);
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(" Queued Jobs ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll, 0));
+    frame.render_widget(paragraph, modal_area);
+    max_scroll
+}
+
+fn render_logs_modal(frame: &mut Frame, app: &App) -> u16 {
+    let Some(logs) = &app.logs else {
+        return 0;
+    };
+    let mut lines = vec![Line::raw("")];
+
+    match logs.problem {
+        Some(problem) if logs.lines.is_empty() => {
+            let user = std::env::var("USER").unwrap_or_default();
+            lines.push(Line::styled(
+                format!("  {}", problem.explanation()),
+                Style::default().fg(Color::Yellow),
+            ));
+            lines.push(Line::raw(""));
+            lines.push(Line::styled(
+                "  To fix it, run:",
+                Style::default().add_modifier(Modifier::BOLD),
+            ));
+            lines.push(Line::styled(
+                format!("    {}", problem.fix_command(&user)),
+                Style::default().fg(Color::White),
+            ));
+        }
+        _ if logs.lines.is_empty() => {
+            lines.push(Line::styled(
+                "  No log entries.",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        _ => {
+            lines.extend(logs.lines.iter().map(|line| Line::raw(format!(" {line}"))));
+        }
+    }
+
+    lines.push(Line::raw(""));
+    let mut footer = vec![Span::styled(
+        "  [j/k] Scroll  [f] Follow  [|] Pager  [Esc/o] Close",
+        Style::default().fg(Color::DarkGray),
+    )];
+    if logs.problem.is_some() && logs.lines.is_empty() {
+        footer.insert(
+            0,
+            Span::styled("  [F] Fix with pkexec", Style::default().fg(Color::Green)),
+        );
+    }
+    lines.push(Line::from(footer));
+
+    let area = frame.area();
+    let modal_width = 100u16.min(area.width.saturating_sub(4));
+    let modal_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    let max_scroll = (lines.len() as u16).saturating_sub(modal_height.saturating_sub(2));
+    let scroll = max_scroll.saturating_sub(app.modal_scroll as u16);
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(format!(" Logs: {} ", logs.unit))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll, 0));
+    frame.render_widget(paragraph, modal_area);
+    max_scroll
+}
+
+/// Full-screen `journalctl -f`, newest at the bottom unless paused.
+fn render_follow(frame: &mut Frame, app: &App) {
+    let Some(follow) = &app.follow else {
+        return;
+    };
+    let area = frame.area();
+    frame.render_widget(Clear, area);
+
+    let [title_area, log_area, footer_area] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Fill(1),
+        Constraint::Length(1),
+    ])
+    .areas(area);
+
+    let mut title = vec![
+        Span::styled(
+            format!(" Following {} ", follow.unit),
+            Style::default().fg(Color::Black).bg(Color::Cyan),
+        ),
+        Span::styled(
+            format!("  priority: {}", journal::priority_name(follow.priority)),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ];
+    if follow.paused {
+        title.push(Span::styled(
+            format!("  ⏸ paused, {} new", follow.unseen),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    frame.render_widget(Paragraph::new(Line::from(title)), title_area);
+
+    let block = Block::default().borders(Borders::TOP | Borders::BOTTOM);
+    let inner = block.inner(log_area);
+    frame.render_widget(block, log_area);
+
+    let height = inner.height as usize;
+    let back = app
+        .modal_scroll
+        .min(follow.lines.len().saturating_sub(height));
+    let end = follow.lines.len() - back;
+    let start = end.saturating_sub(height);
+    let lines: Vec<Line> = if follow.lines.is_empty() {
+        vec![Line::styled(
+            " Waiting for log lines...",
+            Style::default().fg(Color::DarkGray),
+        )]
+    } else {
+        follow.lines[start..end]
+            .iter()
+            .map(|line| Line::raw(format!(" {line}")))
+            .collect()
+    };
+    frame.render_widget(Paragraph::new(lines), inner);
+
+    let footer = Line::from(Span::styled(
+        " [Space] Pause  [p] Priority  [j/k] Scroll  [G] Resume  [Esc/f] Back to list",
+        Style::default().fg(Color::DarkGray),
+    ));
+    frame.render_widget(Paragraph::new(footer), footer_area);
+}
+
+fn render_advisor_modal(frame: &mut Frame, app: &App) {
+    let mut lines = vec![
+        Line::raw(""),
+        Line::styled(
+            format!(
+                " Enabled services a minimal {} can do without:",
+                app.advisor_desktop.label()
+            ),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Line::raw(""),
+    ];
+    for (idx, candidate) in app.advisor.iter().enumerate() {
+        let selected = idx == app.advisor_cursor;
+        let staged = app.toggled.contains(&candidate.service);
+        let checkbox = if staged { "[✗]" } else { "[ ]" };
+        let mut style = Style::default();
+        if staged {
+            style = style.fg(Color::Yellow);
+        }
+        if selected {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        let cursor_indicator = if selected { ">" } else { " " };
+        lines.push(Line::styled(
+            format!("{cursor_indicator} {checkbox} {}", candidate.service),
+            style,
+        ));
+        lines.push(Line::styled(
+            format!("        {}", candidate.reason),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::from(vec![
+        Span::styled(" [Space] Stage disable", Style::default().fg(Color::Yellow)),
+        Span::raw("  "),
+        Span::styled("[Enter] Review", Style::default().fg(Color::Green)),
+        Span::raw("  "),
+        Span::styled("[Esc] Close", Style::default().fg(Color::DarkGray)),
+    ]));
+
+    let area = frame.area();
+    let modal_width = 90u16.min(area.width.saturating_sub(4));
+    let modal_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    // Keep the highlighted suggestion (two lines each) in view
+    let visible = modal_height.saturating_sub(2) as usize;
+    let cursor_line = 3 + app.advisor_cursor * 2 + 1;
+    let scroll = cursor_line.saturating_sub(visible.saturating_sub(2)) as u16;
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(" Minimal-System Advisor ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll, 0));
+    frame.render_widget(paragraph, modal_area);
+}
+
+fn render_deps_modal(frame: &mut Frame, app: &App) {
+    let Some(tree) = &app.deps else {
+        return;
+    };
+    let visible = tree.visible();
+    let area = frame.area();
+    let modal_width = 80u16.min(area.width.saturating_sub(4));
+    // A blank row above and below the tree, then the footer
+    let modal_height = (visible.len() as u16 + 5).min(area.height.saturating_sub(4));
+    let rows = (modal_height as usize).saturating_sub(5);
+    let start = scroll_window(tree.cursor, 0, rows, visible.len());
+
+    let mut lines = vec![Line::raw("")];
+    for (row, &idx) in visible.iter().enumerate().skip(start).take(rows) {
+        let node = &tree.nodes[idx];
+        let selected = row == tree.cursor;
+        let fold = match (tree.has_children(idx), tree.collapsed.contains(&idx)) {
+            (false, _) => " ",
+            (true, true) => "▸",
+            (true, false) => "▾",
+        };
+        let (dot, dot_color) = match node.state {
+            Some(DepState::Active) => ("●", Color::Green),
+            Some(DepState::Failed) => ("✗", Color::Red),
+            Some(DepState::Inactive) => ("○", Color::DarkGray),
+            None => (" ", Color::Reset),
+        };
+        let mut style = Style::default();
+        if node.depth == 0 {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        if selected {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        let cursor_indicator = if selected { ">" } else { " " };
+        lines.push(Line::from(vec![
+            Span::raw(format!("{cursor_indicator} {}", "  ".repeat(node.depth))),
+            Span::styled(fold, Style::default().fg(Color::Cyan)),
+            Span::raw(" "),
+            Span::styled(dot, Style::default().fg(dot_color)),
+            Span::raw(" "),
+            Span::styled(node.unit.as_str(), style),
+        ]));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::from(Span::styled(
+        " [j/k] Move  [Enter/Space] Fold  [h/l] Collapse/expand  [Esc/d] Close",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(" Dependencies ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    frame.render_widget(Paragraph::new(lines).block(block), modal_area);
+}
+
+/// Width of the bar that scales each start time against the slowest.
+const BLAME_BAR: usize = 20;
+
+fn render_blame_modal(frame: &mut Frame, app: &App) {
+    let slowest = app.blame.first().map_or(0.0, |(_, secs)| *secs);
+    let area = frame.area();
+    let modal_width = 90u16.min(area.width.saturating_sub(4));
+    // Three heading rows and two footer rows around the entries
+    let modal_height = (app.blame.len() as u16 + 7).min(area.height.saturating_sub(4));
+    let rows = (modal_height as usize).saturating_sub(7);
+    let start = scroll_window(app.blame_cursor, 0, rows, app.blame.len());
+
+    let mut lines = vec![
+        Line::raw(""),
+        Line::styled(
+            " Start time this boot (systemd-analyze blame):",
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Line::raw(""),
+    ];
+    // Build only the entries in view: every unit file can have a time
+    for (idx, (unit, secs)) in app.blame.iter().enumerate().skip(start).take(rows) {
+        let selected = idx == app.blame_cursor;
+        let svc = app.service_named(unit);
+        let checkbox = match svc {
+            Some(svc) if svc.generated => "[·]",
+            Some(svc) if svc.enabled => "[✓]",
+            _ => "[ ]",
+        };
+        let mut style = Style::default();
+        if svc.is_some_and(|svc| app.is_service_dirty(svc)) {
+            style = style.fg(Color::Yellow);
+        }
+        if selected {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        let filled = if slowest > 0.0 {
+            ((secs / slowest) * BLAME_BAR as f64).ceil() as usize
+        } else {
+            0
+        };
+        let cursor_indicator = if selected { ">" } else { " " };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!(
+                    "{cursor_indicator} {checkbox} {:>10} ",
+                    boot::format_secs(*secs)
+                ),
+                style,
+            ),
+            Span::styled(
+                format!("{:<BLAME_BAR$} ", "█".repeat(filled.min(BLAME_BAR))),
+                Style::default().fg(Color::Magenta),
+            ),
+            Span::styled(unit.clone(), style),
+        ]));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::from(vec![
+        Span::styled(" [Space] Stage toggle", Style::default().fg(Color::Yellow)),
+        Span::raw("  "),
+        Span::styled("[Enter] Review", Style::default().fg(Color::Green)),
+        Span::raw("  "),
+        Span::styled("[Esc] Close", Style::default().fg(Color::DarkGray)),
+    ]));
+
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(format!(
+            " Boot Blame ({}/{}) ",
+            app.blame_cursor + 1,
+            app.blame.len()
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let paragraph = Paragraph::new(lines).block(block);
+    frame.render_widget(paragraph, modal_area);
+}
+
+fn render_profiles_modal(frame: &mut Frame, app: &App) {
+    let mut lines = vec![Line::raw("")];
+    if app.profiles.is_empty() {
+        lines.push(Line::styled(
+            format!(" No {} profiles saved yet.", app.scope().name()),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    for (idx, profile) in app.profiles.iter().enumerate() {
+        let selected = idx == app.profile_cursor;
+        let mut style = Style::default();
+        if selected {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        let cursor_indicator = if selected { ">" } else { " " };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{cursor_indicator} {}", text::pad(&profile.name, 28)),
+                style,
+            ),
+            Span::styled(
+                format!(
+                    " {} services, saved {}",
+                    profile.services,
+                    drift::format_date(profile.saved_at)
+                ),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::from(vec![
+        Span::styled(" [Enter] Restore", Style::default().fg(Color::Green)),
+        Span::raw("  "),
+        Span::styled("[s] Save current", Style::default().fg(Color::Cyan)),
+        Span::raw("  "),
+        Span::styled("[d] Delete", Style::default().fg(Color::Red)),
+        Span::raw("  "),
+        Span::styled("[Esc] Close", Style::default().fg(Color::DarkGray)),
+    ]));
+
+    let area = frame.area();
+    let modal_width = 72u16.min(area.width.saturating_sub(4));
+    let modal_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    // Keep the highlighted profile in view
+    let visible = modal_height.saturating_sub(2) as usize;
+    let scroll = (1 + app.profile_cursor + 1).saturating_sub(visible.saturating_sub(2)) as u16;
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(format!(" Profiles ({}) ", app.scope().name()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll, 0));
     frame.render_widget(paragraph, modal_area);
 }
 
+fn render_scheduler_modal(frame: &mut Frame, app: &App) {
+    let mut lines = vec![
+        Line::raw(""),
+        Line::styled(
+            " Switch the sched-ext CPU scheduler:",
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Line::raw(""),
+    ];
+    for (idx, name) in app.schedulers.iter().enumerate() {
+        let selected = idx == app.sched_cursor;
+        let style = if selected {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        let cursor_indicator = if selected { ">" } else { " " };
+        let current = if app.sched_ext.as_ref() == Some(name) {
+            "  (running)"
+        } else {
+            ""
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{cursor_indicator} {name}"), style),
+            Span::styled(current, Style::default().fg(Color::Green)),
+        ]));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::from(vec![
+        Span::styled(" [Enter] Switch", Style::default().fg(Color::Green)),
+        Span::raw("  "),
+        Span::styled("[Esc] Cancel", Style::default().fg(Color::DarkGray)),
+    ]));
+
+    let area = frame.area();
+    let modal_width = 44u16.min(area.width.saturating_sub(4));
+    let modal_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(" scx_loader ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    frame.render_widget(Paragraph::new(lines).block(block), modal_area);
+}
+
+fn render_hook_modal(frame: &mut Frame, app: &App) {
+    let Some(hook) = &app.hook else {
+        return;
+    };
+    let mut lines = vec![
+        Line::raw(""),
+        Line::from(vec![
+            Span::styled(
+                "  Restart after upgrades of ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(&hook.package, Style::default().fg(Color::Yellow)),
+        ]),
+        Line::styled(
+            format!("  {}", hook.path.display()),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Line::raw(""),
+    ];
+    lines.extend(
+        hook.body
+            .lines()
+            .map(|line| Line::styled(format!("  {line}"), Style::default().fg(Color::White))),
+    );
+    lines.push(Line::raw(""));
+    lines.push(Line::from(vec![
+        Span::styled(
+            " [Enter] Install (pkexec)",
+            Style::default().fg(Color::Green),
+        ),
+        Span::raw("  "),
+        Span::styled("[Esc] Cancel", Style::default().fg(Color::DarkGray)),
+    ]));
+
+    let area = frame.area();
+    let modal_width = 76u16.min(area.width.saturating_sub(4));
+    let modal_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(" Package Hook ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    frame.render_widget(Paragraph::new(lines).block(block), modal_area);
+}
+
+fn render_template_modal(frame: &mut Frame, app: &App) {
+    let mut lines = vec![Line::raw("")];
+
+    match &app.wizard {
+        None => {
+            lines.push(Line::styled(
+                " Create a user service from a template:",
+                Style::default().add_modifier(Modifier::BOLD),
+            ));
+            lines.push(Line::raw(""));
+            for (idx, template) in TEMPLATES.iter().enumerate() {
+                let selected = idx == app.template_cursor;
+                let style = if selected {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                let cursor_indicator = if selected { ">" } else { " " };
+                lines.push(Line::styled(
+                    format!("{cursor_indicator} {}", template.name),
+                    style,
+                ));
+                lines.push(Line::styled(
+                    format!("    {}", template.summary),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            if !app.cron_jobs.is_empty() {
+                lines.push(Line::raw(""));
+                lines.push(Line::styled(
+                    " Or turn a cron job into a timer:",
+                    Style::default().add_modifier(Modifier::BOLD),
+                ));
+            }
+            for (idx, job) in app.cron_jobs.iter().enumerate() {
+                let selected = TEMPLATES.len() + idx == app.template_cursor;
+                let style = if selected {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                let cursor_indicator = if selected { ">" } else { " " };
+                lines.push(Line::styled(
+                    format!("{cursor_indicator} {}", job.command),
+                    style,
+                ));
+                lines.push(Line::styled(
+                    format!("    {}", job.calendar),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            lines.push(Line::raw(""));
+            lines.push(Line::from(vec![
+                Span::styled(" [Enter] Choose", Style::default().fg(Color::Green)),
+                Span::raw("  "),
+                Span::styled("[Esc] Cancel", Style::default().fg(Color::DarkGray)),
+            ]));
+        }
+        Some(wizard) => {
+            lines.push(Line::styled(
+                format!(" {}", wizard.title()),
+                Style::default().add_modifier(Modifier::BOLD),
+            ));
+            if let Some(job) = &wizard.cron {
+                lines.push(Line::styled(
+                    format!("  {}", job.entry.trim()),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            lines.push(Line::raw(""));
+            for (idx, prompt) in wizard.prompts().iter().enumerate() {
+                let (value, style) = match wizard.answers.get(idx) {
+                    Some(answer) => (answer.as_str(), Style::default()),
+                    None if idx == wizard.answers.len() => (
+                        app.prompt_input.as_str(),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    None => (
+                        wizard.defaults[idx].as_str(),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("  {:<30} ", prompt.question),
+                        Style::default().fg(Color::Cyan),
+                    ),
+                    Span::styled(value.to_string(), style),
+                ]));
+            }
+            lines.push(Line::raw(""));
+            lines.push(Line::styled(
+                "  Files go to ~/.config/systemd/user/",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+    }
+
+    let area = frame.area();
+    let modal_width = 72u16.min(area.width.saturating_sub(4));
+    let modal_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let modal_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(" New User Service ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    frame.render_widget(Paragraph::new(lines).block(block), modal_area);
+}
+
 fn render_confirm_modal(frame: &mut Frame, app: &App) {
     let changes = app.pending_changes();
     if changes.is_empty() {
@@ -345,7 +2405,25 @@ fn render_confirm_modal(frame: &mut Frame, app: &App) {
 
     let area = frame.area();
     let modal_width = 50u16.min(area.width.saturating_sub(4));
-    let modal_height = (changes.len() as u16 + 7).min(area.height.saturating_sub(4));
+    let jobs_warning = if app.queued_jobs.is_empty() { 0 } else { 3 };
+    let dm_conflict = app.display_manager_conflict();
+    let dm_warning = if dm_conflict.is_some() { 3 } else { 0 };
+    let memory_line = if app.memory_freed > 0 { 2 } else { 0 };
+    let masking = changes.iter().any(|c| c.action == ChangeAction::Mask);
+    let mask_warning = if masking { 3 } else { 0 };
+    let by_category = app.changes_by_category();
+    let category_lines = match by_category.len() {
+        0 => 0,
+        n => n as u16 + 2,
+    };
+    let modal_height = (changes.len() as u16
+        + 7
+        + jobs_warning
+        + dm_warning
+        + memory_line
+        + mask_warning
+        + category_lines)
+        .min(area.height.saturating_sub(4));
     let modal_area = Rect {
         x: (area.width.saturating_sub(modal_width)) / 2,
         y: (area.height.saturating_sub(modal_height)) / 2,
@@ -365,25 +2443,129 @@ fn render_confirm_modal(frame: &mut Frame, app: &App) {
     ];
 
     for change in &changes {
-        let (icon, action_text) = match change.action {
-            ChangeAction::Enable => ("●", "Enable + Start"),
-            ChangeAction::Disable => ("●", "Disable + Stop"),
+        let (icon, action_text) = match (&change.action, &change.scope) {
+            (ChangeAction::Enable | ChangeAction::EnableOnly, ServiceScope::Global) => {
+                ("●", "Enable for all users")
+            }
+            (ChangeAction::Disable | ChangeAction::DisableOnly, ServiceScope::Global) => {
+                ("●", "Disable for all users")
+            }
+            (ChangeAction::Mask, ServiceScope::Global) => ("⊘", "Mask for all users"),
+            (ChangeAction::Unmask, ServiceScope::Global) => ("○", "Unmask for all users"),
+            (ChangeAction::Enable, _) => ("●", "Enable + Start"),
+            (ChangeAction::Disable, _) => ("●", "Disable + Stop"),
+            (ChangeAction::EnableOnly, _) => ("●", "Enable at boot only"),
+            (ChangeAction::DisableOnly, _) => ("●", "Disable, keep running"),
+            (ChangeAction::Start, _) => ("▶", "Start now only"),
+            (ChangeAction::Stop, _) => ("■", "Stop now only"),
+            (ChangeAction::Restart, _) => ("↻", "Restart"),
+            (ChangeAction::Mask, _) => ("⊘", "Mask + Stop"),
+            (ChangeAction::Unmask, _) => ("○", "Unmask"),
         };
         let color = match change.action {
-            ChangeAction::Enable => Color::Green,
-            ChangeAction::Disable => Color::Red,
+            ChangeAction::Enable | ChangeAction::EnableOnly | ChangeAction::Start => Color::Green,
+            ChangeAction::Disable
+            | ChangeAction::DisableOnly
+            | ChangeAction::Stop
+            | ChangeAction::Mask => Color::Red,
+            ChangeAction::Restart => Color::Yellow,
+            ChangeAction::Unmask => Color::Cyan,
         };
         lines.push(Line::from(vec![
             Span::raw("  "),
             Span::styled(icon, Style::default().fg(color)),
             Span::raw(format!(" {action_text}  {}", change.service)),
+            Span::styled(
+                if change.runtime {
+                    "  (until reboot)"
+                } else {
+                    ""
+                },
+                Style::default().fg(Color::Magenta),
+            ),
         ]));
     }
 
+    if !by_category.is_empty() {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            " By category:",
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        for (name, on, off) in &by_category {
+            let mut spans = vec![Span::raw(format!("  {name}: "))];
+            if *on > 0 {
+                spans.push(Span::styled(
+                    format!("{on} on"),
+                    Style::default().fg(Color::Green),
+                ));
+            }
+            if *on > 0 && *off > 0 {
+                spans.push(Span::raw(", "));
+            }
+            if *off > 0 {
+                spans.push(Span::styled(
+                    format!("{off} off"),
+                    Style::default().fg(Color::Red),
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+    }
+
+    if app.memory_freed > 0 {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            format!(" ≈ {} will be freed", format_bytes(app.memory_freed)),
+            Style::default().fg(Color::Green),
+        ));
+    }
+
+    if !app.queued_jobs.is_empty() {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            format!(
+                " ⚠ {} systemd job(s) already queued;",
+                app.queued_jobs.len()
+            ),
+            Style::default().fg(Color::Yellow),
+        ));
+        lines.push(Line::styled(
+            "   these changes will wait behind them.",
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+
+    if masking {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            " ⚠ Masking is stronger than disabling: nothing can",
+            Style::default().fg(Color::Red),
+        ));
+        lines.push(Line::styled(
+            "   start a masked unit, not even as a dependency.",
+            Style::default().fg(Color::Red),
+        ));
+    }
+
+    if let Some((new, current)) = &dm_conflict {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            format!(" ⚠ {current} still holds display-manager.service;"),
+            Style::default().fg(Color::Red),
+        ));
+        lines.push(Line::styled(
+            format!("   disable it too or enabling {new} fails."),
+            Style::default().fg(Color::Red),
+        ));
+    }
+
     lines.push(Line::raw(""));
     lines.push(Line::from(vec![
         Span::styled(" [Enter] Confirm", Style::default().fg(Color::Green)),
-        Span::raw("    "),
+        Span::raw("  "),
+        Span::styled("[t] Schedule", Style::default().fg(Color::Cyan)),
+        Span::raw("  "),
         Span::styled("[Esc] Cancel", Style::default().fg(Color::DarkGray)),
     ]));
 
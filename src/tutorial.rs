@@ -0,0 +1,65 @@
+/// Where the `--tutorial` walkthrough is. A step is done once the app
+/// shows the user did it, and the overlay moves on to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Move,
+    Toggle,
+    Info,
+    Apply,
+    Done,
+}
+
+/// The app's state as far as the steps care.
+#[derive(Debug, Default)]
+pub struct Progress {
+    pub moved: bool,
+    pub staged: bool,
+    pub info_open: bool,
+    pub applied: bool,
+}
+
+/// Steps before Done, for the "2/4" in the overlay title.
+pub const STEPS: usize = 4;
+
+impl Step {
+    pub fn advance(self, progress: &Progress) -> Step {
+        match self {
+            Step::Move if progress.moved => Step::Toggle,
+            Step::Toggle if progress.staged => Step::Info,
+            Step::Info if progress.info_open => Step::Apply,
+            Step::Apply if progress.applied => Step::Done,
+            step => step,
+        }
+    }
+
+    pub fn number(self) -> usize {
+        self as usize + 1
+    }
+
+    pub fn hint(self) -> &'static str {
+        match self {
+            Step::Move => "Services are grouped by what they do. Move with j/k (or the arrow keys); h/l fold a group.",
+            Step::Toggle => "Press Space on a service to turn it on or off at boot. Nothing happens yet: the change is only staged, and the row turns yellow.",
+            Step::Info => "Not sure what a service is for? Press i for its description, then Esc to close it.",
+            Step::Apply => "Press Enter to review what's staged, and Enter again to apply it. On a real system polkit asks for your password once.",
+            Step::Done => "That's the whole workflow. This is a pretend machine, so explore freely; q quits. Run comma-services without --tutorial for the real thing.",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_wait_their_turn() {
+        let staged = Progress {
+            staged: true,
+            ..Progress::default()
+        };
+        // Staging before moving doesn't skip ahead
+        assert_eq!(Step::Move.advance(&staged), Step::Move);
+        assert_eq!(Step::Toggle.advance(&staged), Step::Info);
+        assert_eq!(Step::Done.number(), STEPS + 1);
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::systemd::ServiceScope;
+use crate::trace;
+
+/// Properties every cached query also asks for: the unit's name and
+/// the files whose mtimes decide whether the rest is still current.
+const STAMP_PROPS: &[&str] = &["Id", "FragmentPath", "DropInPaths"];
+
+/// Unit-file properties from `systemctl show`, good for as long as the
+/// unit file and its drop-ins keep the mtime they had.
+struct Entry {
+    stamp: SystemTime,
+    props: BTreeMap<String, String>,
+}
+
+static CACHE: Mutex<Option<HashMap<(&'static str, String), Entry>>> = Mutex::new(None);
+
+/// Newest mtime of a unit's fragment and drop-ins. Units without a
+/// fragment (transient, generated in /run) aren't cached.
+fn stamp(props: &BTreeMap<String, String>) -> Option<SystemTime> {
+    let fragment = props.get("FragmentPath").filter(|path| !path.is_empty())?;
+    let dropins = props.get("DropInPaths").map(String::as_str).unwrap_or("");
+    std::iter::once(fragment.as_str())
+        .chain(dropins.split_whitespace())
+        .map(|path| {
+            std::fs::metadata(Path::new(path))
+                .and_then(|m| m.modified())
+                .ok()
+        })
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .max()
+}
+
+/// `show` blocks for `names` with at least `props`. Units whose files are
+/// unchanged come from the cache; the rest go to `query` in one batch,
+/// which gets the names and a comma-separated property list. While a
+/// session is being recorded or replayed, everything is queried.
+pub fn show(
+    scope: &ServiceScope,
+    names: &[String],
+    props: &[&str],
+    query: impl FnOnce(&[String], &str) -> String,
+) -> Vec<String> {
+    let Ok(mut guard) = CACHE.lock() else {
+        return Vec::new();
+    };
+    let cache = guard.get_or_insert_with(HashMap::new);
+    let mut blocks = Vec::new();
+    let mut missing = Vec::new();
+    for name in names {
+        let hit = cache
+            .get(&(scope.name(), name.clone()))
+            .filter(|entry| props.iter().all(|p| entry.props.contains_key(*p)))
+            .filter(|entry| stamp(&entry.props) == Some(entry.stamp))
+            .filter(|_| !trace::is_active());
+        match hit {
+            Some(entry) => blocks.push(render(&entry.props)),
+            None => missing.push(name.clone()),
+        }
+    }
+    if missing.is_empty() {
+        return blocks;
+    }
+
+    // Keep what earlier queries learned about these units too
+    let mut wanted: Vec<&str> = STAMP_PROPS.to_vec();
+    for name in &missing {
+        if let Some(entry) = cache.get(&(scope.name(), name.clone())) {
+            wanted.extend(entry.props.keys().map(String::as_str));
+        }
+    }
+    wanted.extend(props);
+    wanted.sort_unstable();
+    wanted.dedup();
+    let stdout = query(&missing, &wanted.join(","));
+
+    for block in stdout.split("\n\n") {
+        let props: BTreeMap<String, String> = block
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let Some(id) = props.get("Id").cloned() else {
+            continue;
+        };
+        blocks.push(render(&props));
+        if let Some(stamp) = stamp(&props) {
+            cache.insert((scope.name(), id), Entry { stamp, props });
+        }
+    }
+    blocks
+}
+
+fn render(props: &BTreeMap<String, String>) -> String {
+    props
+        .iter()
+        .map(|(key, value)| format!("{key}={value}\n"))
+        .collect()
+}
+
+/// Forget everything, for when units may have changed without touching
+/// their files' mtimes (daemon-reload, a new drop-in directory).
+pub fn clear() {
+    if let Ok(mut cache) = CACHE.lock() {
+        *cache = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_show_reuses_unchanged_units() {
+        let fragment = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+        let names = vec!["cache-test.service".to_string()];
+        let mut queries = 0;
+        let mut query = |names: &[String], props: &str| {
+            queries += 1;
+            assert!(props.contains("FragmentPath") && props.contains("User"));
+            format!(
+                "Id={}\nFragmentPath={fragment}\nDropInPaths=\nUser=cups\n",
+                names[0]
+            )
+        };
+        let first = show(&ServiceScope::Global, &names, &["User"], &mut query);
+        let second = show(&ServiceScope::Global, &names, &["User"], &mut query);
+        assert_eq!(first, second);
+        assert!(first[0].contains("User=cups\n"));
+        assert_eq!(queries, 1);
+    }
+}